@@ -9,8 +9,7 @@ use bitcoin::Transaction;
 use bitcoin::{PrivateKey, XOnlyPublicKey};
 use bitcoinkernel::{
     prelude::*, Block, BlockSpentOutputs, BlockTreeEntry, ChainType, ChainstateManager,
-    ChainstateManagerBuilder, Context, ContextBuilder, KernelError, Log, Logger,
-    TransactionSpentOutputsRef,
+    ChainstateManagerBuilder, Context, KernelError, Log, Logger, TransactionSpentOutputsRef,
 };
 use env_logger::Builder;
 use log::LevelFilter;
@@ -106,6 +105,7 @@ impl SilentPaymentScanner {
     ) -> Result<(), ScanError> {
         let spent_outputs: BlockSpentOutputs = chainman.read_spent_outputs(block_index)?;
         let block: Block = chainman.read_block_data(block_index)?;
+        spent_outputs.matches_block(&block)?;
 
         for (index, tx_spent_output) in spent_outputs.iter().enumerate() {
             let tx_index = index + 1;
@@ -136,7 +136,7 @@ impl SilentPaymentScanner {
         let mut inputs = Vec::new();
         for (index, coin) in tx_spent_outputs.coins().enumerate() {
             inputs.push(TransactionInput {
-                prevout_script: coin.output().script_pubkey().to_bytes(),
+                prevout_script: coin.script_pubkey().to_bytes(),
                 script_sig: tx.input[index].script_sig.to_bytes(),
                 witness: tx.input[index].witness.to_vec(),
                 outpoint: (
@@ -260,12 +260,7 @@ fn setup_logging() -> Result<Logger, KernelError> {
 }
 
 fn create_context() -> Arc<Context> {
-    Arc::new(
-        ContextBuilder::new()
-            .chain_type(ChainType::Regtest)
-            .build()
-            .unwrap(),
-    )
+    Context::for_chain(ChainType::Regtest).unwrap()
 }
 
 // silent payment txid:
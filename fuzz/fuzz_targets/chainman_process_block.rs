@@ -8,20 +8,19 @@ use arbitrary::Arbitrary;
 
 use bitcoinkernel::{
     disable_logging, prelude::*, Block, BlockValidationStateRef, ChainType,
-    ChainstateManagerBuilder, Context, ContextBuilder, KernelError, ValidationMode,
+    ChainstateManagerBuilder, Context, ContextBuilder, KernelError, KernelNotificationHandler,
+    ProcessBlockResult, ValidationMode,
 };
 
+struct NoopNotifications;
+
+impl KernelNotificationHandler for NoopNotifications {}
+
 fn create_context(chain_type: ChainType) -> Arc<Context> {
     Arc::new(
         ContextBuilder::new()
             .chain_type(chain_type)
-            .with_block_tip_notification(|_state, _block_index, _verification_progress| {})
-            .with_header_tip_notification(|_state, _height, _timestamp, _presync| {})
-            .with_progress_notification(|_title, _progress, _resume_possible| {})
-            .with_warning_set_notification(|_warning, _message| {})
-            .with_warning_unset_notification(|_warning| {})
-            .with_flush_error_notification(|_message| {})
-            .with_fatal_error_notification(|_message| {})
+            .with_notifications(NoopNotifications)
             .with_block_checked_validation(|_block, state: BlockValidationStateRef<'_>| {
                 assert!(state.mode() != ValidationMode::InternalError)
             })
@@ -34,6 +33,7 @@ fn create_context(chain_type: ChainType) -> Arc<Context> {
 pub enum FuzzChainType {
     MAINNET,
     TESTNET,
+    TESTNET4,
     REGTEST,
     SIGNET,
 }
@@ -43,6 +43,7 @@ impl Into<ChainType> for FuzzChainType {
         match self {
             FuzzChainType::MAINNET => ChainType::Mainnet,
             FuzzChainType::TESTNET => ChainType::Testnet,
+            FuzzChainType::TESTNET4 => ChainType::Testnet4,
             FuzzChainType::REGTEST => ChainType::Regtest,
             FuzzChainType::SIGNET => ChainType::Signet,
         }
@@ -126,7 +127,16 @@ fuzz_target!(|data: ChainstateManagerInput| {
 
     for block in data.blocks {
         if let Ok(block) = Block::try_from(block.as_slice()) {
-            let _ = chainman.process_block(&block);
+            let height = chainman.height();
+            let tip_hash = chainman.tip_hash();
+            match chainman.process_block(&block) {
+                ProcessBlockResult::Rejected => {
+                    // A rejected block must never move the tip.
+                    assert_eq!(chainman.height(), height);
+                    assert_eq!(chainman.tip_hash(), tip_hash);
+                }
+                ProcessBlockResult::NewBlock | ProcessBlockResult::Duplicate => {}
+            }
         }
     }
     drop(chainman);
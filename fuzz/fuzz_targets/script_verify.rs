@@ -4,8 +4,8 @@ use arbitrary::Arbitrary;
 use libfuzzer_sys::fuzz_target;
 
 use bitcoinkernel::{
-    verify, KernelError, PrecomputedTransactionData, ScriptPubkey, ScriptVerifyError, Transaction,
-    TxOut, VERIFY_WITNESS,
+    verify, KernelError, PrecomputedTransactionData, ScriptPubkey, ScriptVerificationFlags,
+    ScriptVerifyError, Transaction, TxOut, VERIFY_WITNESS,
 };
 
 #[derive(Debug, Arbitrary)]
@@ -53,13 +53,13 @@ fuzz_target!(|data: VerifyInput| {
         data.amount,
         &transaction,
         data.input_index,
-        data.flags,
+        data.flags.map(ScriptVerificationFlags::from),
         &tx_data,
     );
 
     match res {
         Err(KernelError::ScriptVerify(ScriptVerifyError::InvalidFlagsCombination)) => {
-            assert_eq!(data.flags.unwrap() & VERIFY_WITNESS, VERIFY_WITNESS)
+            assert!(ScriptVerificationFlags::from(data.flags.unwrap()).contains(VERIFY_WITNESS))
         }
         _ => {}
     }
@@ -0,0 +1,54 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use bitcoinkernel::{
+    prelude::*, verify, KernelError, PrecomputedTransactionData, ScriptVerificationFlags,
+    ScriptVerifyError, Transaction, TxOut,
+};
+
+#[derive(Debug, Arbitrary)]
+pub struct VerifyInput {
+    pub transaction: Transaction,
+    pub spent_outputs: Vec<TxOut>,
+    pub input_index: usize,
+    pub flags: Option<u32>,
+}
+
+fuzz_target!(|data: VerifyInput| {
+    if data.spent_outputs.is_empty() {
+        return;
+    }
+
+    // Pair every input with a spent output, so that verification runs
+    // against structurally valid transactions and matching prevouts.
+    let input_count = data.transaction.input_count();
+    let spent_outputs: Vec<TxOut> = data
+        .spent_outputs
+        .iter()
+        .cycle()
+        .take(input_count)
+        .cloned()
+        .collect();
+    let tx_data = PrecomputedTransactionData::new(&data.transaction, &spent_outputs)
+        .expect("One spent output per input should be accepted");
+
+    let input_index = data.input_index % input_count;
+    let spent = &spent_outputs[input_index];
+    let res = verify(
+        &spent.script_pubkey(),
+        Some(spent.value()),
+        &data.transaction,
+        input_index,
+        data.flags.map(ScriptVerificationFlags::from),
+        &tx_data,
+    );
+
+    assert!(!matches!(
+        res,
+        Err(KernelError::ScriptVerify(
+            ScriptVerifyError::TxInputIndex | ScriptVerifyError::SpentOutputsRequired
+        ))
+    ));
+});
@@ -122,8 +122,10 @@
 //!
 
 use std::{
+    collections::HashSet,
     ffi::c_void,
     fmt::{self, Debug, Display, Formatter},
+    io::{Read, Write},
     marker::PhantomData,
 };
 
@@ -147,16 +149,30 @@ use libbitcoinkernel_sys::{
 };
 
 use crate::{
-    c_helpers, c_serialize,
+    c_helpers, c_serialize, c_serialize_to, encode_hex,
     ffi::{
         c_helpers::present,
         sealed::{AsMutPtr, AsPtr, FromMutPtr, FromPtr},
     },
-    notifications::types::BlockValidationState,
-    state::context::ChainParams,
+    notifications::types::{BlockValidationResult, BlockValidationState},
+    state::context::{ChainParams, ChainType},
     KernelError,
 };
 
+/// Number of weight units per byte of non-witness data.
+const WITNESS_SCALE_FACTOR: usize = 4;
+
+/// Approximate bytes the kernel holds per transaction beyond its serialized
+/// data: the shared pointer in the block, its control block and the
+/// transaction's vectors and cached hashes.
+const TRANSACTION_HEAP_OVERHEAD: usize = 160;
+
+/// Approximate in-memory size of an input, excluding script and witness data.
+const INPUT_HEAP_OVERHEAD: usize = 104;
+
+/// Approximate in-memory size of an output, excluding script data.
+const OUTPUT_HEAP_OVERHEAD: usize = 40;
+
 /// Bitmask of flags controlling which checks [`Block::check`] performs.
 pub type BlockCheckFlags = btck_BlockCheckFlags;
 
@@ -172,6 +188,32 @@ pub const BLOCK_CHECK_MERKLE: BlockCheckFlags = btck_BlockCheckFlags_MERKLE;
 /// Enable all available context-free block checks (PoW + Merkle root).
 pub const BLOCK_CHECK_ALL: BlockCheckFlags = btck_BlockCheckFlags_ALL;
 
+/// Returns the block subsidy in satoshis for a block at `height` on `chain`.
+///
+/// The subsidy starts at 50 BTC and halves every
+/// [`subsidy_halving_interval`](ChainType::subsidy_halving_interval) blocks,
+/// following Bitcoin Core's `GetBlockSubsidy`. It is zero after 64 halvings
+/// and for negative heights.
+///
+/// # Example
+/// ```no_run
+/// use bitcoinkernel::{block_subsidy, ChainType, COIN};
+///
+/// assert_eq!(block_subsidy(0, ChainType::Mainnet), 50 * COIN);
+/// assert_eq!(block_subsidy(840_000, ChainType::Mainnet), 3 * COIN + COIN / 8);
+/// assert_eq!(block_subsidy(150, ChainType::Regtest), 25 * COIN);
+/// ```
+pub fn block_subsidy(height: i32, chain: ChainType) -> i64 {
+    if height < 0 {
+        return 0;
+    }
+    let halvings = height / chain.subsidy_halving_interval();
+    if halvings >= 64 {
+        return 0;
+    }
+    (50 * COIN) >> halvings
+}
+
 /// Outcome of [`Block::check`].
 ///
 /// On failure, the [`BlockValidationState`] carries details that can be
@@ -185,7 +227,12 @@ pub enum BlockCheckResult {
     Invalid(BlockValidationState),
 }
 
-use super::transaction::{TransactionRef, TxOutRef};
+use super::{
+    encoding::{compact_size_len, decode_display_hex, decode_transaction, read_block},
+    pow::{cmp_uint256, CompactTarget},
+    script::{HasScriptPubkey, ScriptPubkeyRef},
+    transaction::{TransactionExt, TransactionRef, TxInExt, TxOutExt, TxOutRef, TxidExt, COIN},
+};
 
 /// Common operations for block hashes, implemented by both owned and borrowed types.
 ///
@@ -226,6 +273,24 @@ pub trait BlockHashExt: AsPtr<btck_BlockHash> + Display {
         unsafe { btck_block_hash_to_bytes(self.as_ptr(), output.as_mut_ptr()) };
         output
     }
+
+    /// Returns the block hash as a hex string in display byte order.
+    ///
+    /// This is the form shown by block explorers and RPC, the same as the
+    /// [`Display`] output. [`BlockHash::from_display_hex`] parses it back.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{prelude::*, BlockHash};
+    ///
+    /// let mut bytes = [0u8; 32];
+    /// bytes[31] = 0xab;
+    /// let hash = BlockHash::from(bytes);
+    /// assert!(hash.to_display_hex().starts_with("ab00"));
+    /// ```
+    fn to_display_hex(&self) -> String {
+        self.to_string()
+    }
 }
 
 /// A 32-byte hash uniquely identifying a block.
@@ -240,7 +305,12 @@ pub trait BlockHashExt: AsPtr<btck_BlockHash> + Display {
 /// - **Display byte order**: Reversed for human-readable hex strings
 ///
 /// The [`to_bytes`](BlockHashExt::to_bytes) method returns internal byte order,
-/// while [`Display`] formatting shows the reversed bytes.
+/// while [`Display`] formatting shows the reversed bytes. For hand-built logs
+/// or JSON, prefer the explicitly named
+/// [`to_display_hex`](BlockHashExt::to_display_hex) and
+/// [`from_display_hex`](BlockHash::from_display_hex), which always use display
+/// byte order. Hex encoding the result of `to_bytes` gives internal byte order
+/// and will not match explorers or RPC.
 ///
 /// # Thread Safety
 ///
@@ -307,6 +377,31 @@ impl BlockHash {
         }
     }
 
+    /// Parses a block hash from a hex string in display byte order.
+    ///
+    /// Block explorers and RPC show block hashes with their bytes reversed
+    /// relative to the internal order taken by [`BlockHash::new`]. This accepts
+    /// that display form, upper or lower case, and reverses it.
+    ///
+    /// # Errors
+    /// * [`KernelError::InvalidLength`] - If the string is not 64 characters long
+    /// * [`KernelError::InvalidHexCharacter`] - If the string contains a
+    ///   character that is not a hex digit
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, BlockHash, KernelError};
+    /// let genesis = BlockHash::from_display_hex(
+    ///     "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+    /// )?;
+    /// assert_eq!(genesis.to_bytes()[31], 0x00);
+    /// assert_eq!(genesis.to_bytes()[0], 0x6f);
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn from_display_hex(hex: &str) -> Result<Self, KernelError> {
+        Ok(BlockHash::from(decode_display_hex(hex)?))
+    }
+
     /// Creates a borrowed reference to this block hash.
     ///
     /// This allows converting from an owned [`BlockHash`] to a [`BlockHashRef`]
@@ -361,6 +456,14 @@ impl TryFrom<&[u8]> for BlockHash {
     }
 }
 
+impl TryFrom<&str> for BlockHash {
+    type Error = KernelError;
+
+    fn try_from(hex: &str) -> Result<Self, Self::Error> {
+        BlockHash::from_display_hex(hex)
+    }
+}
+
 impl From<BlockHash> for [u8; 32] {
     fn from(block_hash: BlockHash) -> Self {
         block_hash.to_bytes()
@@ -381,6 +484,18 @@ impl PartialEq for BlockHash {
 
 impl Eq for BlockHash {}
 
+impl PartialEq<[u8; 32]> for BlockHash {
+    fn eq(&self, other: &[u8; 32]) -> bool {
+        self.to_bytes() == *other
+    }
+}
+
+impl PartialEq<BlockHash> for [u8; 32] {
+    fn eq(&self, other: &BlockHash) -> bool {
+        *self == other.to_bytes()
+    }
+}
+
 impl Debug for BlockHash {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "BlockHash({:?})", self.to_bytes())
@@ -424,6 +539,14 @@ impl<'a> BlockHashRef<'a> {
             inner: unsafe { btck_block_hash_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> BlockHash {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_BlockHash> for BlockHashRef<'a> {
@@ -492,7 +615,8 @@ pub trait BlockHeaderExt: AsPtr<btck_BlockHeader> {
         unsafe { btck_block_header_get_timestamp(self.as_ptr()) }
     }
 
-    // Get the nBits from the header
+    /// Get the nBits from the header, the proof-of-work target in compact
+    /// form. Decode it with [`CompactTarget`].
     fn bits(&self) -> u32 {
         unsafe { btck_block_header_get_bits(self.as_ptr()) }
     }
@@ -507,6 +631,31 @@ pub trait BlockHeaderExt: AsPtr<btck_BlockHeader> {
         unsafe { btck_block_header_get_nonce(self.as_ptr()) }
     }
 
+    /// Returns `true` if the header's hash meets the proof-of-work target
+    /// encoded in its [`bits`](Self::bits).
+    ///
+    /// Mirrors Bitcoin Core's `CheckProofOfWork`: the target must be positive,
+    /// must not overflow and must not exceed the
+    /// [`pow_limit`](ChainType::pow_limit) of `chain_type`. Only the target is
+    /// checked, not whether it is the one the chain requires at this height,
+    /// and on signet the block solution is not checked either.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, BlockHeader, ChainType};
+    /// # fn example(header: &BlockHeader) {
+    /// if !header.check_proof_of_work(ChainType::Mainnet) {
+    ///     println!("Rejecting header {}", header.hash());
+    /// }
+    /// # }
+    /// ```
+    fn check_proof_of_work(&self, chain_type: ChainType) -> bool {
+        match CompactTarget::from_consensus(self.bits()).derive_target(&chain_type.pow_limit()) {
+            Some(target) => cmp_uint256(&self.hash().to_bytes(), &target).is_le(),
+            None => false,
+        }
+    }
+
     /// Serializes the block header to Bitcoin wire format.
     ///
     /// Encodes the 8o-byte block header according to Bitcoin consensus rules.
@@ -716,6 +865,14 @@ impl<'a> BlockHeaderRef<'a> {
             inner: unsafe { btck_block_header_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> BlockHeader {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_BlockHeader> for BlockHeaderRef<'a> {
@@ -845,6 +1002,38 @@ impl Block {
         }
     }
 
+    /// Reads one block in Bitcoin wire format from `reader`.
+    ///
+    /// The header and transactions are parsed only far enough to find where
+    /// the block ends, so exactly one block's worth of bytes is consumed and
+    /// the reader is left positioned at whatever follows. Blocks can thus be
+    /// read one after another from a file or socket without loading the whole
+    /// source into memory. Wrap unbuffered sources such as a [`std::fs::File`]
+    /// in a [`std::io::BufReader`], since the block is read in small pieces.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if:
+    /// - The reader returns an error or ends before the block is complete
+    /// - The block exceeds the maximum serialized block size
+    /// - The data is not a valid block
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, KernelError};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut reader = std::io::BufReader::new(std::fs::File::open("blocks.dat")?);
+    /// let first = Block::consensus_decode(&mut reader)?;
+    /// let second = Block::consensus_decode(&mut reader)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn consensus_decode<R: Read + ?Sized>(reader: &mut R) -> Result<Self, KernelError> {
+        let raw_block = read_block(reader)?.ok_or_else(|| {
+            KernelError::Internal("Failed to read block: unexpected end of stream".to_string())
+        })?;
+        Block::new(&raw_block)
+    }
+
     /// Returns the hash of this block.
     ///
     /// This is the double SHA256 hash of the block header, which serves as
@@ -897,6 +1086,16 @@ impl Block {
         unsafe { BlockHeader::from_ptr(btck_block_get_header(self.inner)) }
     }
 
+    /// Returns `true` if the block's hash meets the proof-of-work target in
+    /// its header.
+    ///
+    /// See [`BlockHeaderExt::check_proof_of_work`]. [`check`](Self::check)
+    /// with [`BLOCK_CHECK_POW`] runs the same check in the kernel, along with
+    /// the other context-free block checks.
+    pub fn check_proof_of_work(&self, chain_type: ChainType) -> bool {
+        self.header().check_proof_of_work(chain_type)
+    }
+
     /// Returns a reference to the transaction at the specified index.
     ///
     /// # Arguments
@@ -960,6 +1159,201 @@ impl Block {
         })
     }
 
+    /// Serializes the block to Bitcoin wire format as a lowercase hex string.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if serialization fails.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, KernelError};
+    /// # fn example(block: &Block) -> Result<(), KernelError> {
+    /// println!("{}", block.consensus_encode_hex()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn consensus_encode_hex(&self) -> Result<String, KernelError> {
+        Ok(encode_hex(&self.consensus_encode()?))
+    }
+
+    /// Serializes the block to Bitcoin wire format, writing it into `writer`.
+    ///
+    /// Unlike [`consensus_encode`](Self::consensus_encode), this streams the
+    /// encoded bytes directly into the writer without allocating an
+    /// intermediate buffer.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if serialization fails or
+    /// the writer returns an error.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, KernelError};
+    /// # fn example(block: &Block) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = std::fs::File::create("block.dat")?;
+    /// block.consensus_encode_to(&mut file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn consensus_encode_to<W: Write + ?Sized>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), KernelError> {
+        c_serialize_to(writer, |callback, user_data| unsafe {
+            btck_block_to_bytes(self.inner, callback, user_data)
+        })
+    }
+
+    /// Returns the total signature operation cost of all transactions in this block.
+    ///
+    /// Sums [`TransactionExt::sigop_cost`](crate::core::TransactionExt::sigop_cost)
+    /// over every transaction, using the given spent outputs for the
+    /// non-coinbase transactions. The consensus limit for a block is 80,000.
+    ///
+    /// # Arguments
+    /// * `spent_outputs` - The spent outputs (undo data) of this block, as read with
+    ///   [`ChainstateManager::read_spent_outputs`](crate::ChainstateManager::read_spent_outputs)
+    ///
+    /// # Errors
+    /// * [`KernelError::MismatchedOutputsSize`] - If the spent outputs do not
+    ///   correspond to the block's transactions and inputs
+    /// * [`KernelError::SerializationFailed`] - If a transaction cannot be
+    ///   serialized for inspection
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ChainstateManager, BlockTreeEntry, KernelError};
+    /// # fn example(chainman: &ChainstateManager, entry: &BlockTreeEntry) -> Result<(), KernelError> {
+    /// let block = chainman.read_block_data(entry)?;
+    /// let spent_outputs = chainman.read_spent_outputs(entry)?;
+    /// println!("Sigop cost: {}", block.total_sigop_cost(&spent_outputs)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn total_sigop_cost(
+        &self,
+        spent_outputs: &impl BlockSpentOutputsExt,
+    ) -> Result<u32, KernelError> {
+        if spent_outputs.count() + 1 != self.transaction_count() {
+            return Err(KernelError::MismatchedOutputsSize);
+        }
+
+        let mut cost = 0;
+        for (i, tx) in self.transactions().enumerate() {
+            let prevouts: Vec<TxOutRef<'_>> = match i.checked_sub(1) {
+                Some(index) => {
+                    let tx_spent = spent_outputs.transaction_spent_outputs(index)?;
+                    tx_spent.coins().map(|coin| coin.output()).collect()
+                }
+                None => Vec::new(),
+            };
+            cost += tx.sigop_cost(&prevouts)?;
+        }
+        Ok(cost)
+    }
+
+    /// Returns the size of the block's serialization in bytes, including
+    /// witness data.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if serialization fails.
+    pub fn total_size(&self) -> Result<usize, KernelError> {
+        Ok(self.consensus_encode()?.len())
+    }
+
+    /// Returns the size of the block's serialization in bytes without witness
+    /// data, as seen by nodes that predate segwit.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if a transaction cannot be
+    /// serialized for inspection, or [`KernelError::Internal`] if the block
+    /// cannot be serialized.
+    pub fn stripped_size(&self) -> Result<usize, KernelError> {
+        let mut witness_size = 0;
+        for tx in self.transactions() {
+            let data = tx.consensus_encode()?;
+            witness_size += decode_transaction(&data)?.witness_size;
+        }
+        Ok(self.total_size()? - witness_size)
+    }
+
+    /// Returns the block's weight in weight units.
+    ///
+    /// The weight is three times the [`stripped_size`](Self::stripped_size)
+    /// plus the [`total_size`](Self::total_size), as defined by BIP 141. The
+    /// consensus limit for a block is 4,000,000.
+    ///
+    /// # Errors
+    /// Returns the errors of [`stripped_size`](Self::stripped_size).
+    pub fn weight(&self) -> Result<usize, KernelError> {
+        let total_size = self.total_size()?;
+        let stripped_size = self.stripped_size()?;
+        Ok(stripped_size * (WITNESS_SCALE_FACTOR - 1) + total_size)
+    }
+
+    /// Returns an estimate of the bytes of memory the kernel holds for this
+    /// block.
+    ///
+    /// The estimate is the [`total_size`](Self::total_size) plus a fixed
+    /// overhead per transaction, input and output for the kernel's in-memory
+    /// representation. It is meant for weighing blocks in a cache, not as an
+    /// exact measurement.
+    ///
+    /// # Errors
+    /// Returns the errors of [`total_size`](Self::total_size).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, KernelError};
+    /// # fn example(blocks: &[Block]) -> Result<(), KernelError> {
+    /// let mut cached_bytes = 0;
+    /// for block in blocks {
+    ///     cached_bytes += block.heap_size_estimate()?;
+    /// }
+    /// println!("Cache holds about {} bytes", cached_bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn heap_size_estimate(&self) -> Result<usize, KernelError> {
+        let mut estimate = self.total_size()?;
+        for tx in self.transactions() {
+            estimate += TRANSACTION_HEAP_OVERHEAD
+                + tx.input_count() * INPUT_HEAP_OVERHEAD
+                + tx.output_count() * OUTPUT_HEAP_OVERHEAD;
+        }
+        Ok(estimate)
+    }
+
+    /// Checks that the block's weight agrees with the weights of its parts.
+    ///
+    /// Compares [`weight`](Self::weight), computed from the serialized block,
+    /// against the header and transaction count weighed as non-witness data
+    /// plus the sum of each transaction's
+    /// [`TransactionExt::weight`](crate::core::TransactionExt::weight). A
+    /// mismatch indicates a serialization bug, so this is intended for tests
+    /// and debug assertions.
+    ///
+    /// # Errors
+    /// Returns the errors of [`weight`](Self::weight).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, KernelError};
+    /// # fn example(block: &Block) -> Result<(), KernelError> {
+    /// debug_assert!(block.verify_weight_invariant()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify_weight_invariant(&self) -> Result<bool, KernelError> {
+        let header_size = self.header().consensus_encode()?.len();
+        let count_size = compact_size_len(self.transaction_count());
+        let mut expected = (header_size + count_size) * WITNESS_SCALE_FACTOR;
+        for tx in self.transactions() {
+            expected += tx.weight()?;
+        }
+        Ok(self.weight()? == expected)
+    }
+
     /// Returns an iterator over all transactions in this block.
     ///
     /// The iterator yields [`TransactionRef`] instances that borrow from this block.
@@ -1024,6 +1418,90 @@ impl Block {
             BlockCheckResult::Invalid(state)
         }
     }
+
+    /// Checks the structural rules of `CheckBlock` that do not depend on the
+    /// chain.
+    ///
+    /// Verifies that no transaction appears twice, that the block has at least
+    /// one transaction, and that the first one, and only the first one, is a
+    /// coinbase with a single input spending the null outpoint. Duplicate
+    /// transactions can leave the merkle root unchanged (CVE-2012-2459), so a
+    /// block failing that check must not be treated as invalid by hash, only
+    /// as malformed.
+    ///
+    /// Use this to pre-screen untrusted blocks before passing them to
+    /// [`ChainstateManager::process_block`](crate::ChainstateManager::process_block).
+    /// [`check`](Self::check) covers the full set of context-free rules.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::InvalidBlock`] carrying
+    /// [`BlockValidationResult::Mutated`] if the block contains a duplicate
+    /// transaction, and [`BlockValidationResult::Consensus`] if the block has
+    /// no transactions, does not start with a coinbase or contains a second
+    /// coinbase.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, KernelError};
+    /// # fn example(raw_block: &[u8]) -> Result<(), KernelError> {
+    /// let block = Block::new(raw_block)?;
+    /// block.validate_structure()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_structure(&self) -> Result<(), KernelError> {
+        // Checked first, as `CheckBlock` does through the merkle root.
+        let mut txids = HashSet::with_capacity(self.transaction_count());
+        for tx in self.transactions() {
+            if !txids.insert(tx.txid().to_bytes()) {
+                return Err(KernelError::InvalidBlock(BlockValidationResult::Mutated));
+            }
+        }
+
+        let is_coinbase = |tx: TransactionRef<'_>| {
+            tx.input_count() == 1 && tx.input(0).is_ok_and(|input| input.is_coinbase_input())
+        };
+        let coinbase = self
+            .transaction(0)
+            .map_err(|_| KernelError::InvalidBlock(BlockValidationResult::Consensus))?;
+        if !is_coinbase(coinbase) || self.transactions().skip(1).any(is_coinbase) {
+            return Err(KernelError::InvalidBlock(BlockValidationResult::Consensus));
+        }
+        Ok(())
+    }
+
+    /// Checks that the coinbase does not claim more than the block may pay out.
+    ///
+    /// Returns `true` if the sum of the coinbase transaction's outputs is at
+    /// most the [`block_subsidy`] for `height` on `chain` plus `fees`, the
+    /// total fee paid by the block's other transactions. Returns `false` if it
+    /// claims more, or if the block has no transactions.
+    ///
+    /// # Arguments
+    /// * `height` - The height of this block
+    /// * `fees` - The total fees of the block's non-coinbase transactions
+    /// * `chain` - The network the block belongs to
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Block, BlockSpentOutputs, ChainType, KernelError};
+    /// # fn example(block: &Block, spent_outputs: &BlockSpentOutputs, height: i32) -> Result<(), KernelError> {
+    /// let mut fees = 0;
+    /// for (tx, spent) in block.transactions().skip(1).zip(spent_outputs.iter()) {
+    ///     fees += tx.fee(&spent)?;
+    /// }
+    /// assert!(block.validate_coinbase_value(height, fees, ChainType::Mainnet));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_coinbase_value(&self, height: i32, fees: i64, chain: ChainType) -> bool {
+        let coinbase = match self.transaction(0) {
+            Ok(coinbase) => coinbase,
+            Err(_) => return false,
+        };
+        let value_out: i64 = coinbase.outputs().map(|output| output.value()).sum();
+        value_out <= block_subsidy(height, chain).saturating_add(fees)
+    }
 }
 
 impl AsPtr<btck_Block> for Block {
@@ -1060,6 +1538,14 @@ impl TryFrom<&[u8]> for Block {
     }
 }
 
+impl TryFrom<Vec<u8>> for Block {
+    type Error = KernelError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Block::new(&bytes)
+    }
+}
+
 impl TryFrom<Block> for Vec<u8> {
     type Error = KernelError;
 
@@ -1231,6 +1717,39 @@ pub trait BlockSpentOutputsExt: AsPtr<btck_BlockSpentOutputs> {
     fn iter(&self) -> BlockSpentOutputsIter<'_> {
         BlockSpentOutputsIter::new(unsafe { BlockSpentOutputsRef::from_ptr(self.as_ptr()) })
     }
+
+    /// Checks that these spent outputs correspond to the given block.
+    ///
+    /// The spent outputs must hold one entry per non-coinbase transaction,
+    /// and each entry must hold one coin per input of its transaction. This
+    /// catches spent outputs that were read for a different block before
+    /// they are paired with its transactions by index.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::MismatchedOutputsSize`] if the transaction count
+    /// or any transaction's coin count does not match the block.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ChainstateManager, BlockTreeEntry, KernelError};
+    /// # fn example(chainman: &ChainstateManager, entry: &BlockTreeEntry) -> Result<(), KernelError> {
+    /// let block = chainman.read_block_data(entry)?;
+    /// let spent_outputs = chainman.read_spent_outputs(entry)?;
+    /// spent_outputs.matches_block(&block)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn matches_block(&self, block: &Block) -> Result<(), KernelError> {
+        if self.count() + 1 != block.transaction_count() {
+            return Err(KernelError::MismatchedOutputsSize);
+        }
+        for (tx, tx_spent) in block.transactions().skip(1).zip(self.iter()) {
+            if tx_spent.count() != tx.input_count() {
+                return Err(KernelError::MismatchedOutputsSize);
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Spent output data for all transactions in a block.
@@ -1272,7 +1791,6 @@ pub trait BlockSpentOutputsExt: AsPtr<btck_BlockSpentOutputs> {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug)]
 pub struct BlockSpentOutputs {
     inner: *mut btck_BlockSpentOutputs,
 }
@@ -1315,6 +1833,12 @@ impl Clone for BlockSpentOutputs {
     }
 }
 
+impl Debug for BlockSpentOutputs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_block_spent_outputs(self, f)
+    }
+}
+
 impl Drop for BlockSpentOutputs {
     fn drop(&mut self) {
         unsafe { btck_block_spent_outputs_destroy(self.inner) };
@@ -1350,6 +1874,14 @@ impl<'a> BlockSpentOutputsRef<'a> {
             inner: unsafe { btck_block_spent_outputs_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> BlockSpentOutputs {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_BlockSpentOutputs> for BlockSpentOutputsRef<'a> {
@@ -1377,6 +1909,33 @@ impl<'a> Clone for BlockSpentOutputsRef<'a> {
 
 impl<'a> Copy for BlockSpentOutputsRef<'a> {}
 
+impl<'a> Debug for BlockSpentOutputsRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_block_spent_outputs(self, f)
+    }
+}
+
+/// Formats block spent outputs as their transaction and coin counts and the
+/// total value of the coins.
+fn fmt_block_spent_outputs(
+    spent_outputs: &impl BlockSpentOutputsExt,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let (coins, value) = spent_outputs
+        .iter()
+        .map(|tx_spent| spent_coins_summary(&tx_spent))
+        .fold((0, 0), |(coins, value), (tx_coins, tx_value)| {
+            (coins + tx_coins, value + tx_value)
+        });
+    write!(
+        f,
+        "BlockSpentOutputs(transactions: {}, coins: {}, value: {})",
+        spent_outputs.count(),
+        coins,
+        value
+    )
+}
+
 /// Iterator over transaction spent outputs in a block.
 ///
 /// This iterator yields [`TransactionSpentOutputsRef`] items for each transaction
@@ -1561,7 +2120,6 @@ pub trait TransactionSpentOutputsExt: AsPtr<btck_TransactionSpentOutputs> {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug)]
 pub struct TransactionSpentOutputs {
     inner: *mut btck_TransactionSpentOutputs,
 }
@@ -1604,6 +2162,12 @@ impl Clone for TransactionSpentOutputs {
     }
 }
 
+impl Debug for TransactionSpentOutputs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_transaction_spent_outputs(self, f)
+    }
+}
+
 impl Drop for TransactionSpentOutputs {
     fn drop(&mut self) {
         unsafe { btck_transaction_spent_outputs_destroy(self.inner) };
@@ -1637,6 +2201,41 @@ impl<'a> TransactionSpentOutputsRef<'a> {
             inner: unsafe { btck_transaction_spent_outputs_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> TransactionSpentOutputs {
+        self.to_owned()
+    }
+
+    /// Returns a reference to the coin at the specified input index.
+    ///
+    /// Same as [`TransactionSpentOutputsExt::coin`], except that the coin is
+    /// borrowed from the underlying spent outputs rather than from this
+    /// reference, so it may outlive it.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::OutOfBounds`] if the index is greater than or
+    /// equal to [`count`](TransactionSpentOutputsExt::count).
+    pub fn coin(&self, coin_index: usize) -> Result<CoinRef<'a>, KernelError> {
+        if coin_index >= self.count() {
+            return Err(KernelError::OutOfBounds);
+        }
+        let coin_ptr =
+            unsafe { btck_transaction_spent_outputs_get_coin_at(self.inner, coin_index) };
+        Ok(unsafe { CoinRef::from_ptr(coin_ptr) })
+    }
+
+    /// Returns an iterator over the coins spent by this transaction.
+    ///
+    /// Same as [`TransactionSpentOutputsExt::coins`], except that the coins
+    /// are borrowed from the underlying spent outputs rather than from this
+    /// reference.
+    pub fn coins(&self) -> TransactionSpentOutputsIter<'a> {
+        TransactionSpentOutputsIter::new(*self)
+    }
 }
 
 impl<'a> AsPtr<btck_TransactionSpentOutputs> for TransactionSpentOutputsRef<'a> {
@@ -1664,6 +2263,35 @@ impl<'a> Clone for TransactionSpentOutputsRef<'a> {
 
 impl<'a> Copy for TransactionSpentOutputsRef<'a> {}
 
+impl<'a> Debug for TransactionSpentOutputsRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_transaction_spent_outputs(self, f)
+    }
+}
+
+/// Formats transaction spent outputs as their coin count and the total value
+/// of the coins.
+fn fmt_transaction_spent_outputs(
+    tx_spent: &impl TransactionSpentOutputsExt,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let (coins, value) = spent_coins_summary(tx_spent);
+    write!(
+        f,
+        "TransactionSpentOutputs(coins: {}, value: {})",
+        coins, value
+    )
+}
+
+/// Returns the number of coins a transaction spent and their total value in
+/// satoshis.
+fn spent_coins_summary(tx_spent: &impl TransactionSpentOutputsExt) -> (usize, i64) {
+    (
+        tx_spent.count(),
+        tx_spent.coins().map(|coin| coin.value()).sum(),
+    )
+}
+
 /// Iterator over coins in transaction spent outputs.
 ///
 /// This iterator yields [`CoinRef`] items for each coin (UTXO) spent by a
@@ -1749,10 +2377,17 @@ impl<'a> ExactSizeIterator for TransactionSpentOutputsIter<'a> {
     }
 }
 
+/// Number of blocks a coinbase output must wait before it can be spent.
+pub const COINBASE_MATURITY: u32 = 100;
+
 /// Common operations for coins, implemented by both owned and borrowed types.
 ///
 /// This trait provides shared functionality for [`Coin`] and [`CoinRef`],
 /// allowing code to work with either owned or borrowed coin data.
+///
+/// Coins read from [`BlockSpentOutputs`] are the outputs consumed by that
+/// block. The kernel does not record a separate spend height for them: the
+/// spending height is the height of the block the spent outputs were read for.
 pub trait CoinExt: AsPtr<btck_Coin> {
     /// Returns the height of the block where this coin was created.
     ///
@@ -1807,6 +2442,63 @@ pub trait CoinExt: AsPtr<btck_Coin> {
         let output_ptr = unsafe { btck_coin_get_output(self.as_ptr()) };
         unsafe { TxOutRef::from_ptr(output_ptr) }
     }
+
+    /// Returns the amount of this coin in satoshis.
+    ///
+    /// Shorthand for `coin.output().value()`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Coin};
+    /// # fn example(coin: &Coin) {
+    /// println!("Value: {} satoshis", coin.value());
+    /// # }
+    /// ```
+    fn value(&self) -> i64 {
+        self.output().value()
+    }
+
+    /// Returns the script that must be satisfied to spend this coin.
+    ///
+    /// Shorthand for `coin.output().script_pubkey()`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Coin};
+    /// # fn example(coin: &Coin) {
+    /// println!("Script length: {} bytes", coin.script_pubkey().as_bytes().len());
+    /// # }
+    /// ```
+    fn script_pubkey(&self) -> ScriptPubkeyRef<'_> {
+        self.output().script_pubkey()
+    }
+
+    /// Returns the first block height at which this coin may be spent, if it
+    /// is a coinbase output.
+    ///
+    /// Coinbase outputs mature [`COINBASE_MATURITY`] blocks after the block
+    /// that created them.
+    ///
+    /// # Returns
+    /// * `Some(height)` - `confirmation_height + COINBASE_MATURITY` for coinbase coins
+    /// * `None` - If the coin is not a coinbase output and is spendable immediately
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Coin};
+    /// # fn example(coin: &Coin) {
+    /// if let Some(height) = coin.maturity_height() {
+    ///     println!("Coinbase output spendable from height {}", height);
+    /// }
+    /// # }
+    /// ```
+    fn maturity_height(&self) -> Option<u32> {
+        if self.is_coinbase() {
+            Some(self.confirmation_height().saturating_add(COINBASE_MATURITY))
+        } else {
+            None
+        }
+    }
 }
 
 /// A coin (UTXO) representing a spendable transaction output.
@@ -1817,8 +2509,8 @@ pub trait CoinExt: AsPtr<btck_Coin> {
 /// - Whether it came from a coinbase transaction
 ///
 /// Coins are the fundamental unit of the UTXO (Unspent Transaction Output)
-/// set. When found in spent output data, they represent outputs that have been
-/// consumed by transaction inputs.
+/// set. When found in spent output data, they are the outputs consumed by
+/// the block's transaction inputs.
 ///
 /// # Thread Safety
 ///
@@ -1837,7 +2529,6 @@ pub trait CoinExt: AsPtr<btck_Coin> {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug)]
 pub struct Coin {
     inner: *mut btck_Coin,
 }
@@ -1872,6 +2563,12 @@ impl FromMutPtr<btck_Coin> for Coin {
 
 impl CoinExt for Coin {}
 
+impl HasScriptPubkey for Coin {
+    fn locking_script(&self) -> ScriptPubkeyRef<'_> {
+        CoinExt::script_pubkey(self)
+    }
+}
+
 impl Clone for Coin {
     fn clone(&self) -> Self {
         Coin {
@@ -1880,6 +2577,12 @@ impl Clone for Coin {
     }
 }
 
+impl Debug for Coin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_coin(self, f)
+    }
+}
+
 impl Drop for Coin {
     fn drop(&mut self) {
         unsafe { btck_coin_destroy(self.inner) };
@@ -1913,6 +2616,32 @@ impl<'a> CoinRef<'a> {
             inner: unsafe { btck_coin_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> Coin {
+        self.to_owned()
+    }
+
+    /// Returns a reference to the transaction output data for this coin.
+    ///
+    /// Same as [`CoinExt::output`], except that the output is borrowed from
+    /// the underlying coin rather than from this reference, so it may outlive
+    /// it.
+    pub fn output(&self) -> TxOutRef<'a> {
+        let output_ptr = unsafe { btck_coin_get_output(self.inner) };
+        unsafe { TxOutRef::from_ptr(output_ptr) }
+    }
+
+    /// Returns the script pubkey locking this coin.
+    ///
+    /// Same as [`CoinExt::script_pubkey`], except that the script is borrowed
+    /// from the underlying coin rather than from this reference.
+    pub fn script_pubkey(&self) -> ScriptPubkeyRef<'a> {
+        self.output().script_pubkey()
+    }
 }
 
 impl<'a> AsPtr<btck_Coin> for CoinRef<'a> {
@@ -1932,6 +2661,12 @@ impl<'a> FromPtr<btck_Coin> for CoinRef<'a> {
 
 impl<'a> CoinExt for CoinRef<'a> {}
 
+impl<'a> HasScriptPubkey for CoinRef<'a> {
+    fn locking_script(&self) -> ScriptPubkeyRef<'_> {
+        CoinExt::script_pubkey(self)
+    }
+}
+
 impl<'a> Clone for CoinRef<'a> {
     fn clone(&self) -> Self {
         *self
@@ -1940,6 +2675,23 @@ impl<'a> Clone for CoinRef<'a> {
 
 impl<'a> Copy for CoinRef<'a> {}
 
+impl<'a> Debug for CoinRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_coin(self, f)
+    }
+}
+
+/// Formats a coin as its confirmation height, coinbase flag and output.
+fn fmt_coin(coin: &impl CoinExt, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+        f,
+        "Coin(confirmation_height: {}, is_coinbase: {}, output: {:?})",
+        coin.confirmation_height(),
+        coin.is_coinbase(),
+        coin.output()
+    )
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1948,7 +2700,7 @@ mod tests {
         test_owned_clone_and_send, test_owned_trait_requirements, test_ref_trait_requirements,
     };
     use crate::prelude::*;
-    use crate::{BlockValidationResult, ChainType, ValidationMode};
+    use crate::{BlockValidationResult, ChainType, TxOut, ValidationMode};
     use std::{
         fs::File,
         io::{BufRead, BufReader},
@@ -2267,6 +3019,10 @@ mod tests {
         let block_data = read_block_data();
         let block = Block::try_from(block_data[0].as_slice());
         assert!(block.is_ok());
+
+        let block = Block::try_from(block_data[0].clone()).unwrap();
+        assert_eq!(Vec::try_from(block).unwrap(), block_data[0]);
+        assert!(Block::try_from(vec![0u8; 10]).is_err());
     }
 
     #[test]
@@ -2319,6 +3075,37 @@ mod tests {
         assert_eq!(bytes3, block_data[0]);
     }
 
+    #[test]
+    fn test_block_consensus_encode_to() {
+        let block_data = read_block_data();
+        let block = Block::new(&block_data[0]).unwrap();
+
+        let mut buffer = vec![0u8; block_data[0].len()];
+        let mut cursor = std::io::Cursor::new(&mut buffer[..]);
+        block.consensus_encode_to(&mut cursor).unwrap();
+        assert_eq!(cursor.position() as usize, block_data[0].len());
+        assert_eq!(buffer, block_data[0]);
+    }
+
+    #[test]
+    fn test_block_consensus_encode_hex() {
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        assert_eq!(block.consensus_encode_hex().unwrap(), MAINNET_BLOCK_1_HEX);
+    }
+
+    #[test]
+    fn test_block_consensus_encode_to_short_buffer() {
+        let block_data = read_block_data();
+        let block = Block::new(&block_data[0]).unwrap();
+
+        let mut buffer = [0u8; 10];
+        let mut cursor = std::io::Cursor::new(&mut buffer[..]);
+        assert!(matches!(
+            block.consensus_encode_to(&mut cursor),
+            Err(KernelError::SerializationFailed)
+        ));
+    }
+
     #[test]
     fn test_block_try_into_vec() {
         let block_data = read_block_data();
@@ -2357,6 +3144,16 @@ mod tests {
         assert_ne!(block1.hash(), block2.hash());
     }
 
+    #[test]
+    fn test_block_coinbase_sigop_count() {
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        let coinbase = block.transaction(0).unwrap();
+
+        // A single pay-to-pubkey output.
+        assert_eq!(coinbase.legacy_sigop_count().unwrap(), 1);
+        assert_eq!(coinbase.sigop_cost(&Vec::<TxOut>::new()).unwrap(), 4);
+    }
+
     #[test]
     fn test_block_hash_display() {
         let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
@@ -2369,6 +3166,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_block_hash_display_hex_round_trip() {
+        const GENESIS_HEX: &str =
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+
+        let hash = BlockHash::from_display_hex(GENESIS_HEX).unwrap();
+        let bytes = hash.to_bytes();
+        assert_eq!(bytes[0], 0x6f);
+        assert_eq!(bytes[31], 0x00);
+        assert_eq!(hash.to_display_hex(), GENESIS_HEX);
+        assert_eq!(hash.as_ref().to_display_hex(), GENESIS_HEX);
+        assert_ne!(hex::encode(bytes), GENESIS_HEX);
+
+        let upper = BlockHash::from_display_hex(&GENESIS_HEX.to_uppercase()).unwrap();
+        assert_eq!(upper, hash);
+
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        let block_hash = block.hash().to_owned();
+        assert_eq!(
+            BlockHash::from_display_hex(&block_hash.to_display_hex()).unwrap(),
+            block_hash
+        );
+    }
+
+    #[test]
+    fn test_block_subsidy() {
+        assert_eq!(block_subsidy(0, ChainType::Mainnet), 50 * COIN);
+        assert_eq!(block_subsidy(209_999, ChainType::Mainnet), 50 * COIN);
+        assert_eq!(block_subsidy(210_000, ChainType::Mainnet), 25 * COIN);
+        assert_eq!(block_subsidy(840_000, ChainType::Mainnet), 312_500_000);
+        assert_eq!(block_subsidy(149, ChainType::Regtest), 50 * COIN);
+        assert_eq!(block_subsidy(150, ChainType::Regtest), 25 * COIN);
+        assert_eq!(block_subsidy(210_000 * 33, ChainType::Mainnet), 0);
+        assert_eq!(block_subsidy(i32::MAX, ChainType::Regtest), 0);
+        assert_eq!(block_subsidy(-1, ChainType::Mainnet), 0);
+    }
+
+    #[test]
+    fn test_validate_coinbase_value() {
+        // Mainnet block 1 pays exactly the 50 BTC subsidy.
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        assert!(block.validate_coinbase_value(1, 0, ChainType::Mainnet));
+        assert!(!block.validate_coinbase_value(210_000, 0, ChainType::Mainnet));
+        assert!(block.validate_coinbase_value(210_000, 25 * COIN, ChainType::Mainnet));
+        // Regtest halves far earlier.
+        assert!(!block.validate_coinbase_value(150, 0, ChainType::Regtest));
+    }
+
+    #[test]
+    fn test_block_sizes_and_weight() {
+        // Mainnet block 1 has no witness data.
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        assert_eq!(block.total_size().unwrap(), 215);
+        assert_eq!(block.stripped_size().unwrap(), 215);
+        assert_eq!(block.weight().unwrap(), 860);
+        assert!(block.verify_weight_invariant().unwrap());
+
+        // The regtest blocks carry a witness commitment and segwit spends.
+        let block_data = read_block_data();
+        let block = Block::new(&block_data[205]).unwrap();
+        assert_eq!(block.total_size().unwrap(), 644);
+        assert_eq!(block.stripped_size().unwrap(), 392);
+        assert_eq!(block.weight().unwrap(), 1820);
+        for raw_block in block_data.iter() {
+            assert!(Block::new(raw_block)
+                .unwrap()
+                .verify_weight_invariant()
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_block_heap_size_estimate() {
+        // Mainnet block 1 holds a single coinbase with one input and output.
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        assert_eq!(
+            block.heap_size_estimate().unwrap(),
+            215 + TRANSACTION_HEAP_OVERHEAD + INPUT_HEAP_OVERHEAD + OUTPUT_HEAP_OVERHEAD
+        );
+
+        let block_data = read_block_data();
+        let small = Block::new(&block_data[0]).unwrap();
+        let large = Block::new(&block_data[205]).unwrap();
+        assert!(small.heap_size_estimate().unwrap() > small.total_size().unwrap());
+        assert!(large.heap_size_estimate().unwrap() > small.heap_size_estimate().unwrap());
+    }
+
+    #[test]
+    fn test_block_hash_try_from_str() {
+        const BLOCK_1_HEX: &str =
+            "00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048";
+
+        let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
+        let hash = BlockHash::try_from(BLOCK_1_HEX).unwrap();
+        assert_eq!(hash, block.hash().to_owned());
+
+        assert!(matches!(
+            BlockHash::try_from(&BLOCK_1_HEX[2..]),
+            Err(KernelError::InvalidLength {
+                expected: 64,
+                actual: 62
+            })
+        ));
+        assert!(matches!(
+            BlockHash::try_from(BLOCK_1_HEX.replace('a', "z").as_str()),
+            Err(KernelError::InvalidHexCharacter { character: 'z', .. })
+        ));
+    }
+
+    #[test]
+    fn test_block_hash_from_display_hex_errors() {
+        assert!(matches!(
+            BlockHash::from_display_hex("00ff"),
+            Err(KernelError::InvalidLength {
+                expected: 64,
+                actual: 4
+            })
+        ));
+        assert!(matches!(
+            BlockHash::from_display_hex(""),
+            Err(KernelError::InvalidLength { .. })
+        ));
+
+        let mut invalid = "0".repeat(64);
+        invalid.replace_range(10..11, "g");
+        assert!(matches!(
+            BlockHash::from_display_hex(&invalid),
+            Err(KernelError::InvalidHexCharacter {
+                character: 'g',
+                index: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_block_hash_eq_bytes() {
+        let bytes = [0xab; 32];
+        let block_hash = BlockHash::from(bytes);
+
+        assert_eq!(block_hash, bytes);
+        assert_eq!(bytes, block_hash);
+        assert_ne!(block_hash, [0u8; 32]);
+        assert_ne!([0u8; 32], block_hash);
+        assert_eq!(block_hash, BlockHash::from(bytes));
+    }
+
     #[test]
     fn test_block_hash_ref_display() {
         let block = Block::new(hex::decode(MAINNET_BLOCK_1_HEX).unwrap().as_slice()).unwrap();
@@ -2455,6 +3398,67 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_block_validate_structure() {
+        let raw_block = hex::decode(MAINNET_BLOCK_1_HEX).unwrap();
+        let block = Block::new(&raw_block).unwrap();
+        assert!(block.validate_structure().is_ok());
+
+        let block_data = read_block_data();
+        for raw_block in &block_data {
+            assert!(Block::new(raw_block).unwrap().validate_structure().is_ok());
+        }
+
+        let header = &raw_block[..80];
+        let coinbase = block.transaction(0).unwrap().consensus_encode().unwrap();
+        let with_transactions = |transactions: &[&[u8]]| {
+            let mut raw = header.to_vec();
+            raw.push(transactions.len() as u8);
+            for tx in transactions {
+                raw.extend_from_slice(tx);
+            }
+            Block::new(&raw).unwrap()
+        };
+
+        assert!(matches!(
+            with_transactions(&[]).validate_structure(),
+            Err(KernelError::InvalidBlock(BlockValidationResult::Consensus))
+        ));
+        assert!(matches!(
+            with_transactions(&[&coinbase, &coinbase]).validate_structure(),
+            Err(KernelError::InvalidBlock(BlockValidationResult::Mutated))
+        ));
+
+        let spending_block = block_data
+            .iter()
+            .map(|raw| Block::new(raw).unwrap())
+            .find(|block| block.transaction_count() > 1)
+            .unwrap();
+        let spend = spending_block
+            .transaction(1)
+            .unwrap()
+            .consensus_encode()
+            .unwrap();
+        assert!(matches!(
+            with_transactions(&[&spend]).validate_structure(),
+            Err(KernelError::InvalidBlock(BlockValidationResult::Consensus))
+        ));
+        assert!(with_transactions(&[&coinbase, &spend])
+            .validate_structure()
+            .is_ok());
+
+        // A second, distinct coinbase is rejected like Core's `bad-cb-multiple`.
+        let other_coinbase = spending_block
+            .transaction(0)
+            .unwrap()
+            .consensus_encode()
+            .unwrap();
+        assert!(matches!(
+            with_transactions(&[&coinbase, &spend, &other_coinbase]).validate_structure(),
+            Err(KernelError::InvalidBlock(BlockValidationResult::Consensus))
+        ));
+    }
+
     #[test]
     fn check_invalid_pow() {
         const NBITS_OFFSET: usize = 4 // version
@@ -2481,6 +3485,28 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_check_proof_of_work() {
+        let raw_block = hex::decode(MAINNET_BLOCK_1_HEX).unwrap();
+        let block = Block::new(&raw_block).unwrap();
+        assert!(block.check_proof_of_work(ChainType::Mainnet));
+        assert!(block.header().check_proof_of_work(ChainType::Regtest));
+
+        // A different nonce leaves the target unchanged but breaks the hash.
+        const NONCE_OFFSET: usize = 4 + 32 + 32 + 4 + 4;
+        let mut tampered = raw_block.clone();
+        tampered[NONCE_OFFSET] ^= 0x01;
+        let tampered = Block::new(&tampered).unwrap();
+        assert!(!tampered.check_proof_of_work(ChainType::Mainnet));
+
+        // Regtest blocks meet their trivial target, which mainnet rejects as
+        // above its limit.
+        let regtest = Block::new(&read_block_data()[0]).unwrap();
+        assert_eq!(regtest.header().bits(), 0x207f_ffff);
+        assert!(regtest.check_proof_of_work(ChainType::Regtest));
+        assert!(!regtest.check_proof_of_work(ChainType::Mainnet));
+    }
+
     #[test]
     fn check_tampered_coinbase() {
         const COINBASE_PREVOUT_N_OFFSET: usize = 4 // version
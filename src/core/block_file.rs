@@ -0,0 +1,430 @@
+//! Reading blocks from Bitcoin Core block files.
+//!
+//! Bitcoin Core stores blocks in `blocks/blkNNNNN.dat` files as a sequence of
+//! records, each made up of the 4-byte network magic, a 4-byte little-endian
+//! length and the serialized block. [`BlockFileReader`] iterates over these
+//! records without requiring a block index or a full data directory, so the
+//! blocks can be fed to [`ChainstateManager::process_block`](crate::ChainstateManager::process_block).
+//!
+//! Block files are read directly, without the kernel and without taking the
+//! data directory lock, so [`BlockFileReader::open`] can read the blocks of a
+//! node that is running. A [`ChainstateManager`](crate::ChainstateManager)
+//! always takes that lock; see its
+//! [Thread Safety](crate::ChainstateManager#thread-safety) section.
+//!
+//! # Example
+//! ```no_run
+//! # use bitcoinkernel::{BlockFileReader, ChainType, ChainstateManager, KernelError};
+//! # fn example(chainman: &ChainstateManager) -> Result<(), Box<dyn std::error::Error>> {
+//! let file = std::io::BufReader::new(std::fs::File::open("blocks/blk00000.dat")?);
+//! for block in BlockFileReader::new(file, ChainType::Signet) {
+//!     chainman.process_block(&block?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, ErrorKind, Read},
+    path::Path,
+};
+
+use crate::{ChainType, ChainstateManager, KernelError};
+
+use super::{encoding::MAX_BLOCK_SERIALIZED_SIZE, Block};
+
+/// Iterator over the blocks stored in a Bitcoin Core block file.
+///
+/// Each record's magic is checked against the expected network magic, and a
+/// mismatch yields [`KernelError::InvalidMagic`], so that files from one
+/// network are not imported into another. Iteration ends at the end of the
+/// stream, or at the zeroed space Bitcoin Core pre-allocates at the end of a
+/// block file, which is not obfuscated. After an error the iterator yields no
+/// further items.
+///
+/// Block files written by Bitcoin Core 28.0 and later are obfuscated with the
+/// key stored in `blocks/xor.dat`. Pass that key to [`xor_key`](Self::xor_key)
+/// to read them.
+///
+/// Wrap unbuffered sources such as a [`std::fs::File`] in a
+/// [`std::io::BufReader`].
+pub struct BlockFileReader<R> {
+    reader: R,
+    magic: [u8; 4],
+    xor_key: [u8; 8],
+    offset: u64,
+    blocks_read: u64,
+    done: bool,
+}
+
+/// Counters describing what a [`BlockFileReader`] has read so far, returned
+/// by [`BlockFileReader::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlockFileReaderStats {
+    blocks_read: u64,
+    bytes_read: u64,
+}
+
+impl BlockFileReaderStats {
+    /// Returns the number of blocks successfully read.
+    pub fn blocks_read(&self) -> u64 {
+        self.blocks_read
+    }
+
+    /// Returns the number of bytes consumed from the underlying reader,
+    /// including record headers and any padding read to detect the end of
+    /// the file.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+}
+
+impl<R: Read> BlockFileReader<R> {
+    /// Creates a reader expecting the magic of `chain_type`.
+    ///
+    /// For a custom signet, use [`with_magic`](Self::with_magic) instead.
+    pub fn new(reader: R, chain_type: ChainType) -> Self {
+        Self::with_magic(reader, chain_type.magic())
+    }
+
+    /// Creates a reader expecting the given network magic.
+    pub fn with_magic(reader: R, magic: [u8; 4]) -> Self {
+        BlockFileReader {
+            reader,
+            magic,
+            xor_key: [0; 8],
+            offset: 0,
+            blocks_read: 0,
+            done: false,
+        }
+    }
+
+    /// Sets the key used to de-obfuscate the file, as stored in
+    /// `blocks/xor.dat`.
+    ///
+    /// The key is applied relative to the start of the file, so the reader
+    /// must be positioned at the beginning of the file.
+    pub fn xor_key(mut self, key: [u8; 8]) -> Self {
+        self.xor_key = key;
+        self
+    }
+
+    /// Returns the number of blocks and bytes read since the reader was
+    /// created.
+    ///
+    /// Blocks are read straight from the stream, so there is no cache whose
+    /// hits and misses could be counted.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{BlockFileReader, ChainType, KernelError};
+    /// # fn example() -> Result<(), KernelError> {
+    /// let mut reader = BlockFileReader::open("/home/user/.bitcoin/blocks", 0, ChainType::Mainnet)?;
+    /// for block in reader.by_ref() {
+    ///     block?;
+    /// }
+    /// let stats = reader.stats();
+    /// println!("{} blocks, {} bytes", stats.blocks_read(), stats.bytes_read());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> BlockFileReaderStats {
+        BlockFileReaderStats {
+            blocks_read: self.blocks_read,
+            bytes_read: self.offset,
+        }
+    }
+
+    /// Fills `buf` with raw, still obfuscated bytes, returning `Ok(false)` if
+    /// the stream ended before the first byte.
+    fn read_raw_or_eof(&mut self, buf: &mut [u8]) -> Result<bool, KernelError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => {
+                    return Err(KernelError::Internal(
+                        "Failed to read block file: unexpected end of file".to_string(),
+                    ))
+                }
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    return Err(KernelError::Internal(format!(
+                        "Failed to read block file: {}",
+                        err
+                    )))
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Removes the obfuscation from bytes just read with
+    /// [`read_raw_or_eof`](Self::read_raw_or_eof) and advances the offset.
+    fn deobfuscate(&mut self, buf: &mut [u8]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte ^= self.xor_key[((self.offset + i as u64) % 8) as usize];
+        }
+        self.offset += buf.len() as u64;
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), KernelError> {
+        if self.read_raw_or_eof(buf)? {
+            self.deobfuscate(buf);
+            Ok(())
+        } else {
+            Err(KernelError::Internal(
+                "Failed to read block file: unexpected end of file".to_string(),
+            ))
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Option<Block>, KernelError> {
+        let mut magic = [0u8; 4];
+        if !self.read_raw_or_eof(&mut magic)? {
+            return Ok(None);
+        }
+        // The pre-allocated space is written as plain zeros, not obfuscated.
+        let is_padding = magic == [0; 4];
+        self.deobfuscate(&mut magic);
+        if is_padding {
+            return Ok(None);
+        }
+        if magic != self.magic {
+            return Err(KernelError::InvalidMagic {
+                expected: self.magic,
+                actual: magic,
+            });
+        }
+
+        let mut len = [0u8; 4];
+        self.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_BLOCK_SERIALIZED_SIZE {
+            return Err(KernelError::Internal(format!(
+                "Block record of {} bytes exceeds the maximum block size",
+                len
+            )));
+        }
+
+        let mut raw_block = vec![0u8; len];
+        self.read_exact(&mut raw_block)?;
+        Block::new(&raw_block).map(Some)
+    }
+}
+
+impl BlockFileReader<BufReader<File>> {
+    /// Opens block file `blk<file_number>.dat` in `blocks_dir`.
+    ///
+    /// The obfuscation key is read from `xor.dat` in the same directory if
+    /// that file exists. No lock is taken, so this works on the blocks
+    /// directory of a running node. The view is a snapshot of what was written
+    /// when each record is read: a node may still be appending to its newest
+    /// file, in which case iteration can end early or with an error at a
+    /// partially written record.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if the block file or `xor.dat`
+    /// cannot be read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{BlockFileReader, ChainType, KernelError};
+    /// # fn example() -> Result<(), KernelError> {
+    /// let blocks_dir = "/home/user/.bitcoin/blocks";
+    /// for block in BlockFileReader::open(blocks_dir, 0, ChainType::Mainnet)? {
+    ///     println!("{}", block?.hash());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open(
+        blocks_dir: impl AsRef<Path>,
+        file_number: u32,
+        chain_type: ChainType,
+    ) -> Result<Self, KernelError> {
+        let blocks_dir = blocks_dir.as_ref();
+        let path = blocks_dir.join(format!("blk{:05}.dat", file_number));
+        let file = File::open(&path).map_err(|err| {
+            KernelError::Internal(format!(
+                "Failed to open block file {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        let xor_path = blocks_dir.join("xor.dat");
+        let xor_key = match fs::read(&xor_path) {
+            Ok(key) => key.try_into().map_err(|_| {
+                KernelError::Internal(format!("Invalid key size in {}", xor_path.display()))
+            })?,
+            Err(err) if err.kind() == ErrorKind::NotFound => [0; 8],
+            Err(err) => {
+                return Err(KernelError::Internal(format!(
+                    "Failed to read {}: {}",
+                    xor_path.display(),
+                    err
+                )))
+            }
+        };
+
+        Ok(BlockFileReader::new(BufReader::new(file), chain_type).xor_key(xor_key))
+    }
+
+    /// Opens block file `blk<file_number>.dat` of a chainstate manager.
+    ///
+    /// The file is looked up in the blocks directory the manager was created
+    /// with, the expected magic is that of its context's chain type, and the
+    /// obfuscation key is read from `xor.dat` if that file exists, so none of
+    /// the manager's configuration has to be repeated. For a custom signet,
+    /// whose magic differs from the default signet's, use
+    /// [`with_magic`](Self::with_magic) instead.
+    ///
+    /// Blocks are stored in the order they were received, which is not
+    /// necessarily height order. Use
+    /// [`ChainstateManager::read_block_data`] to read blocks by their
+    /// position in the chain.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if the block file or `xor.dat`
+    /// cannot be read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{BlockFileReader, ChainstateManager, KernelError};
+    /// # fn example(chainman: &ChainstateManager) -> Result<(), KernelError> {
+    /// for block in BlockFileReader::from_chainstate_manager(chainman, 0)? {
+    ///     println!("{}", block?.hash());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_chainstate_manager(
+        chainman: &ChainstateManager,
+        file_number: u32,
+    ) -> Result<Self, KernelError> {
+        Self::open(chainman.blocks_dir(), file_number, chainman.chain_type())
+    }
+}
+
+impl<R: Read> Iterator for BlockFileReader<R> {
+    type Item = Result<Block, KernelError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.read_record().transpose();
+        if matches!(record, Some(Ok(_))) {
+            self.blocks_read += 1;
+        } else {
+            self.done = true;
+        }
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn record(magic: [u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = magic.to_vec();
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let mut reader = BlockFileReader::new(Cursor::new(Vec::new()), ChainType::Mainnet);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_zero_padding_ends_iteration() {
+        let mut reader = BlockFileReader::new(Cursor::new(vec![0u8; 64]), ChainType::Mainnet);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_zero_padding_ends_iteration_with_xor_key() {
+        let block_data = std::fs::read_to_string("tests/block_data.txt").unwrap();
+        let raw_block = hex::decode(block_data.lines().next().unwrap()).unwrap();
+
+        let key = [0x5a, 0x01, 0xff, 0x10, 0x00, 0x33, 0x7e, 0x80];
+        let mut data = record(ChainType::Regtest.magic(), &raw_block);
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte ^= key[i % 8];
+        }
+        // Bitcoin Core pre-allocates the tail of the file with plain zeros.
+        data.extend_from_slice(&[0u8; 64]);
+
+        let mut reader = BlockFileReader::new(Cursor::new(data), ChainType::Regtest).xor_key(key);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().is_none());
+        assert_eq!(reader.stats().blocks_read(), 1);
+    }
+
+    #[test]
+    fn test_stats_count_consumed_bytes() {
+        let mut data = record(ChainType::Regtest.magic(), &[0u8; 10]);
+        data.extend_from_slice(&[0u8; 8]);
+        let mut reader = BlockFileReader::new(Cursor::new(data), ChainType::Regtest);
+        assert_eq!(reader.stats(), BlockFileReaderStats::default());
+
+        // The record is read in full but is not a valid block.
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert_eq!(reader.stats().blocks_read(), 0);
+        assert_eq!(reader.stats().bytes_read(), 18);
+        assert!(reader.next().is_none());
+        assert_eq!(reader.stats().bytes_read(), 18);
+    }
+
+    #[test]
+    fn test_magic_mismatch() {
+        let data = record(ChainType::Mainnet.magic(), &[0u8; 10]);
+        let mut reader = BlockFileReader::new(Cursor::new(data), ChainType::Signet);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(KernelError::InvalidMagic { expected, actual }))
+                if expected == ChainType::Signet.magic() && actual == ChainType::Mainnet.magic()
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        let mut data = record(ChainType::Regtest.magic(), &[0u8; 10]);
+        data.truncate(data.len() - 1);
+        let mut reader = BlockFileReader::new(Cursor::new(data), ChainType::Regtest);
+        assert!(matches!(reader.next(), Some(Err(KernelError::Internal(_)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_oversized_record() {
+        let mut data = ChainType::Regtest.magic().to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = BlockFileReader::new(Cursor::new(data), ChainType::Regtest);
+        assert!(matches!(reader.next(), Some(Err(KernelError::Internal(_)))));
+    }
+
+    #[test]
+    fn test_xor_key_applied_to_magic() {
+        let key = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut data = ChainType::Mainnet.magic().to_vec();
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte ^= key[i];
+        }
+        let mut reader = BlockFileReader::new(Cursor::new(data), ChainType::Regtest).xor_key(key);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(KernelError::InvalidMagic { actual, .. })) if actual == ChainType::Mainnet.magic()
+        ));
+    }
+}
@@ -1,4 +1,7 @@
-use std::marker::PhantomData;
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
 
 use libbitcoinkernel_sys::{
     btck_BlockTreeEntry, btck_block_tree_entry_get_ancestor, btck_block_tree_entry_get_block_hash,
@@ -7,9 +10,9 @@ use libbitcoinkernel_sys::{
 };
 
 use crate::{
-    core::block::{BlockHashRef, BlockHeaderRef},
+    core::block::{BlockHashRef, BlockHeaderExt, BlockHeaderRef},
     ffi::sealed::{AsPtr, FromPtr},
-    ChainstateManager,
+    Chain, ChainstateManager, KernelError,
 };
 
 /// A block tree entry that is tied to a specific [`ChainstateManager`].
@@ -18,7 +21,16 @@ use crate::{
 /// tree once it is loaded. The [`BlockTreeEntry`] points to an entry in this tree.
 /// It is only valid as long as the [`ChainstateManager`] it was retrieved from
 /// remains in scope.
-#[derive(Debug)]
+///
+/// This is the only block tree entry type: every lookup, whether through
+/// [`ChainstateManager::get_block_tree_entry`], [`ChainstateManager::best_entry`]
+/// or the active [`Chain`], returns a [`BlockTreeEntry`], so code that
+/// analyzes entries by [`height`](Self::height), [`block_hash`](Self::block_hash),
+/// [`prev`](Self::prev) or [`header`](Self::header) is written once for all of
+/// them.
+///
+/// Two entries are equal if they have the same height and block hash, even if
+/// they were retrieved through separate lookups.
 pub struct BlockTreeEntry<'a> {
     inner: *const btck_BlockTreeEntry,
     marker: PhantomData<&'a ChainstateManager>,
@@ -40,6 +52,18 @@ impl<'a> BlockTreeEntry<'a> {
         Some(unsafe { BlockTreeEntry::from_ptr(inner) })
     }
 
+    /// Move to the next entry on `chain`. E.g. from height n to height n+1.
+    ///
+    /// Unlike [`prev`](Self::prev), the next entry is only well-defined on a
+    /// specific chain, since the block tree may branch. Returns `None` if this
+    /// entry is not on `chain` or is its tip.
+    pub fn next(&self, chain: &Chain<'a>) -> Option<BlockTreeEntry<'a>> {
+        if !chain.contains(self) {
+            return None;
+        }
+        chain.at_height(self.height() as usize + 1)
+    }
+
     /// Returns the current height associated with this BlockTreeEntry.
     pub fn height(&self) -> i32 {
         unsafe { btck_block_tree_entry_get_height(self.inner) }
@@ -50,6 +74,14 @@ impl<'a> BlockTreeEntry<'a> {
         unsafe { BlockHeaderRef::from_ptr(btck_block_tree_entry_get_block_header(self.inner)) }
     }
 
+    /// Returns the 80-byte serialized block header associated with this
+    /// BlockTreeEntry, without reading the block body from disk.
+    ///
+    /// Returns [`KernelError::Internal`] if the header cannot be serialized.
+    pub fn raw_header(&self) -> Result<[u8; 80], KernelError> {
+        self.header().consensus_encode()
+    }
+
     /// Returns the current block hash associated with this BlockTreeEntry.
     pub fn block_hash(&self) -> BlockHashRef<'_> {
         let hash_ptr = unsafe { btck_block_tree_entry_get_block_hash(self.inner) };
@@ -70,6 +102,17 @@ impl<'a> BlockTreeEntry<'a> {
         }
         Some(unsafe { BlockTreeEntry::from_ptr(ptr) })
     }
+
+    /// Returns the ancestor `n` blocks back, or `None` if that would go past
+    /// the genesis block.
+    ///
+    /// `nth_ancestor(0)` is this entry and `nth_ancestor(1)` is the same as
+    /// [`prev`](Self::prev). Unlike calling `prev` `n` times, this operation
+    /// is O(log N), like [`ancestor`](Self::ancestor).
+    pub fn nth_ancestor(&self, n: usize) -> Option<BlockTreeEntry<'a>> {
+        let n = i32::try_from(n).ok()?;
+        self.ancestor(self.height().checked_sub(n)?)
+    }
 }
 
 impl<'a> AsPtr<btck_BlockTreeEntry> for BlockTreeEntry<'a> {
@@ -95,6 +138,25 @@ impl<'a> Clone for BlockTreeEntry<'a> {
 
 impl<'a> Copy for BlockTreeEntry<'a> {}
 
+impl<'a> PartialEq for BlockTreeEntry<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.height() == other.height() && self.block_hash() == other.block_hash()
+    }
+}
+
+impl<'a> Eq for BlockTreeEntry<'a> {}
+
+impl<'a> Debug for BlockTreeEntry<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BlockTreeEntry(height: {}, hash: {})",
+            self.height(),
+            self.block_hash()
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,96 @@
+//! Looking up the coins spent by a block's transactions.
+//!
+//! A [`CoinProvider`] answers "which coin does this outpoint refer to?". The
+//! [`SpentOutputsCoinProvider`] answers it from a block and its
+//! [`BlockSpentOutputs`](crate::BlockSpentOutputs), so a historical block can be
+//! re-validated with [`verify`](crate::verify) from just the two serialized
+//! blobs, without a live chainstate.
+
+use std::collections::HashMap;
+
+use crate::KernelError;
+
+use super::{
+    block::{BlockSpentOutputsExt, CoinRef},
+    transaction::{TransactionExt, TxOutPointRef},
+    utxo_map::UtxoOutPoint,
+    Block,
+};
+
+/// A source of coins, looked up by the outpoint that created them.
+///
+/// The trait is object safe, so a provider can be passed around as
+/// `&dyn CoinProvider`. An owned [`TxOutPoint`](crate::TxOutPoint) is looked
+/// up through [`as_ref`](crate::TxOutPoint::as_ref).
+pub trait CoinProvider {
+    /// Returns the coin at `outpoint`, or `None` if the provider does not know it.
+    fn coin(&self, outpoint: TxOutPointRef<'_>) -> Option<CoinRef<'_>>;
+}
+
+/// A [`CoinProvider`] over the coins spent by a single block.
+///
+/// The coins are indexed by the outpoints of the block's inputs, matching each
+/// entry of the block's spent outputs to the input at the same position.
+///
+/// # Example
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, verify, Block, BlockSpentOutputs, CoinProvider,
+/// #     KernelError, PrecomputedTransactionData, SpentOutputsCoinProvider, TxOutRef};
+/// # fn example(block: &Block, spent_outputs: &BlockSpentOutputs) -> Result<(), KernelError> {
+/// let provider = SpentOutputsCoinProvider::new(block, spent_outputs)?;
+///
+/// for tx in block.transactions().skip(1) {
+///     let coins: Vec<_> = tx
+///         .inputs()
+///         .map(|input| provider.coin(input.outpoint()).expect("coin spent by block"))
+///         .collect();
+///     let outputs: Vec<TxOutRef> = coins.iter().map(|coin| coin.output()).collect();
+///     let tx_data = PrecomputedTransactionData::new(&tx, &outputs)?;
+///     for (index, output) in outputs.iter().enumerate() {
+///         verify(&output.script_pubkey(), Some(output.value()), &tx, index, None, &tx_data)?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SpentOutputsCoinProvider<'a> {
+    coins: HashMap<UtxoOutPoint, CoinRef<'a>>,
+}
+
+impl<'a> SpentOutputsCoinProvider<'a> {
+    /// Indexes the coins in `spent_outputs` by the outpoints of `block`'s inputs.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::MismatchedOutputsSize`] if `spent_outputs` does
+    /// not hold exactly one coin per input of every non-coinbase transaction.
+    pub fn new<S: BlockSpentOutputsExt>(
+        block: &Block,
+        spent_outputs: &'a S,
+    ) -> Result<Self, KernelError> {
+        spent_outputs.matches_block(block)?;
+
+        let mut coins = HashMap::new();
+        for (tx, tx_spent) in block.transactions().skip(1).zip(spent_outputs.iter()) {
+            for (input, coin) in tx.inputs().zip(tx_spent.coins()) {
+                coins.insert(UtxoOutPoint::from(&input.outpoint()), coin);
+            }
+        }
+        Ok(SpentOutputsCoinProvider { coins })
+    }
+
+    /// Returns the number of coins indexed.
+    pub fn len(&self) -> usize {
+        self.coins.len()
+    }
+
+    /// Returns `true` if the block spends no coins.
+    pub fn is_empty(&self) -> bool {
+        self.coins.is_empty()
+    }
+}
+
+impl CoinProvider for SpentOutputsCoinProvider<'_> {
+    fn coin(&self, outpoint: TxOutPointRef<'_>) -> Option<CoinRef<'_>> {
+        self.coins.get(&UtxoOutPoint::from(&outpoint)).copied()
+    }
+}
@@ -0,0 +1,450 @@
+//! Minimal decoding of the Bitcoin wire format.
+//!
+//! The kernel API does not expose every part of a transaction, such as input
+//! scriptSigs and witness stacks. This module decodes those parts directly from
+//! a transaction's consensus encoding, borrowing from the encoded buffer.
+//!
+//! It also finds the boundaries of a block in a byte stream, so a block can be
+//! read from a [`Read`] source before being handed to the kernel, and decodes
+//! the hex strings accepted by the crate's `*_hex` constructors.
+
+use std::io::{ErrorKind, Read};
+
+use crate::KernelError;
+
+/// Maximum size of a serialized block, used to bound reads from a stream.
+pub(crate) const MAX_BLOCK_SERIALIZED_SIZE: usize = 4_000_000;
+
+/// A decoded transaction input, borrowing from the encoded transaction.
+pub(crate) struct RawTxIn<'a> {
+    pub(crate) script_sig: &'a [u8],
+    pub(crate) witness: Vec<&'a [u8]>,
+}
+
+/// A decoded transaction, borrowing from the encoded transaction.
+pub(crate) struct RawTransaction<'a> {
+    pub(crate) inputs: Vec<RawTxIn<'a>>,
+    pub(crate) output_scripts: Vec<&'a [u8]>,
+    /// Number of bytes taken by the segwit marker, flag and witness stacks.
+    pub(crate) witness_size: usize,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], KernelError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                KernelError::Internal("Unexpected end of transaction data.".to_string())
+            })?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.data.get(self.pos + offset).copied()
+    }
+
+    fn compact_size(&mut self) -> Result<usize, KernelError> {
+        let value = match self.take(1)?[0] {
+            0xfd => u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            0xfe => u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            0xff => u64::from_le_bytes(self.take(8)?.try_into().unwrap()),
+            n => n as u64,
+        };
+        usize::try_from(value)
+            .ok()
+            .filter(|size| *size <= self.data.len())
+            .ok_or_else(|| KernelError::Internal("Invalid compact size.".to_string()))
+    }
+
+    fn var_bytes(&mut self) -> Result<&'a [u8], KernelError> {
+        let len = self.compact_size()?;
+        self.take(len)
+    }
+}
+
+/// Returns the number of bytes `value` takes when encoded as a compact size.
+pub(crate) fn compact_size_len(value: usize) -> usize {
+    match value {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Appends `value` to `out` encoded as a compact size.
+pub(crate) fn write_compact_size(out: &mut Vec<u8>, value: usize) {
+    match value {
+        0..=0xfc => out.push(value as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+    }
+}
+
+/// Decodes a hex string of either case into bytes.
+pub(crate) fn decode_hex(hex: &str) -> Result<Vec<u8>, KernelError> {
+    if hex.len() % 2 != 0 {
+        return Err(KernelError::InvalidLength {
+            expected: hex.len() + 1,
+            actual: hex.len(),
+        });
+    }
+    let mut bytes = Vec::with_capacity(hex.len() / 2);
+    let mut high = 0;
+    for (index, character) in hex.chars().enumerate() {
+        let digit = character
+            .to_digit(16)
+            .ok_or(KernelError::InvalidHexCharacter { character, index })?
+            as u8;
+        if index % 2 == 0 {
+            high = digit << 4;
+        } else {
+            bytes.push(high | digit);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decodes a 64 character hex string in display order, as used for hashes,
+/// into internal order bytes.
+pub(crate) fn decode_display_hex(hex: &str) -> Result<[u8; 32], KernelError> {
+    if hex.len() != 64 {
+        return Err(KernelError::InvalidLength {
+            expected: 64,
+            actual: hex.len(),
+        });
+    }
+    let mut bytes: [u8; 32] = decode_hex(hex)?
+        .try_into()
+        .expect("64 hex characters decode to 32 bytes");
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Decodes a transaction from its consensus encoding.
+pub(crate) fn decode_transaction(data: &[u8]) -> Result<RawTransaction<'_>, KernelError> {
+    let mut reader = Reader::new(data);
+    reader.take(4)?; // version
+
+    let has_witness = reader.peek(0) == Some(0x00) && reader.peek(1).is_some_and(|f| f != 0);
+    if has_witness {
+        reader.take(2)?; // marker and flag
+    }
+
+    let input_count = reader.compact_size()?;
+    let mut inputs = Vec::with_capacity(input_count);
+    for _ in 0..input_count {
+        reader.take(36)?; // prevout
+        let script_sig = reader.var_bytes()?;
+        reader.take(4)?; // sequence
+        inputs.push(RawTxIn {
+            script_sig,
+            witness: Vec::new(),
+        });
+    }
+
+    let output_count = reader.compact_size()?;
+    let mut output_scripts = Vec::with_capacity(output_count);
+    for _ in 0..output_count {
+        reader.take(8)?; // value
+        output_scripts.push(reader.var_bytes()?);
+    }
+
+    let mut witness_size = 0;
+    if has_witness {
+        let witness_start = reader.pos;
+        for input in inputs.iter_mut() {
+            let item_count = reader.compact_size()?;
+            for _ in 0..item_count {
+                input.witness.push(reader.var_bytes()?);
+            }
+        }
+        witness_size = 2 + reader.pos - witness_start;
+    }
+
+    reader.take(4)?; // locktime
+
+    Ok(RawTransaction {
+        inputs,
+        output_scripts,
+        witness_size,
+    })
+}
+
+/// Copies bytes from a stream into a buffer while the caller parses them.
+struct StreamReader<'r, R: Read + ?Sized> {
+    reader: &'r mut R,
+    buf: Vec<u8>,
+}
+
+impl<'r, R: Read + ?Sized> StreamReader<'r, R> {
+    fn take(&mut self, len: usize) -> Result<&[u8], KernelError> {
+        let start = self.buf.len();
+        if len > MAX_BLOCK_SERIALIZED_SIZE - start {
+            return Err(KernelError::Internal(
+                "Block exceeds the maximum serialized size.".to_string(),
+            ));
+        }
+        self.buf.resize(start + len, 0);
+        self.reader
+            .read_exact(&mut self.buf[start..])
+            .map_err(|err| KernelError::Internal(format!("Failed to read block: {}", err)))?;
+        Ok(&self.buf[start..])
+    }
+
+    fn byte(&mut self) -> Result<u8, KernelError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn compact_size(&mut self) -> Result<usize, KernelError> {
+        let value = match self.byte()? {
+            0xfd => u16::from_le_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            0xfe => u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            0xff => u64::from_le_bytes(self.take(8)?.try_into().unwrap()),
+            n => n as u64,
+        };
+        usize::try_from(value)
+            .ok()
+            .filter(|size| *size <= MAX_BLOCK_SERIALIZED_SIZE)
+            .ok_or_else(|| KernelError::Internal("Invalid compact size.".to_string()))
+    }
+
+    fn skip_var_bytes(&mut self) -> Result<(), KernelError> {
+        let len = self.compact_size()?;
+        self.take(len)?;
+        Ok(())
+    }
+
+    fn skip_inputs(&mut self, count: usize) -> Result<(), KernelError> {
+        for _ in 0..count {
+            self.take(36)?; // prevout
+            self.skip_var_bytes()?; // scriptSig
+            self.take(4)?; // sequence
+        }
+        Ok(())
+    }
+
+    fn skip_outputs(&mut self, count: usize) -> Result<(), KernelError> {
+        for _ in 0..count {
+            self.take(8)?; // value
+            self.skip_var_bytes()?; // scriptPubkey
+        }
+        Ok(())
+    }
+
+    /// Skips one transaction, following the same rules as Bitcoin Core for
+    /// telling an empty input vector apart from the segwit marker.
+    fn skip_transaction(&mut self) -> Result<(), KernelError> {
+        self.take(4)?; // version
+        let mut input_count = self.compact_size()?;
+        let mut flags = 0;
+        let output_count = if input_count == 0 {
+            flags = self.byte()?;
+            if flags != 0 {
+                input_count = self.compact_size()?;
+                self.skip_inputs(input_count)?;
+                self.compact_size()?
+            } else {
+                0
+            }
+        } else {
+            self.skip_inputs(input_count)?;
+            self.compact_size()?
+        };
+        self.skip_outputs(output_count)?;
+
+        if flags & 1 != 0 {
+            for _ in 0..input_count {
+                let item_count = self.compact_size()?;
+                for _ in 0..item_count {
+                    self.skip_var_bytes()?;
+                }
+            }
+        }
+
+        self.take(4)?; // locktime
+        Ok(())
+    }
+}
+
+/// Reads exactly one serialized block from `reader`.
+///
+/// Returns `Ok(None)` if the stream ends before the first byte of the block.
+pub(crate) fn read_block<R: Read + ?Sized>(reader: &mut R) -> Result<Option<Vec<u8>>, KernelError> {
+    let mut first = [0u8; 1];
+    loop {
+        match reader.read(&mut first) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => {
+                return Err(KernelError::Internal(format!(
+                    "Failed to read block: {}",
+                    err
+                )))
+            }
+        }
+    }
+
+    let mut stream = StreamReader {
+        reader,
+        buf: first.to_vec(),
+    };
+    stream.take(79)?; // remainder of the header
+    let tx_count = stream.compact_size()?;
+    for _ in 0..tx_count {
+        stream.skip_transaction()?;
+    }
+    Ok(Some(stream.buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mainnet block 1 coinbase transaction.
+    const COINBASE_TX_HEX: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82cbf2342c858eeac00000000";
+
+    // A P2WPKH spend from the regtest test data.
+    const SEGWIT_TX_HEX: &str = "02000000000101d453cc476a5662964ce6eecddb78efb40e1616223d4c7fd54f6ca84abdc87e020000000000fdffffff0273101024010000001600140b500a01c9926e36f8913967382fd767a48309a200e1f505000000001600141409745405c4e8310a875bcd602db6b9b3dc0cf902463043021f1921216d69304c9f523fabd39e12f98dc0c2d9d4598c28494c92b065d25a570220121ae3ff0feb349b7f357bc6bc98163da82ca63f6ae51fcf791ff0c40a068ac701210300d999684bd123d72074bf26bfaca11775eb371029bf714a60bdd4e976b1cc8fc9000000";
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+        assert_eq!(decode_hex("00ffAb").unwrap(), vec![0x00, 0xff, 0xab]);
+        assert!(matches!(
+            decode_hex("abc"),
+            Err(KernelError::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        ));
+        assert!(matches!(
+            decode_hex("0g"),
+            Err(KernelError::InvalidHexCharacter {
+                character: 'g',
+                index: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_decode_display_hex() {
+        let hex = format!("ab{}01", "00".repeat(30));
+        let bytes = decode_display_hex(&hex).unwrap();
+        assert_eq!(bytes[0], 0x01);
+        assert_eq!(bytes[31], 0xab);
+        assert!(matches!(
+            decode_display_hex("abcd"),
+            Err(KernelError::InvalidLength {
+                expected: 64,
+                actual: 4
+            })
+        ));
+        assert!(matches!(
+            decode_display_hex(&format!("{}x", "0".repeat(63))),
+            Err(KernelError::InvalidHexCharacter {
+                character: 'x',
+                index: 63
+            })
+        ));
+    }
+
+    #[test]
+    fn test_compact_size_len() {
+        assert_eq!(compact_size_len(0), 1);
+        assert_eq!(compact_size_len(0xfc), 1);
+        assert_eq!(compact_size_len(0xfd), 3);
+        assert_eq!(compact_size_len(0xffff), 3);
+        assert_eq!(compact_size_len(0x1_0000), 5);
+    }
+
+    #[test]
+    fn test_decode_legacy_transaction() {
+        let data = hex::decode(COINBASE_TX_HEX).unwrap();
+        let tx = decode_transaction(&data).unwrap();
+
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(
+            tx.inputs[0].script_sig,
+            &[0x04, 0xff, 0xff, 0x00, 0x1d, 0x01, 0x04]
+        );
+        assert!(tx.inputs[0].witness.is_empty());
+        assert_eq!(tx.output_scripts.len(), 1);
+        assert_eq!(tx.output_scripts[0].len(), 67);
+        assert_eq!(tx.witness_size, 0);
+    }
+
+    #[test]
+    fn test_decode_segwit_transaction() {
+        let data = hex::decode(SEGWIT_TX_HEX).unwrap();
+        let tx = decode_transaction(&data).unwrap();
+
+        assert_eq!(tx.inputs.len(), 1);
+        assert!(tx.inputs[0].script_sig.is_empty());
+        assert_eq!(tx.inputs[0].witness.len(), 2);
+        assert_eq!(tx.inputs[0].witness[0].len(), 70);
+        assert_eq!(tx.inputs[0].witness[1].len(), 33);
+        assert_eq!(tx.output_scripts.len(), 2);
+        assert_eq!(data.len(), 221);
+        assert_eq!(tx.witness_size, 108);
+    }
+
+    #[test]
+    fn test_read_block_boundaries() {
+        let header = [0u8; 80];
+        let tx = hex::decode(COINBASE_TX_HEX).unwrap();
+        let mut block = header.to_vec();
+        block.push(1);
+        block.extend_from_slice(&tx);
+
+        let mut stream = block.clone();
+        stream.extend_from_slice(&block);
+        let mut cursor = std::io::Cursor::new(stream);
+
+        assert_eq!(read_block(&mut cursor).unwrap().unwrap(), block);
+        assert_eq!(read_block(&mut cursor).unwrap().unwrap(), block);
+        assert!(read_block(&mut cursor).unwrap().is_none());
+
+        let mut truncated = std::io::Cursor::new(&block[..block.len() - 1]);
+        assert!(read_block(&mut truncated).is_err());
+    }
+
+    #[test]
+    fn test_read_block_rejects_oversized_counts() {
+        let mut block = vec![0u8; 80];
+        block.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        let mut cursor = std::io::Cursor::new(block);
+        assert!(read_block(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn test_decode_truncated_transaction() {
+        let data = hex::decode(COINBASE_TX_HEX).unwrap();
+        assert!(decode_transaction(&data[..data.len() - 1]).is_err());
+        assert!(decode_transaction(&[]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Fee rates for comparing transactions by the fee they pay per unit of size.
+
+use std::fmt::{self, Display, Formatter};
+
+/// Number of weight units in a virtual byte.
+const WITNESS_SCALE_FACTOR: usize = 4;
+
+/// A fee rate, stored in satoshis per 1000 virtual bytes.
+///
+/// Storing the rate as an integer makes it totally ordered, so transactions
+/// can be sorted by fee rate. It displays in satoshis per virtual byte with
+/// three decimal places, which is exact and matches Bitcoin Core's
+/// `CFeeRate::ToString`, for example `"1.234 sat/vB"`.
+///
+/// # Examples
+/// ```
+/// use bitcoinkernel::FeeRate;
+///
+/// // A 141 sat fee on a 560 weight unit (140 vbyte) transaction.
+/// let rate = FeeRate::from_fee_and_weight(141, 560);
+/// assert_eq!(rate.to_sat_per_kvb(), 1007);
+/// assert_eq!(rate.to_string(), "1.007 sat/vB");
+/// assert!(rate > FeeRate::from_sat_per_kvb(1000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FeeRate(i64);
+
+impl FeeRate {
+    /// A fee rate of zero.
+    pub const ZERO: FeeRate = FeeRate(0);
+
+    /// Creates a fee rate from a rate in satoshis per 1000 virtual bytes.
+    pub const fn from_sat_per_kvb(sat_per_kvb: i64) -> Self {
+        FeeRate(sat_per_kvb)
+    }
+
+    /// Creates the fee rate of paying `fee` satoshis for `weight` weight units.
+    ///
+    /// The weight is converted to virtual bytes rounding up, as Bitcoin Core
+    /// does. A weight of zero gives [`FeeRate::ZERO`].
+    pub fn from_fee_and_weight(fee: i64, weight: usize) -> Self {
+        let vsize = (weight.saturating_add(WITNESS_SCALE_FACTOR - 1) / WITNESS_SCALE_FACTOR) as i64;
+        if vsize == 0 {
+            return FeeRate::ZERO;
+        }
+        FeeRate(fee.saturating_mul(1000) / vsize)
+    }
+
+    /// Returns the rate in satoshis per 1000 virtual bytes.
+    pub const fn to_sat_per_kvb(self) -> i64 {
+        self.0
+    }
+
+    /// Returns the fee in satoshis this rate charges for `vsize` virtual bytes.
+    pub fn fee_for_vsize(self, vsize: usize) -> i64 {
+        self.0.saturating_mul(vsize as i64) / 1000
+    }
+}
+
+impl Display for FeeRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{}{}.{:03} sat/vB", sign, abs / 1000, abs % 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_fee_and_weight() {
+        assert_eq!(
+            FeeRate::from_fee_and_weight(141, 560).to_sat_per_kvb(),
+            1007
+        );
+        // 561 weight units round up to 141 vbytes.
+        assert_eq!(
+            FeeRate::from_fee_and_weight(141, 561).to_sat_per_kvb(),
+            1000
+        );
+        assert_eq!(FeeRate::from_fee_and_weight(1000, 0), FeeRate::ZERO);
+        assert_eq!(FeeRate::from_fee_and_weight(0, 400), FeeRate::ZERO);
+    }
+
+    #[test]
+    fn test_fee_rate_display() {
+        // Rates that two decimal places would truncate.
+        assert_eq!(FeeRate::from_sat_per_kvb(1007).to_string(), "1.007 sat/vB");
+        assert_eq!(
+            FeeRate::from_sat_per_kvb(12_345).to_string(),
+            "12.345 sat/vB"
+        );
+        assert_eq!(FeeRate::from_sat_per_kvb(1).to_string(), "0.001 sat/vB");
+        assert_eq!(FeeRate::from_sat_per_kvb(50).to_string(), "0.050 sat/vB");
+        assert_eq!(
+            FeeRate::from_sat_per_kvb(-1_500).to_string(),
+            "-1.500 sat/vB"
+        );
+        assert_eq!(FeeRate::ZERO.to_string(), "0.000 sat/vB");
+    }
+
+    #[test]
+    fn test_fee_rate_ordering() {
+        let mut rates = vec![
+            FeeRate::from_sat_per_kvb(2_000),
+            FeeRate::from_sat_per_kvb(1_000),
+            FeeRate::from_sat_per_kvb(10_000),
+        ];
+        rates.sort();
+        assert_eq!(
+            rates,
+            vec![
+                FeeRate::from_sat_per_kvb(1_000),
+                FeeRate::from_sat_per_kvb(2_000),
+                FeeRate::from_sat_per_kvb(10_000),
+            ]
+        );
+        assert!(FeeRate::ZERO < FeeRate::from_sat_per_kvb(1));
+    }
+
+    #[test]
+    fn test_fee_for_vsize() {
+        let rate = FeeRate::from_sat_per_kvb(1_500);
+        assert_eq!(rate.fee_for_vsize(200), 300);
+        assert_eq!(rate.fee_for_vsize(0), 0);
+    }
+}
@@ -1,19 +1,33 @@
 pub mod block;
+pub mod block_file;
 pub mod block_tree_entry;
+pub mod coin_provider;
+mod encoding;
+pub mod fee_rate;
+pub mod pow;
 pub mod script;
 pub mod transaction;
+pub mod utxo_map;
 pub mod verify;
+pub mod verify_pool;
 
 pub use block::{
-    Block, BlockCheckFlags, BlockCheckResult, BlockHash, BlockHeader, BlockSpentOutputs,
-    BlockSpentOutputsRef, Coin, CoinRef, TransactionSpentOutputs, TransactionSpentOutputsRef,
+    block_subsidy, Block, BlockCheckFlags, BlockCheckResult, BlockHash, BlockHeader,
+    BlockSpentOutputs, BlockSpentOutputsRef, Coin, CoinRef, TransactionSpentOutputs,
+    TransactionSpentOutputsRef, COINBASE_MATURITY,
 };
+pub use block_file::{BlockFileReader, BlockFileReaderStats};
 pub use block_tree_entry::BlockTreeEntry;
-pub use script::{ScriptPubkey, ScriptPubkeyRef};
+pub use coin_provider::{CoinProvider, SpentOutputsCoinProvider};
+pub use fee_rate::FeeRate;
+pub use pow::CompactTarget;
+pub use script::{HasScriptPubkey, ScriptPubkey, ScriptPubkeyRef, MAX_SCRIPT_SIZE};
 pub use transaction::{
-    Transaction, TransactionRef, TxCheckResult, TxIn, TxInRef, TxOut, TxOutPoint, TxOutPointRef,
-    TxOutRef, Txid, TxidRef,
+    Transaction, TransactionBuilder, TransactionRef, TxCheckResult, TxIn, TxInRef, TxOut,
+    TxOutPoint, TxOutPointRef, TxOutRef, Txid, TxidRef, Witness, WitnessIter, COIN,
+    LOCKTIME_THRESHOLD, MAX_BIP125_RBF_SEQUENCE, MAX_MONEY, SEQUENCE_FINAL,
 };
+pub use utxo_map::{UtxoEntry, UtxoMap, UtxoOutPoint};
 
 pub use block::{
     BlockHashExt, BlockHeaderExt, BlockSpentOutputsExt, CoinExt, TransactionSpentOutputsExt,
@@ -21,12 +35,17 @@ pub use block::{
 pub use script::ScriptPubkeyExt;
 pub use transaction::{TransactionExt, TxInExt, TxOutExt, TxOutPointExt, TxidExt};
 
-pub use verify::{verify, PrecomputedTransactionData, ScriptVerificationFlags, ScriptVerifyError};
+pub use verify::{
+    verify, verify_detailed, verify_hex, verify_transaction, PrecomputedTransactionData,
+    ScriptVerificationFlags, ScriptVerifyError, VerifyReport,
+};
+pub use verify_pool::{VerifyFuture, VerifyPool};
 
 pub mod verify_flags {
     pub use super::verify::{
-        VERIFY_ALL, VERIFY_ALL_PRE_TAPROOT, VERIFY_CHECKLOCKTIMEVERIFY, VERIFY_CHECKSEQUENCEVERIFY,
-        VERIFY_DERSIG, VERIFY_NONE, VERIFY_NULLDUMMY, VERIFY_P2SH, VERIFY_TAPROOT, VERIFY_WITNESS,
+        validate_flags, VERIFY_ALL, VERIFY_ALL_PRE_TAPROOT, VERIFY_CHECKLOCKTIMEVERIFY,
+        VERIFY_CHECKSEQUENCEVERIFY, VERIFY_DERSIG, VERIFY_NONE, VERIFY_NULLDUMMY, VERIFY_P2SH,
+        VERIFY_TAPROOT, VERIFY_WITNESS,
     };
 }
 
@@ -0,0 +1,265 @@
+//! Proof-of-work targets in the compact form stored in block headers.
+//!
+//! A header's [`bits`](crate::BlockHeaderExt::bits) field encodes a 256-bit
+//! target as a floating point style number: the high byte is a size in bytes
+//! and the low 23 bits are the most significant digits. [`CompactTarget`]
+//! converts between this form and the full target, and derives the difficulty
+//! explorers display.
+
+use std::cmp::Ordering;
+
+use crate::ChainType;
+
+/// A proof-of-work target in the compact form of a header's `bits` field.
+///
+/// Full targets are 256-bit integers in the same internal byte order as
+/// [`BlockHashExt::to_bytes`](crate::BlockHashExt::to_bytes), so a block hash
+/// meets a target if it is numerically less than or equal to it.
+///
+/// # Examples
+/// ```
+/// use bitcoinkernel::{ChainType, CompactTarget};
+///
+/// let genesis = CompactTarget::from_consensus(0x1d00ffff);
+/// let target = genesis.to_target();
+/// assert_eq!(target[26..28], [0xff, 0xff]);
+/// assert_eq!(CompactTarget::from_target(&target), genesis);
+/// assert_eq!(genesis.difficulty(ChainType::Mainnet), 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactTarget(u32);
+
+impl CompactTarget {
+    /// Creates a compact target from the raw `bits` field of a header.
+    pub const fn from_consensus(bits: u32) -> Self {
+        CompactTarget(bits)
+    }
+
+    /// Returns the raw `bits` value.
+    pub const fn to_consensus(self) -> u32 {
+        self.0
+    }
+
+    /// Encodes `target` in compact form.
+    ///
+    /// Only the three most significant bytes are kept, so the result decodes
+    /// to `target` rounded down. Mirrors Bitcoin Core's `GetCompact`.
+    pub fn from_target(target: &[u8; 32]) -> Self {
+        let mut size = 32 - target.iter().rev().take_while(|byte| **byte == 0).count();
+        let mut word = if size <= 3 {
+            u32::from_le_bytes([target[0], target[1], target[2], 0]) << (8 * (3 - size))
+        } else {
+            u32::from_le_bytes([target[size - 3], target[size - 2], target[size - 1], 0])
+        };
+        // The high bit of the word is a sign bit, so shift it out of the way.
+        if word & 0x0080_0000 != 0 {
+            word >>= 8;
+            size += 1;
+        }
+        CompactTarget(word | (size as u32) << 24)
+    }
+
+    /// Decodes the full 256-bit target.
+    ///
+    /// Mirrors Bitcoin Core's `SetCompact`: the sign bit is ignored and digits
+    /// shifted past 256 bits are dropped. Use
+    /// [`BlockHeaderExt::check_proof_of_work`](crate::BlockHeaderExt::check_proof_of_work)
+    /// to check a header, which rejects such targets.
+    pub fn to_target(self) -> [u8; 32] {
+        let size = (self.0 >> 24) as usize;
+        let mut word = self.0 & 0x007f_ffff;
+        let mut target = [0u8; 32];
+        if size <= 3 {
+            word >>= 8 * (3 - size);
+            target[..3].copy_from_slice(&word.to_le_bytes()[..3]);
+        } else {
+            for (offset, byte) in word.to_le_bytes()[..3].iter().enumerate() {
+                if let Some(slot) = target.get_mut(size - 3 + offset) {
+                    *slot = *byte;
+                }
+            }
+        }
+        target
+    }
+
+    /// Returns how many times harder this target is to meet than the easiest
+    /// target allowed on `chain_type`.
+    ///
+    /// The easiest target is the compact form of
+    /// [`pow_limit`](ChainType::pow_limit), which on mainnet is `0x1d00ffff`,
+    /// so mainnet values match the difficulty shown by explorers and RPC.
+    /// Bitcoin Core's RPC measures every network against the mainnet limit,
+    /// so values for the test networks differ from it. A zero target gives
+    /// infinity.
+    pub fn difficulty(self, chain_type: ChainType) -> f64 {
+        let easiest = CompactTarget::from_target(&chain_type.pow_limit()).to_target();
+        target_to_f64(&easiest) / target_to_f64(&self.to_target())
+    }
+
+    /// Decodes the target, returning `None` if it is negative, zero,
+    /// overflows or is above `pow_limit`. Mirrors Bitcoin Core's
+    /// `DeriveTarget`.
+    pub(crate) fn derive_target(self, pow_limit: &[u8; 32]) -> Option<[u8; 32]> {
+        let size = self.0 >> 24;
+        let word = self.0 & 0x007f_ffff;
+        let negative = word != 0 && self.0 & 0x0080_0000 != 0;
+        let overflow =
+            word != 0 && (size > 34 || (word > 0xff && size > 33) || (word > 0xffff && size > 32));
+        if negative || overflow {
+            return None;
+        }
+
+        let target = self.to_target();
+        if target == [0u8; 32] || cmp_uint256(&target, pow_limit).is_gt() {
+            return None;
+        }
+        Some(target)
+    }
+}
+
+impl From<u32> for CompactTarget {
+    fn from(bits: u32) -> Self {
+        CompactTarget(bits)
+    }
+}
+
+impl From<CompactTarget> for u32 {
+    fn from(target: CompactTarget) -> Self {
+        target.0
+    }
+}
+
+/// Compares two 256-bit integers stored in little-endian byte order.
+pub(crate) fn cmp_uint256(a: &[u8; 32], b: &[u8; 32]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+fn target_to_f64(target: &[u8; 32]) -> f64 {
+    target
+        .iter()
+        .rev()
+        .fold(0.0, |value, byte| value * 256.0 + f64::from(*byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_target_to_target() {
+        let mut genesis = [0u8; 32];
+        genesis[26..28].copy_from_slice(&[0xff, 0xff]);
+        assert_eq!(CompactTarget::from(0x1d00_ffff).to_target(), genesis);
+
+        let mut expected = [0u8; 32];
+        expected[25..28].copy_from_slice(&[0x56, 0x34, 0x12]);
+        assert_eq!(CompactTarget::from(0x1c12_3456).to_target(), expected);
+
+        let mut small = [0u8; 32];
+        small[0] = 0x12;
+        assert_eq!(CompactTarget::from(0x0112_3456).to_target(), small);
+
+        // The sign bit is ignored.
+        assert_eq!(CompactTarget::from(0x1d80_ffff).to_target(), genesis);
+    }
+
+    #[test]
+    fn test_compact_target_from_target() {
+        for bits in [
+            0x1d00_ffff,
+            0x1c12_3456,
+            0x207f_ffff,
+            0x1b04_04cb,
+            0x0300_8000,
+        ] {
+            let compact = CompactTarget::from(bits);
+            assert_eq!(CompactTarget::from_target(&compact.to_target()), compact);
+        }
+
+        // Only the three most significant bytes are kept.
+        let limit = ChainType::Mainnet.pow_limit();
+        assert_eq!(
+            CompactTarget::from_target(&limit).to_consensus(),
+            0x1d00_ffff
+        );
+
+        // A word with its high bit set moves up a byte.
+        let mut target = [0u8; 32];
+        target[0] = 0x80;
+        assert_eq!(
+            CompactTarget::from_target(&target).to_consensus(),
+            0x0200_8000
+        );
+
+        assert_eq!(CompactTarget::from_target(&[0u8; 32]).to_consensus(), 0);
+    }
+
+    #[test]
+    fn test_compact_target_difficulty() {
+        let genesis = CompactTarget::from(0x1d00_ffff);
+        assert_eq!(genesis.difficulty(ChainType::Mainnet), 1.0);
+
+        let harder = CompactTarget::from(0x1cf8_8f6f).difficulty(ChainType::Mainnet);
+        assert!((harder - 2.123_386_557_135_080_7).abs() < 1e-12);
+
+        let hardest = CompactTarget::from(0x1234_5678).difficulty(ChainType::Mainnet);
+        assert!((hardest / 5.913_134_931_067_755e24 - 1.0).abs() < 1e-12);
+
+        let regtest = CompactTarget::from(0x207f_ffff);
+        assert_eq!(regtest.difficulty(ChainType::Regtest), 1.0);
+        assert!(regtest.difficulty(ChainType::Mainnet) < 1e-9);
+
+        assert!(CompactTarget::from(0)
+            .difficulty(ChainType::Mainnet)
+            .is_infinite());
+    }
+
+    #[test]
+    fn test_compact_target_derive_target() {
+        let mainnet_limit = ChainType::Mainnet.pow_limit();
+        let genesis = CompactTarget::from(0x1d00_ffff);
+        assert_eq!(
+            genesis.derive_target(&mainnet_limit),
+            Some(genesis.to_target())
+        );
+
+        // Negative, zero, overflowing and too easy targets are rejected.
+        assert_eq!(
+            CompactTarget::from(0x1d80_ffff).derive_target(&mainnet_limit),
+            None
+        );
+        assert_eq!(
+            CompactTarget::from(0x1d00_0000).derive_target(&mainnet_limit),
+            None
+        );
+        assert_eq!(
+            CompactTarget::from(0x0100_3456).derive_target(&mainnet_limit),
+            None
+        );
+        assert_eq!(
+            CompactTarget::from(0x2301_0000).derive_target(&[0xff; 32]),
+            None
+        );
+        assert_eq!(
+            CompactTarget::from(0x1d01_0000).derive_target(&mainnet_limit),
+            None
+        );
+
+        let regtest = CompactTarget::from(0x207f_ffff);
+        assert_eq!(
+            regtest.derive_target(&ChainType::Regtest.pow_limit()),
+            Some(regtest.to_target())
+        );
+    }
+
+    #[test]
+    fn test_cmp_uint256() {
+        let mut low = [0u8; 32];
+        low[0] = 0xff;
+        let mut high = [0u8; 32];
+        high[31] = 0x01;
+        assert_eq!(cmp_uint256(&low, &high), Ordering::Less);
+        assert_eq!(cmp_uint256(&high, &low), Ordering::Greater);
+        assert_eq!(cmp_uint256(&high, &high), Ordering::Equal);
+    }
+}
@@ -48,7 +48,13 @@
 //! let p2wpkh = ScriptPubkey::new(&hex::decode(p2wpkh_hex).unwrap()).unwrap();
 //! ```
 
-use std::{ffi::c_void, marker::PhantomData, panic};
+use std::{
+    borrow::Borrow,
+    ffi::c_void,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    panic,
+};
 
 use libbitcoinkernel_sys::{
     btck_ScriptPubkey, btck_script_pubkey_copy, btck_script_pubkey_create,
@@ -56,7 +62,7 @@ use libbitcoinkernel_sys::{
 };
 
 use crate::{
-    c_serialize,
+    c_serialize, encode_hex,
     ffi::{
         c_helpers,
         sealed::{AsPtr, FromMutPtr, FromPtr},
@@ -64,6 +70,9 @@ use crate::{
     KernelError,
 };
 
+/// Maximum size of a script in bytes. Larger scripts can never be spent.
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+
 /// Common operations for script pubkeys, implemented by both owned and borrowed types.
 ///
 /// This trait provides shared functionality for [`ScriptPubkey`] and [`ScriptPubkeyRef`],
@@ -88,6 +97,22 @@ pub trait ScriptPubkeyExt: AsPtr<btck_ScriptPubkey> {
         .expect("Script pubkey to_bytes should never fail")
     }
 
+    /// Returns the raw script bytes as a lowercase hex string.
+    ///
+    /// The script is encoded without a length prefix, matching
+    /// [`to_bytes`](ScriptPubkeyExt::to_bytes).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ScriptPubkey};
+    /// let script = ScriptPubkey::new(&[0x76, 0xa9]).unwrap();
+    /// assert_eq!(script.consensus_encode_hex(), "76a9");
+    /// ```
+    fn consensus_encode_hex(&self) -> String {
+        encode_hex(self.as_bytes())
+    }
+
     /// Returns a zero-copy view of the script's raw bytes.
     /// Unlike [`to_bytes`](ScriptPubkeyExt::to_bytes), this does not allocate.
     /// The returned slice borrows directly from kernel-managed memory and is
@@ -141,6 +166,117 @@ pub trait ScriptPubkeyExt: AsPtr<btck_ScriptPubkey> {
         }
         unsafe { std::slice::from_raw_parts(out.ptr, out.len) }
     }
+
+    /// Returns the length of the script in bytes, without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ScriptPubkey};
+    /// let script = ScriptPubkey::new(&[0x76, 0xa9]).unwrap();
+    /// assert_eq!(script.len(), 2);
+    /// ```
+    fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    /// Returns `true` if the script has no bytes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the script is larger than [`MAX_SCRIPT_SIZE`].
+    ///
+    /// Such an output can never be spent, so Bitcoin Core treats it as
+    /// provably unspendable and never adds it to the UTXO set.
+    fn is_oversized(&self) -> bool {
+        self.len() > MAX_SCRIPT_SIZE
+    }
+
+    /// Returns `true` if the script matches the witness program template
+    /// `OP_n <2 to 40 byte push>` of BIP 141, for any version `n` from 0 to
+    /// 16.
+    fn is_witness_program(&self) -> bool {
+        witness_program(self.as_bytes()).is_some()
+    }
+
+    /// Returns the witness version of a witness program: 0 for segwit v0, 1
+    /// for taproot, up to 16.
+    ///
+    /// Future versions are returned too, so indexers can recognize outputs
+    /// whose version they do not understand yet. Returns `None` if the script
+    /// is not a witness program.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ScriptPubkey};
+    /// let mut p2tr = vec![0x51, 0x20];
+    /// p2tr.extend_from_slice(&[0xab; 32]);
+    /// let script = ScriptPubkey::new(&p2tr).unwrap();
+    /// assert_eq!(script.witness_version(), Some(1));
+    /// assert_eq!(script.witness_program(), Some(&[0xab; 32][..]));
+    /// ```
+    fn witness_version(&self) -> Option<u8> {
+        witness_program(self.as_bytes()).map(|(version, _)| version)
+    }
+
+    /// Returns the program of a witness program, without the version and
+    /// push opcodes, or `None` if the script is not a witness program.
+    ///
+    /// This is the 20-byte key hash or 32-byte script hash for segwit v0 and
+    /// the 32-byte output key for taproot.
+    fn witness_program(&self) -> Option<&[u8]> {
+        witness_program(self.as_bytes()).map(|(_, program)| program)
+    }
+
+    /// Parses a bare multisig script of the form
+    /// `OP_m <pubkey>... OP_n OP_CHECKMULTISIG`.
+    ///
+    /// Returns the number of required signatures `m` and the public keys, or
+    /// `None` if the script is not a well-formed multisig script. Both
+    /// compressed (33 byte) and uncompressed (65 byte) public keys are
+    /// accepted, and `m` and `n` must satisfy `1 <= m <= n <= 16`.
+    ///
+    /// For P2SH multisig, the output script only commits to a hash, so parse
+    /// the redeem script revealed in the spending input instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ScriptPubkey};
+    /// # let script_bytes: Vec<u8> = vec![];
+    /// let script = ScriptPubkey::new(&script_bytes).unwrap();
+    /// if let Some((m, pubkeys)) = script.parse_multisig() {
+    ///     println!("{}-of-{} multisig", m, pubkeys.len());
+    /// }
+    /// ```
+    fn parse_multisig(&self) -> Option<(u8, Vec<Vec<u8>>)> {
+        let (required, pubkeys) = parse_multisig(self.as_bytes())?;
+        Some((required, pubkeys.into_iter().map(<[u8]>::to_vec).collect()))
+    }
+}
+
+/// Access to the script pubkey of anything that carries one.
+///
+/// Implemented by [`TxOut`](crate::TxOut), [`TxOutRef`](crate::TxOutRef),
+/// [`Coin`](crate::Coin) and [`CoinRef`](crate::CoinRef), so functions that
+/// only need the script, such as address extraction, can accept any of them.
+/// Each implementation forwards to
+/// [`TxOutExt::script_pubkey`](crate::prelude::TxOutExt::script_pubkey) or
+/// [`CoinExt::script_pubkey`](crate::prelude::CoinExt::script_pubkey).
+///
+/// # Examples
+/// ```no_run
+/// use bitcoinkernel::{prelude::*, HasScriptPubkey};
+///
+/// fn is_p2tr(output: &impl HasScriptPubkey) -> bool {
+///     output.locking_script().witness_version() == Some(1)
+/// }
+/// ```
+pub trait HasScriptPubkey {
+    /// Returns the script pubkey that locks the output.
+    fn locking_script(&self) -> ScriptPubkeyRef<'_>;
 }
 
 /// A single script pubkey containing spending conditions for a [`crate::TxOut`].
@@ -247,6 +383,47 @@ impl Drop for ScriptPubkey {
     }
 }
 
+impl PartialEq for ScriptPubkey {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<ScriptPubkeyRef<'_>> for ScriptPubkey {
+    fn eq(&self, other: &ScriptPubkeyRef<'_>) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for ScriptPubkey {}
+
+/// Hashes the raw script bytes, so a script hashes like its
+/// [`ScriptPubkeyRef`] and like the byte slice it borrows as.
+impl Hash for ScriptPubkey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+/// Allows maps keyed by [`ScriptPubkey`] to be queried with raw bytes, for
+/// example a [`ScriptPubkeyRef`]'s [`as_bytes`](ScriptPubkeyExt::as_bytes),
+/// without copying the script.
+///
+/// # Examples
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, ScriptPubkey};
+/// # use std::collections::HashMap;
+/// let script = ScriptPubkey::new(&[0x51]).unwrap();
+/// let mut outputs = HashMap::new();
+/// outputs.insert(script.clone(), 1);
+/// assert_eq!(outputs.get(script.as_ref().as_bytes()), Some(&1));
+/// ```
+impl Borrow<[u8]> for ScriptPubkey {
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
 impl TryFrom<&[u8]> for ScriptPubkey {
     type Error = KernelError;
 
@@ -255,6 +432,14 @@ impl TryFrom<&[u8]> for ScriptPubkey {
     }
 }
 
+impl TryFrom<Vec<u8>> for ScriptPubkey {
+    type Error = KernelError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        ScriptPubkey::new(&bytes)
+    }
+}
+
 impl From<ScriptPubkey> for Vec<u8> {
     fn from(script: ScriptPubkey) -> Self {
         script.to_bytes()
@@ -306,6 +491,14 @@ impl<'a> ScriptPubkeyRef<'a> {
             inner: unsafe { btck_script_pubkey_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> ScriptPubkey {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_ScriptPubkey> for ScriptPubkeyRef<'a> {
@@ -345,6 +538,242 @@ impl<'a> Clone for ScriptPubkeyRef<'a> {
 
 impl<'a> Copy for ScriptPubkeyRef<'a> {}
 
+impl<'a> PartialEq for ScriptPubkeyRef<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<ScriptPubkey> for ScriptPubkeyRef<'_> {
+    fn eq(&self, other: &ScriptPubkey) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<'a> Eq for ScriptPubkeyRef<'a> {}
+
+impl<'a> Hash for ScriptPubkeyRef<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl<'a> Borrow<[u8]> for ScriptPubkeyRef<'a> {
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Generates standard P2PKH, P2SH, P2WPKH, P2WSH and P2TR scripts with
+/// arbitrary hashes and keys, or arbitrary bytes.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ScriptPubkey {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let script = match u.int_in_range(0u8..=5)? {
+            0 => [&[0x76, 0xa9, 0x14][..], u.bytes(20)?, &[0x88, 0xac]].concat(),
+            1 => [&[0xa9, 0x14][..], u.bytes(20)?, &[0x87]].concat(),
+            2 => [&[0x00, 0x14][..], u.bytes(20)?].concat(),
+            3 => [&[0x00, 0x20][..], u.bytes(32)?].concat(),
+            4 => [&[0x51, 0x20][..], u.bytes(32)?].concat(),
+            _ => u.arbitrary::<Vec<u8>>()?,
+        };
+        ScriptPubkey::new(&script).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+const OP_0: u8 = 0x00;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_1: u8 = 0x51;
+const OP_16: u8 = 0x60;
+const OP_EQUAL: u8 = 0x87;
+const OP_HASH160: u8 = 0xa9;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKSIGVERIFY: u8 = 0xad;
+const OP_CHECKMULTISIG: u8 = 0xae;
+const OP_CHECKMULTISIGVERIFY: u8 = 0xaf;
+
+/// Sigops counted for a bare `OP_CHECKMULTISIG` whose key count is not known.
+const MAX_PUBKEYS_PER_MULTISIG: u32 = 20;
+
+/// A single decoded script operation.
+pub(crate) struct Instruction<'a> {
+    pub(crate) opcode: u8,
+    /// The pushed data for push opcodes, `None` for all other opcodes.
+    pub(crate) push_data: Option<&'a [u8]>,
+}
+
+/// Iterator over the operations of a raw script.
+///
+/// Yields `Err(())` once if a push runs past the end of the script, then stops.
+pub(crate) struct Instructions<'a> {
+    script: &'a [u8],
+    pos: usize,
+    done: bool,
+}
+
+impl<'a> Instructions<'a> {
+    pub(crate) fn new(script: &'a [u8]) -> Self {
+        Instructions {
+            script,
+            pos: 0,
+            done: false,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let data = self.script.get(self.pos..end)?;
+        self.pos = end;
+        Some(data)
+    }
+
+    fn next_instruction(&mut self) -> Option<Instruction<'a>> {
+        let opcode = *self.take(1)?.first()?;
+        if opcode > OP_PUSHDATA4 {
+            return Some(Instruction {
+                opcode,
+                push_data: None,
+            });
+        }
+        let len = match opcode {
+            OP_PUSHDATA1 => self.take(1)?[0] as usize,
+            OP_PUSHDATA2 => u16::from_le_bytes(self.take(2)?.try_into().ok()?) as usize,
+            OP_PUSHDATA4 => u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize,
+            n => n as usize,
+        };
+        Some(Instruction {
+            opcode,
+            push_data: Some(self.take(len)?),
+        })
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Result<Instruction<'a>, ()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.pos >= self.script.len() {
+            return None;
+        }
+        match self.next_instruction() {
+            Some(instruction) => Some(Ok(instruction)),
+            None => {
+                self.done = true;
+                Some(Err(()))
+            }
+        }
+    }
+}
+
+/// Counts the signature operations in a raw script.
+///
+/// With `accurate` set, an `OP_CHECKMULTISIG` preceded by `OP_1`..`OP_16`
+/// counts as that many sigops instead of the maximum of 20. This mirrors
+/// Bitcoin Core's `CScript::GetSigOpCount`.
+pub(crate) fn sigop_count(script: &[u8], accurate: bool) -> u32 {
+    let mut count = 0;
+    let mut last_opcode = None;
+    for instruction in Instructions::new(script) {
+        let Ok(instruction) = instruction else {
+            break;
+        };
+        match instruction.opcode {
+            OP_CHECKSIG | OP_CHECKSIGVERIFY => count += 1,
+            OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                count += match last_opcode {
+                    Some(n @ OP_1..=OP_16) if accurate => u32::from(n - OP_1 + 1),
+                    _ => MAX_PUBKEYS_PER_MULTISIG,
+                }
+            }
+            _ => {}
+        }
+        last_opcode = Some(instruction.opcode);
+    }
+    count
+}
+
+/// Returns true if the script is a pay-to-script-hash output script.
+pub(crate) fn is_pay_to_script_hash(script: &[u8]) -> bool {
+    script.len() == 23 && script[0] == OP_HASH160 && script[1] == 0x14 && script[22] == OP_EQUAL
+}
+
+/// Returns the last item a push-only script pushes onto the stack, or `None`
+/// if the script contains non-push operations.
+///
+/// Small integer opcodes count as pushes but push no data, so an empty slice
+/// is returned for them, as well as for an empty script.
+pub(crate) fn last_pushed_item(script: &[u8]) -> Option<&[u8]> {
+    let mut last: &[u8] = &[];
+    for instruction in Instructions::new(script) {
+        let instruction = instruction.ok()?;
+        if instruction.opcode > OP_16 {
+            return None;
+        }
+        last = instruction.push_data.unwrap_or(&[]);
+    }
+    Some(last)
+}
+
+/// Returns true if `data` has the size implied by its public key prefix, as
+/// Bitcoin Core's `CPubKey::ValidSize` checks.
+fn is_valid_pubkey_size(data: &[u8]) -> bool {
+    match data.first() {
+        Some(0x02 | 0x03) => data.len() == 33,
+        Some(0x04 | 0x06 | 0x07) => data.len() == 65,
+        _ => false,
+    }
+}
+
+/// Returns the required signature count and public keys of a bare multisig
+/// script, following Bitcoin Core's `MatchMultisig`.
+pub(crate) fn parse_multisig(script: &[u8]) -> Option<(u8, Vec<&[u8]>)> {
+    let mut instructions = Instructions::new(script);
+    let required = match instructions.next()?.ok()?.opcode {
+        op @ OP_1..=OP_16 => op - OP_1 + 1,
+        _ => return None,
+    };
+
+    let mut pubkeys = Vec::new();
+    let total = loop {
+        let instruction = instructions.next()?.ok()?;
+        match instruction.push_data {
+            Some(data) if is_valid_pubkey_size(data) => pubkeys.push(data),
+            Some(_) => return None,
+            None => match instruction.opcode {
+                op @ OP_1..=OP_16 => break op - OP_1 + 1,
+                _ => return None,
+            },
+        }
+    };
+
+    let last = instructions.next()?.ok()?;
+    if last.opcode != OP_CHECKMULTISIG || instructions.next().is_some() {
+        return None;
+    }
+    if usize::from(total) != pubkeys.len() || required > total {
+        return None;
+    }
+    Some((required, pubkeys))
+}
+
+/// Returns the witness version and program if the script is a witness program.
+pub(crate) fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    if script.len() < 4 || script.len() > 42 {
+        return None;
+    }
+    let version = match script[0] {
+        OP_0 => 0,
+        op @ OP_1..=OP_16 => op - OP_1 + 1,
+        _ => return None,
+    };
+    if script[1] as usize + 2 != script.len() {
+        return None;
+    }
+    Some((version, &script[2..]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -396,6 +825,9 @@ mod tests {
         let script_data: &[u8] = &[0x76, 0xa9, 0x14];
         let script = ScriptPubkey::try_from(script_data);
         assert!(script.is_ok());
+
+        let script = ScriptPubkey::try_from(script_data.to_vec()).unwrap();
+        assert_eq!(script.as_bytes(), script_data);
     }
 
     #[test]
@@ -406,6 +838,16 @@ mod tests {
         assert_eq!(bytes, script_data);
     }
 
+    #[test]
+    fn test_scriptpubkey_consensus_encode_hex() {
+        let script = ScriptPubkey::new(&[0x76, 0xa9, 0x14, 0xab, 0x0f]).unwrap();
+        assert_eq!(script.consensus_encode_hex(), "76a914ab0f");
+        assert_eq!(script.as_ref().consensus_encode_hex(), "76a914ab0f");
+
+        let empty = ScriptPubkey::new(&[]).unwrap();
+        assert_eq!(empty.consensus_encode_hex(), "");
+    }
+
     #[test]
     fn test_scriptpubkey_as_bytes_empty() {
         let script = ScriptPubkey::new(&[]).unwrap();
@@ -496,6 +938,15 @@ mod tests {
         assert_eq!(bytes, vec![0x76, 0xa9, 0x14]);
     }
 
+    #[test]
+    fn test_scriptpubkey_ref_into_owned() {
+        let owned = {
+            let script = ScriptPubkey::new(&[0x76, 0xa9]).unwrap();
+            script.as_ref().into_owned()
+        };
+        assert_eq!(owned.to_bytes(), vec![0x76, 0xa9]);
+    }
+
     #[test]
     fn test_scriptpubkey_ref_into_vec_from_ref() {
         let script_data = vec![0x76, 0xa9, 0x14];
@@ -516,6 +967,41 @@ mod tests {
         assert_eq!(bytes, script_data);
     }
 
+    #[test]
+    fn test_scriptpubkey_eq_and_hash() {
+        use std::collections::{hash_map::DefaultHasher, HashMap};
+
+        fn hash_of(value: &(impl Hash + ?Sized)) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let p2pkh = ScriptPubkey::new(&[0x76, 0xa9, 0x14]).unwrap();
+        let same = ScriptPubkey::new(&[0x76, 0xa9, 0x14]).unwrap();
+        let other = ScriptPubkey::new(&[0x51]).unwrap();
+
+        assert_eq!(p2pkh, same);
+        assert_ne!(p2pkh, other);
+        assert!(p2pkh == same.as_ref());
+        assert!(same.as_ref() == p2pkh);
+        assert!(p2pkh.as_ref() == same.as_ref());
+        assert!(p2pkh.as_ref() != other.as_ref());
+
+        assert_eq!(hash_of(&p2pkh), hash_of(&same));
+        assert_eq!(hash_of(&p2pkh), hash_of(&same.as_ref()));
+        assert_eq!(hash_of(&p2pkh), hash_of(p2pkh.as_bytes()));
+
+        let mut outputs: HashMap<ScriptPubkey, Vec<u32>> = HashMap::new();
+        outputs.entry(p2pkh.clone()).or_default().push(0);
+        outputs.entry(same).or_default().push(1);
+        outputs.entry(other).or_default().push(2);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[&p2pkh], vec![0, 1]);
+        assert_eq!(outputs.get(p2pkh.as_ref().as_bytes()), Some(&vec![0, 1]));
+        assert_eq!(outputs.get([0x51].as_slice()), Some(&vec![2]));
+    }
+
     #[test]
     fn test_owned_and_ref_polymorphism() {
         let script_data = vec![0x76, 0xa9, 0x14];
@@ -544,6 +1030,27 @@ mod tests {
         assert_eq!(script.to_bytes(), script_data);
     }
 
+    #[test]
+    fn test_script_len_and_is_oversized() {
+        let empty = ScriptPubkey::new(&[]).unwrap();
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+        assert!(!empty.is_oversized());
+
+        let p2wpkh = ScriptPubkey::new(&[0x00; 22]).unwrap();
+        assert_eq!(p2wpkh.len(), 22);
+        assert_eq!(p2wpkh.as_ref().len(), 22);
+        assert!(!p2wpkh.is_empty());
+
+        let max = ScriptPubkey::new(&vec![0x51; MAX_SCRIPT_SIZE]).unwrap();
+        assert_eq!(max.len(), MAX_SCRIPT_SIZE);
+        assert!(!max.is_oversized());
+
+        let oversized = ScriptPubkey::new(&vec![0x51; MAX_SCRIPT_SIZE + 1]).unwrap();
+        assert!(oversized.is_oversized());
+        assert!(oversized.as_ref().is_oversized());
+    }
+
     #[test]
     fn test_single_byte_script() {
         let script_data = vec![0x51];
@@ -625,4 +1132,154 @@ mod tests {
         let script = ScriptPubkey::new(&multisig).unwrap();
         assert_eq!(script.to_bytes(), multisig);
     }
+
+    #[test]
+    fn test_sigop_count() {
+        let p2pkh = hex::decode("76a914deadbeefdeadbeefdeadbeefdeadbeefdeadbeef88ac").unwrap();
+        assert_eq!(sigop_count(&p2pkh, false), 1);
+        assert_eq!(sigop_count(&p2pkh, true), 1);
+
+        // OP_2 <pk> <pk> <pk> OP_3 OP_CHECKMULTISIG
+        let mut multisig = vec![0x52];
+        for _ in 0..3 {
+            multisig.push(33);
+            multisig.extend_from_slice(&[0x02; 33]);
+        }
+        multisig.extend_from_slice(&[0x53, 0xae]);
+        assert_eq!(sigop_count(&multisig, false), 20);
+        assert_eq!(sigop_count(&multisig, true), 3);
+
+        // A truncated push stops counting.
+        assert_eq!(sigop_count(&[0xac, 0x4c, 0x05, 0xac], false), 1);
+    }
+
+    #[test]
+    fn test_script_classification() {
+        let p2sh = hex::decode("a914deadbeefdeadbeefdeadbeefdeadbeefdeadbeef87").unwrap();
+        assert!(is_pay_to_script_hash(&p2sh));
+        assert!(witness_program(&p2sh).is_none());
+
+        let p2wpkh = hex::decode("0014deadbeefdeadbeefdeadbeefdeadbeefdeadbeef").unwrap();
+        assert!(!is_pay_to_script_hash(&p2wpkh));
+        assert_eq!(witness_program(&p2wpkh), Some((0, &p2wpkh[2..])));
+
+        let p2tr = [&[0x51, 0x20][..], &[0xab; 32][..]].concat();
+        assert_eq!(witness_program(&p2tr).map(|(v, _)| v), Some(1));
+    }
+
+    #[test]
+    fn test_script_witness_version() {
+        let p2wpkh = ScriptPubkey::new(
+            &hex::decode("0014deadbeefdeadbeefdeadbeefdeadbeefdeadbeef").unwrap(),
+        )
+        .unwrap();
+        assert!(p2wpkh.is_witness_program());
+        assert_eq!(p2wpkh.witness_version(), Some(0));
+        assert_eq!(p2wpkh.witness_program(), Some(&p2wpkh.as_bytes()[2..]));
+
+        let p2tr = ScriptPubkey::new(&[&[0x51, 0x20][..], &[0xab; 32][..]].concat()).unwrap();
+        assert_eq!(p2tr.as_ref().witness_version(), Some(1));
+        assert_eq!(p2tr.witness_program(), Some(&[0xab; 32][..]));
+
+        // Unknown versions and program lengths are still witness programs.
+        let v16 = ScriptPubkey::new(&[0x60, 0x02, 0x01, 0x02]).unwrap();
+        assert_eq!(v16.witness_version(), Some(16));
+        assert_eq!(v16.witness_program(), Some(&[0x01, 0x02][..]));
+        let v2 = ScriptPubkey::new(&[&[0x52, 0x28][..], &[0x00; 40][..]].concat()).unwrap();
+        assert_eq!(v2.witness_version(), Some(2));
+
+        // Programs of 1 or 41 bytes and non-push versions are not.
+        let too_short = ScriptPubkey::new(&[0x51, 0x01, 0x00]).unwrap();
+        assert!(!too_short.is_witness_program());
+        let too_long = ScriptPubkey::new(&[&[0x51, 0x29][..], &[0x00; 41][..]].concat()).unwrap();
+        assert_eq!(too_long.witness_version(), None);
+        let op_1negate = ScriptPubkey::new(&[0x4f, 0x02, 0x01, 0x02]).unwrap();
+        assert_eq!(op_1negate.witness_version(), None);
+
+        let p2sh = ScriptPubkey::new(
+            &hex::decode("a914deadbeefdeadbeefdeadbeefdeadbeefdeadbeef87").unwrap(),
+        )
+        .unwrap();
+        assert!(!p2sh.is_witness_program());
+        assert_eq!(p2sh.witness_program(), None);
+    }
+
+    #[test]
+    fn test_parse_multisig() {
+        let compressed = [[0x02; 33], [0x03; 33]];
+        let mut uncompressed = [0x11; 65];
+        uncompressed[0] = 0x04;
+
+        // 1-of-2 with compressed keys.
+        let mut script = vec![OP_1];
+        for key in &compressed {
+            script.push(33);
+            script.extend_from_slice(key);
+        }
+        script.extend_from_slice(&[OP_1 + 1, OP_CHECKMULTISIG]);
+        let (m, pubkeys) = ScriptPubkey::new(&script)
+            .unwrap()
+            .parse_multisig()
+            .unwrap();
+        assert_eq!(m, 1);
+        assert_eq!(
+            pubkeys,
+            vec![compressed[0].to_vec(), compressed[1].to_vec()]
+        );
+
+        // 2-of-3 mixing compressed and uncompressed keys.
+        let mut script = vec![OP_1 + 1, 33];
+        script.extend_from_slice(&compressed[0]);
+        script.push(65);
+        script.extend_from_slice(&uncompressed);
+        script.push(33);
+        script.extend_from_slice(&compressed[1]);
+        script.extend_from_slice(&[OP_1 + 2, OP_CHECKMULTISIG]);
+        let (m, pubkeys) = parse_multisig(&script).unwrap();
+        assert_eq!(m, 2);
+        assert_eq!(pubkeys.len(), 3);
+        assert_eq!(pubkeys[1], &uncompressed[..]);
+
+        // m greater than n.
+        let mut bad = script.clone();
+        bad[0] = OP_1 + 3;
+        assert!(parse_multisig(&bad).is_none());
+        // m of zero.
+        bad[0] = OP_0;
+        assert!(parse_multisig(&bad).is_none());
+        // n does not match the number of keys.
+        let mut bad = script.clone();
+        let n_pos = bad.len() - 2;
+        bad[n_pos] = OP_1 + 3;
+        assert!(parse_multisig(&bad).is_none());
+        // OP_CHECKMULTISIGVERIFY instead of OP_CHECKMULTISIG.
+        let mut bad = script.clone();
+        *bad.last_mut().unwrap() = OP_CHECKMULTISIGVERIFY;
+        assert!(parse_multisig(&bad).is_none());
+        // Trailing operations.
+        let mut bad = script.clone();
+        bad.push(OP_1);
+        assert!(parse_multisig(&bad).is_none());
+        // Truncated public key push.
+        assert!(parse_multisig(&script[..20]).is_none());
+        // Public key with a mismatched prefix.
+        let mut bad = script.clone();
+        bad[2] = 0x04;
+        assert!(parse_multisig(&bad).is_none());
+        // Not multisig at all.
+        assert!(parse_multisig(&[]).is_none());
+        assert!(parse_multisig(&[OP_HASH160, 0x14]).is_none());
+    }
+
+    #[test]
+    fn test_last_pushed_item() {
+        assert_eq!(last_pushed_item(&[]), Some(&[][..]));
+        assert_eq!(
+            last_pushed_item(&[0x01, 0xaa, 0x02, 0xbb, 0xcc]),
+            Some(&[0xbb, 0xcc][..])
+        );
+        assert_eq!(last_pushed_item(&[0x01, 0xaa, 0x51]), Some(&[][..]));
+        assert_eq!(last_pushed_item(&[0x01, 0xaa, 0xac]), None);
+        assert_eq!(last_pushed_item(&[0x02, 0xaa]), None);
+    }
 }
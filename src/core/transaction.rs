@@ -144,7 +144,11 @@
 use std::{
     ffi::c_void,
     fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    io::Write,
+    iter::{Map, Zip},
     marker::PhantomData,
+    ops::RangeFrom,
 };
 
 use libbitcoinkernel_sys::{
@@ -172,7 +176,7 @@ use libbitcoinkernel_sys::{
 };
 
 use crate::{
-    c_serialize,
+    c_serialize, c_serialize_to, encode_hex,
     ffi::{
         c_helpers::present,
         sealed::{AsPtr, FromMutPtr, FromPtr},
@@ -180,7 +184,57 @@ use crate::{
     KernelError, ScriptPubkeyExt, ValidationMode,
 };
 
-use super::script::ScriptPubkeyRef;
+use super::{
+    block::{CoinExt, TransactionSpentOutputsExt},
+    encoding::{compact_size_len, decode_transaction, write_compact_size, RawTransaction},
+    fee_rate::FeeRate,
+    script::{self, HasScriptPubkey, ScriptPubkeyRef},
+};
+
+/// Scale factor between legacy sigops and sigop cost.
+const WITNESS_SCALE_FACTOR: u32 = 4;
+
+fn legacy_sigop_count(raw: &RawTransaction) -> u32 {
+    let input_sigops: u32 = raw
+        .inputs
+        .iter()
+        .map(|input| script::sigop_count(input.script_sig, false))
+        .sum();
+    let output_sigops: u32 = raw
+        .output_scripts
+        .iter()
+        .map(|script_pubkey| script::sigop_count(script_pubkey, false))
+        .sum();
+    input_sigops + output_sigops
+}
+
+fn witness_sigop_count(version: u8, program: &[u8], witness: &[&[u8]]) -> u32 {
+    if version != 0 {
+        return 0;
+    }
+    match (program.len(), witness.last()) {
+        (20, _) => 1,
+        (32, Some(witness_script)) => script::sigop_count(witness_script, true),
+        _ => 0,
+    }
+}
+
+/// Lock times below this value are interpreted as block heights, and lock
+/// times at or above it as Unix timestamps.
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+/// Input sequence number that disables lock time checks for that input.
+pub const SEQUENCE_FINAL: u32 = 0xffffffff;
+
+/// Highest input sequence number that signals opt-in replace-by-fee (BIP 125).
+pub const MAX_BIP125_RBF_SEQUENCE: u32 = 0xfffffffd;
+
+/// Number of satoshis in one bitcoin.
+pub const COIN: i64 = 100_000_000;
+
+/// Maximum amount of satoshis a single output, or the sum of a transaction's
+/// outputs, may carry.
+pub const MAX_MONEY: i64 = 21_000_000 * COIN;
 
 /// Common operations for transactions, implemented by both owned and borrowed types.
 ///
@@ -251,6 +305,37 @@ pub trait TransactionExt: AsPtr<btck_Transaction> {
         Ok(tx_out_ref)
     }
 
+    /// Returns the value in satoshis of the output at the specified index.
+    ///
+    /// Equivalent to `output(index)?.value()`, but reads the amount directly
+    /// without constructing a [`TxOutRef`]. Prefer this in loops summing the
+    /// values of many outputs.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::OutOfBounds`] if the index is invalid.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction, KernelError};
+    /// # fn example(tx: &Transaction) -> Result<(), KernelError> {
+    /// let mut total = 0;
+    /// for index in 0..tx.output_count() {
+    ///     total += tx.output_value(index)?;
+    /// }
+    /// println!("Total output value: {} satoshis", total);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn output_value(&self, index: usize) -> Result<i64, KernelError> {
+        if index >= self.output_count() {
+            return Err(KernelError::OutOfBounds);
+        }
+
+        Ok(unsafe {
+            btck_transaction_output_get_amount(btck_transaction_get_output_at(self.as_ptr(), index))
+        })
+    }
+
     /// Returns the number of inputs in this transaction.
     ///
     /// # Examples
@@ -345,6 +430,54 @@ pub trait TransactionExt: AsPtr<btck_Transaction> {
         })
     }
 
+    /// Serializes the transaction to Bitcoin wire format as a lowercase hex
+    /// string.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if serialization fails.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction, KernelError};
+    /// # fn example(tx: &Transaction) -> Result<(), KernelError> {
+    /// println!("{}", tx.consensus_encode_hex()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn consensus_encode_hex(&self) -> Result<String, KernelError> {
+        Ok(encode_hex(&self.consensus_encode()?))
+    }
+
+    /// Serializes the transaction to Bitcoin wire format, writing it into `writer`.
+    ///
+    /// Unlike [`consensus_encode`](Self::consensus_encode), this streams the
+    /// encoded bytes directly into the writer without allocating an
+    /// intermediate buffer.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if serialization fails or
+    /// the writer returns an error.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction, KernelError};
+    /// # fn example(tx: &Transaction) -> Result<(), KernelError> {
+    /// let mut buffer = [0u8; 1024];
+    /// let mut cursor = std::io::Cursor::new(&mut buffer[..]);
+    /// tx.consensus_encode_to(&mut cursor)?;
+    /// println!("Transaction is {} bytes", cursor.position());
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn consensus_encode_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), KernelError>
+    where
+        Self: Sized,
+    {
+        c_serialize_to(writer, |callback, user_data| unsafe {
+            btck_transaction_to_bytes(self.as_ptr(), callback, user_data)
+        })
+    }
+
     /// Returns an iterator over all inputs in this transaction.
     ///
     /// The iterator yields [`TxInRef`] instances in the order they appear in the
@@ -368,6 +501,26 @@ pub trait TransactionExt: AsPtr<btck_Transaction> {
         TxInIter::new(unsafe { TransactionRef::from_ptr(self.as_ptr()) })
     }
 
+    /// Returns an iterator over the outpoints spent by this transaction, in
+    /// input order.
+    ///
+    /// Coinbase transactions spend no output, but their single input still
+    /// references the null outpoint, which is yielded like any other. Check
+    /// [`TxOutPointExt::is_null`] to skip it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example(tx: &Transaction) {
+    /// for outpoint in tx.spent_outpoints().filter(|outpoint| !outpoint.is_null()) {
+    ///     println!("Spends {}:{}", outpoint.txid(), outpoint.index());
+    /// }
+    /// # }
+    /// ```
+    fn spent_outpoints(&self) -> Map<TxInIter<'_>, fn(TxInRef<'_>) -> TxOutPointRef<'_>> {
+        self.inputs().map(|input| input.outpoint())
+    }
+
     /// Returns an iterator over all outputs in this transaction.
     ///
     /// The iterator yields [`TxOutRef`] instances in the order they appear in the
@@ -389,6 +542,57 @@ pub trait TransactionExt: AsPtr<btck_Transaction> {
         TxOutIter::new(unsafe { TransactionRef::from_ptr(self.as_ptr()) })
     }
 
+    /// Returns an iterator over all outputs in this transaction, paired with
+    /// their output index.
+    ///
+    /// The index is a `u32` vout, matching [`TxOutPointExt::index`], so it can
+    /// be used to build outpoints without casting.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example() -> Result<(), bitcoinkernel::KernelError> {
+    /// # let tx_data = vec![0u8; 100]; // placeholder
+    /// # let tx = Transaction::new(&tx_data)?;
+    /// for (vout, output) in tx.outputs_enumerated() {
+    ///     println!("{}:{} pays {} satoshis", tx.txid(), vout, output.value());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn outputs_enumerated(&self) -> Zip<RangeFrom<u32>, TxOutIter<'_>> {
+        (0..).zip(self.outputs())
+    }
+
+    /// Returns the witness stack of every input, in input order.
+    ///
+    /// The transaction is serialized and decoded once, so prefer this over
+    /// decoding per input when walking all witnesses. Inputs without witness
+    /// data get an empty [`Witness`].
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if the transaction cannot
+    /// be serialized for inspection.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction, KernelError};
+    /// # fn example(tx: &Transaction) -> Result<(), KernelError> {
+    /// let witnesses = tx.witnesses()?;
+    /// assert_eq!(witnesses.len(), tx.input_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn witnesses(&self) -> Result<Vec<Witness>, KernelError> {
+        let data = self.consensus_encode()?;
+        let raw = decode_transaction(&data)?;
+        Ok(raw
+            .inputs
+            .iter()
+            .map(|input| Witness::from_items(&input.witness))
+            .collect())
+    }
+
     /// Returns the transaction's `nLockTime` value.
     ///
     /// `nLockTime` is used to restrict when a transaction can be included in a block.
@@ -409,6 +613,283 @@ pub trait TransactionExt: AsPtr<btck_Transaction> {
         unsafe { btck_transaction_get_locktime(self.as_ptr()) }
     }
 
+    /// Returns true if the transaction is final at the given block height and time.
+    ///
+    /// This mirrors Bitcoin Core's `IsFinalTx`. A transaction is final if its
+    /// `nLockTime` is zero, if the lock time has already passed, or if every
+    /// input has a sequence number of [`SEQUENCE_FINAL`].
+    ///
+    /// A lock time below [`LOCKTIME_THRESHOLD`] is compared against `height`,
+    /// otherwise it is compared against `block_time`.
+    ///
+    /// # Arguments
+    /// * `height` - The height of the block the transaction would be included in
+    /// * `block_time` - The time to evaluate time-based lock times against,
+    ///   typically the median time past of the previous block
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example() -> Result<(), bitcoinkernel::KernelError> {
+    /// # let tx_data = vec![0u8; 100]; // placeholder
+    /// # let tx = Transaction::new(&tx_data)?;
+    /// if tx.is_final(800_000, 1_690_000_000) {
+    ///     println!("Transaction can be included in the next block");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn is_final(&self, height: i32, block_time: u32) -> bool {
+        let locktime = self.locktime();
+        if locktime == 0 {
+            return true;
+        }
+        let cutoff = if locktime < LOCKTIME_THRESHOLD {
+            i64::from(height)
+        } else {
+            i64::from(block_time)
+        };
+        if i64::from(locktime) < cutoff {
+            return true;
+        }
+        self.inputs()
+            .all(|input| input.sequence() == SEQUENCE_FINAL)
+    }
+
+    /// Returns true if the transaction signals opt-in replace-by-fee.
+    ///
+    /// A transaction signals as defined by BIP 125 if any of its inputs has a
+    /// sequence number of at most [`MAX_BIP125_RBF_SEQUENCE`]. This mirrors
+    /// Bitcoin Core's `SignalsOptInRBF` and ignores whether an unconfirmed
+    /// ancestor signals.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example(tx: &Transaction) {
+    /// if tx.is_rbf_signaling() {
+    ///     println!("Transaction can be replaced by a higher fee version");
+    /// }
+    /// # }
+    /// ```
+    fn is_rbf_signaling(&self) -> bool {
+        self.inputs()
+            .any(|input| input.sequence() <= MAX_BIP125_RBF_SEQUENCE)
+    }
+
+    /// Returns the number of legacy signature operations in this transaction.
+    ///
+    /// Counts the sigops in every input's scriptSig and every output's
+    /// scriptPubkey, treating each `OP_CHECKMULTISIG` as 20 sigops. This mirrors
+    /// Bitcoin Core's `GetLegacySigOpCount`.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if the transaction cannot
+    /// be serialized for inspection.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example() -> Result<(), bitcoinkernel::KernelError> {
+    /// # let tx_data = vec![0u8; 100]; // placeholder
+    /// # let tx = Transaction::new(&tx_data)?;
+    /// println!("Legacy sigops: {}", tx.legacy_sigop_count()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn legacy_sigop_count(&self) -> Result<u32, KernelError> {
+        let data = self.consensus_encode()?;
+        let raw = decode_transaction(&data)?;
+        Ok(legacy_sigop_count(&raw))
+    }
+
+    /// Returns the total signature operation cost of this transaction.
+    ///
+    /// The cost includes the legacy sigops scaled by the witness scale factor,
+    /// the sigops of P2SH redeem scripts and the sigops of witness programs,
+    /// following Bitcoin Core's `GetTransactionSigOpCost` with the P2SH and
+    /// segwit rules active. Coinbase transactions only have a legacy cost, so
+    /// `spent_outputs` is ignored for them.
+    ///
+    /// # Arguments
+    /// * `spent_outputs` - The outputs spent by this transaction's inputs, in input order
+    ///
+    /// # Errors
+    /// * [`KernelError::MismatchedOutputsSize`] - If the number of spent outputs
+    ///   does not match the number of inputs of a non-coinbase transaction
+    /// * [`KernelError::SerializationFailed`] - If the transaction cannot be
+    ///   serialized for inspection
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction, TxOut};
+    /// # fn example(tx: &Transaction, spent_outputs: &[TxOut]) -> Result<(), bitcoinkernel::KernelError> {
+    /// println!("Sigop cost: {}", tx.sigop_cost(spent_outputs)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn sigop_cost(&self, spent_outputs: &[impl TxOutExt]) -> Result<u32, KernelError>
+    where
+        Self: Sized,
+    {
+        let data = self.consensus_encode()?;
+        let raw = decode_transaction(&data)?;
+        let legacy_cost = legacy_sigop_count(&raw) * WITNESS_SCALE_FACTOR;
+
+        let is_coinbase = self.input_count() == 1 && self.input(0)?.outpoint().is_null();
+        if is_coinbase {
+            return Ok(legacy_cost);
+        }
+        if spent_outputs.len() != raw.inputs.len() {
+            return Err(KernelError::MismatchedOutputsSize);
+        }
+
+        let mut cost = legacy_cost;
+        for (input, spent_output) in raw.inputs.iter().zip(spent_outputs) {
+            let script_pubkey = spent_output.script_pubkey();
+            let prev_script = script_pubkey.as_bytes();
+            let redeem_script = if script::is_pay_to_script_hash(prev_script) {
+                script::last_pushed_item(input.script_sig)
+            } else {
+                None
+            };
+
+            if let Some(redeem_script) = redeem_script {
+                cost += script::sigop_count(redeem_script, true) * WITNESS_SCALE_FACTOR;
+            }
+
+            let witness_program = script::witness_program(prev_script)
+                .or_else(|| redeem_script.and_then(script::witness_program));
+            if let Some((version, program)) = witness_program {
+                cost += witness_sigop_count(version, program, &input.witness);
+            }
+        }
+        Ok(cost)
+    }
+
+    /// Returns the fee paid by this transaction in satoshis.
+    ///
+    /// The fee is the sum of the values of the coins spent by the inputs minus
+    /// the sum of the output values. A coinbase transaction spends no coins and
+    /// has no fee, so it should not be passed here; its entry is not part of a
+    /// block's spent outputs.
+    ///
+    /// # Arguments
+    /// * `spent` - The coins spent by this transaction, as read from a block's undo data
+    ///
+    /// # Errors
+    /// Returns [`KernelError::MismatchedOutputsSize`] if `spent` does not hold
+    /// exactly one coin per input, which indicates the transaction was paired
+    /// with the wrong spent outputs.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Block, BlockSpentOutputs};
+    /// # fn example(block: &Block, spent_outputs: &BlockSpentOutputs) -> Result<(), bitcoinkernel::KernelError> {
+    /// for (tx, spent) in block.transactions().skip(1).zip(spent_outputs.iter()) {
+    ///     println!("{} pays {} sat", tx.txid(), tx.fee(&spent)?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fee(&self, spent: &impl TransactionSpentOutputsExt) -> Result<i64, KernelError>
+    where
+        Self: Sized,
+    {
+        if spent.count() != self.input_count() {
+            return Err(KernelError::MismatchedOutputsSize);
+        }
+        let value_in: i64 = spent.coins().map(|coin| coin.value()).sum();
+        let value_out: i64 = self.outputs().map(|output| output.value()).sum();
+        Ok(value_in - value_out)
+    }
+
+    /// Returns the weight of this transaction in weight units.
+    ///
+    /// The weight is three times the size without witness data plus the full
+    /// serialized size, as defined by BIP 141. See [`vsize`](Self::vsize) for
+    /// the virtual size used for fee rates.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if the transaction cannot
+    /// be serialized for inspection.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example(tx: &Transaction) -> Result<(), bitcoinkernel::KernelError> {
+    /// println!("Weight: {} WU", tx.weight()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn weight(&self) -> Result<usize, KernelError> {
+        let data = self.consensus_encode()?;
+        let raw = decode_transaction(&data)?;
+        let stripped_size = data.len() - raw.witness_size;
+        Ok(stripped_size * (WITNESS_SCALE_FACTOR as usize - 1) + data.len())
+    }
+
+    /// Returns the virtual size of this transaction in virtual bytes.
+    ///
+    /// The virtual size is the [`weight`](Self::weight) divided by four,
+    /// rounded up, as defined by BIP 141. A transaction of 561 weight units
+    /// has a virtual size of 141 vbytes, not 140.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::SerializationFailed`] if the transaction cannot
+    /// be serialized for inspection.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example(tx: &Transaction) -> Result<(), bitcoinkernel::KernelError> {
+    /// println!("Virtual size: {} vB", tx.vsize()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn vsize(&self) -> Result<usize, KernelError> {
+        let scale = WITNESS_SCALE_FACTOR as usize;
+        Ok((self.weight()? + scale - 1) / scale)
+    }
+
+    /// Returns the fee rate paid by this transaction.
+    ///
+    /// Combines [`fee`](Self::fee) and [`weight`](Self::weight). As with
+    /// `fee`, a coinbase transaction should not be passed here.
+    ///
+    /// # Arguments
+    /// * `spent` - The coins spent by this transaction, as read from a block's undo data
+    ///
+    /// # Errors
+    /// * [`KernelError::MismatchedOutputsSize`] - If `spent` does not hold
+    ///   exactly one coin per input
+    /// * [`KernelError::SerializationFailed`] - If the transaction cannot be
+    ///   serialized for inspection
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Block, BlockSpentOutputs, FeeRate};
+    /// # fn example(block: &Block, spent_outputs: &BlockSpentOutputs) -> Result<(), bitcoinkernel::KernelError> {
+    /// let mut rates: Vec<FeeRate> = block
+    ///     .transactions()
+    ///     .skip(1)
+    ///     .zip(spent_outputs.iter())
+    ///     .map(|(tx, spent)| tx.fee_rate(&spent))
+    ///     .collect::<Result<_, _>>()?;
+    /// rates.sort();
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fee_rate(&self, spent: &impl TransactionSpentOutputsExt) -> Result<FeeRate, KernelError>
+    where
+        Self: Sized,
+    {
+        Ok(FeeRate::from_fee_and_weight(
+            self.fee(spent)?,
+            self.weight()?,
+        ))
+    }
+
     /// Runs context-free consensus validation on this transaction.
     ///
     /// Performs basic structural checks (empty inputs/outputs, value ranges,
@@ -569,12 +1050,83 @@ impl Clone for Transaction {
     }
 }
 
+impl Debug for Transaction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_transaction(self, f)
+    }
+}
+
 impl Drop for Transaction {
     fn drop(&mut self) {
         unsafe { btck_transaction_destroy(self.inner) }
     }
 }
 
+/// Maximum number of inputs of an arbitrary [`Transaction`].
+#[cfg(feature = "arbitrary")]
+const MAX_ARBITRARY_INPUTS: usize = 8;
+
+/// Maximum number of outputs of an arbitrary [`Transaction`].
+#[cfg(feature = "arbitrary")]
+const MAX_ARBITRARY_OUTPUTS: usize = 8;
+
+/// Generates transactions that always deserialize, with arbitrary outpoints,
+/// scriptSigs, sequences, witnesses and lock time, and outputs carrying
+/// amounts within `0..=`[`MAX_MONEY`]. Every transaction has at least one
+/// input, and uses the segwit encoding only if a witness is non-empty.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Transaction {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let version: u32 = u.arbitrary()?;
+
+        let input_count = u.int_in_range(1..=MAX_ARBITRARY_INPUTS)?;
+        let mut inputs = Vec::new();
+        let mut witnesses = Vec::new();
+        let mut has_witness = false;
+        write_compact_size(&mut inputs, input_count);
+        for _ in 0..input_count {
+            inputs.extend_from_slice(&u.arbitrary::<[u8; 32]>()?);
+            inputs.extend_from_slice(&u.arbitrary::<u32>()?.to_le_bytes());
+            let script_sig: Vec<u8> = u.arbitrary()?;
+            write_compact_size(&mut inputs, script_sig.len());
+            inputs.extend_from_slice(&script_sig);
+            inputs.extend_from_slice(&u.arbitrary::<u32>()?.to_le_bytes());
+
+            let witness: Vec<Vec<u8>> = u.arbitrary()?;
+            has_witness |= !witness.is_empty();
+            write_compact_size(&mut witnesses, witness.len());
+            for item in witness {
+                write_compact_size(&mut witnesses, item.len());
+                witnesses.extend_from_slice(&item);
+            }
+        }
+
+        let output_count = u.int_in_range(0..=MAX_ARBITRARY_OUTPUTS)?;
+        let mut outputs = Vec::new();
+        write_compact_size(&mut outputs, output_count);
+        for _ in 0..output_count {
+            let output: TxOut = u.arbitrary()?;
+            let script_pubkey = output.script_pubkey();
+            outputs.extend_from_slice(&output.value().to_le_bytes());
+            write_compact_size(&mut outputs, script_pubkey.as_bytes().len());
+            outputs.extend_from_slice(script_pubkey.as_bytes());
+        }
+
+        let mut data = version.to_le_bytes().to_vec();
+        if has_witness {
+            data.extend_from_slice(&[0x00, 0x01]);
+        }
+        data.extend_from_slice(&inputs);
+        data.extend_from_slice(&outputs);
+        if has_witness {
+            data.extend_from_slice(&witnesses);
+        }
+        data.extend_from_slice(&u.arbitrary::<u32>()?.to_le_bytes());
+
+        Transaction::new(&data).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 impl TryFrom<&[u8]> for Transaction {
     type Error = KernelError;
 
@@ -583,6 +1135,14 @@ impl TryFrom<&[u8]> for Transaction {
     }
 }
 
+impl TryFrom<Vec<u8>> for Transaction {
+    type Error = KernelError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Transaction::new(&bytes)
+    }
+}
+
 impl TryFrom<Transaction> for Vec<u8> {
     type Error = KernelError;
 
@@ -599,6 +1159,128 @@ impl TryFrom<&Transaction> for Vec<u8> {
     }
 }
 
+/// Builder for assembling a [`Transaction`] from its inputs and outputs.
+///
+/// Inputs are added with an empty scriptSig and no witness, which is enough
+/// for spending outputs whose script needs no signature data, such as
+/// `OP_TRUE`. The transaction defaults to version 2 and a lock time of 0.
+///
+/// Collecting an iterator of [`TxOut`] yields a builder with those outputs
+/// and no inputs.
+///
+/// # Examples
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, KernelError, ScriptPubkey, Transaction, TransactionBuilder};
+/// # fn example(previous: &Transaction) -> Result<(), KernelError> {
+/// let script = ScriptPubkey::new(&[0x51])?;
+/// let tx = TransactionBuilder::new()
+///     .add_input(&previous.input(0)?.outpoint(), 0xffffffff)
+///     .add_output(&script, 50_000)
+///     .build()?;
+/// assert_eq!(tx.output_count(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    version: u32,
+    inputs: Vec<([u8; 32], u32, u32)>,
+    outputs: Vec<TxOut>,
+    lock_time: u32,
+}
+
+impl Default for TransactionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionBuilder {
+    /// Creates a builder for a version 2 transaction without inputs or
+    /// outputs.
+    pub fn new() -> TransactionBuilder {
+        TransactionBuilder {
+            version: 2,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            lock_time: 0,
+        }
+    }
+
+    /// Sets the transaction version.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the transaction lock time.
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Appends an input spending `outpoint` with the given sequence number.
+    pub fn add_input(mut self, outpoint: &impl TxOutPointExt, sequence: u32) -> Self {
+        self.inputs
+            .push((outpoint.txid().to_bytes(), outpoint.index(), sequence));
+        self
+    }
+
+    /// Appends an output paying `amount` satoshis to `script_pubkey`.
+    pub fn add_output(mut self, script_pubkey: &impl ScriptPubkeyExt, amount: i64) -> Self {
+        self.outputs.push(TxOut::new(script_pubkey, amount));
+        self
+    }
+
+    /// Serializes the inputs and outputs and parses them with
+    /// [`Transaction::new`].
+    ///
+    /// # Errors
+    /// Returns [`KernelError::InvalidOptions`] if no input was added. Without
+    /// inputs the serialization would be read as the segwit marker, so it
+    /// could not round-trip.
+    ///
+    /// Returns [`KernelError::Internal`] if the kernel rejects the
+    /// serialization.
+    pub fn build(&self) -> Result<Transaction, KernelError> {
+        if self.inputs.is_empty() {
+            return Err(KernelError::InvalidOptions(
+                "A transaction needs at least one input.".to_string(),
+            ));
+        }
+
+        let mut data = self.version.to_le_bytes().to_vec();
+
+        write_compact_size(&mut data, self.inputs.len());
+        for (txid, index, sequence) in &self.inputs {
+            data.extend_from_slice(txid);
+            data.extend_from_slice(&index.to_le_bytes());
+            write_compact_size(&mut data, 0);
+            data.extend_from_slice(&sequence.to_le_bytes());
+        }
+
+        write_compact_size(&mut data, self.outputs.len());
+        for output in &self.outputs {
+            let script_pubkey = output.script_pubkey();
+            data.extend_from_slice(&output.value().to_le_bytes());
+            write_compact_size(&mut data, script_pubkey.as_bytes().len());
+            data.extend_from_slice(script_pubkey.as_bytes());
+        }
+
+        data.extend_from_slice(&self.lock_time.to_le_bytes());
+        Transaction::new(&data)
+    }
+}
+
+impl FromIterator<TxOut> for TransactionBuilder {
+    fn from_iter<I: IntoIterator<Item = TxOut>>(iter: I) -> Self {
+        TransactionBuilder {
+            outputs: iter.into_iter().collect(),
+            ..TransactionBuilder::new()
+        }
+    }
+}
+
 /// A borrowed reference to a transaction.
 ///
 /// Provides zero-copy access to transaction data. It implements [`Copy`],
@@ -639,6 +1321,14 @@ impl<'a> TransactionRef<'a> {
             inner: unsafe { btck_transaction_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> Transaction {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_Transaction> for TransactionRef<'a> {
@@ -665,6 +1355,26 @@ impl<'a> Clone for TransactionRef<'a> {
 
 impl<'a> Copy for TransactionRef<'a> {}
 
+impl<'a> Debug for TransactionRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_transaction(self, f)
+    }
+}
+
+/// Formats a transaction as a summary of its txid, input and output counts,
+/// and total output value in satoshis.
+fn fmt_transaction(tx: &impl TransactionExt, f: &mut Formatter<'_>) -> fmt::Result {
+    let output_value: i64 = tx.outputs().map(|output| output.value()).sum();
+    write!(
+        f,
+        "Transaction(txid: {}, inputs: {}, outputs: {}, output_value: {})",
+        tx.txid(),
+        tx.input_count(),
+        tx.output_count(),
+        output_value
+    )
+}
+
 /// Iterator over transaction inputs.
 ///
 /// This iterator yields [`TxInRef`] items for each input in the transaction,
@@ -786,50 +1496,184 @@ pub struct TxOutIter<'a> {
     current_index: usize,
 }
 
-impl<'a> TxOutIter<'a> {
-    fn new(transaction: TransactionRef<'a>) -> Self {
-        Self {
-            transaction,
+impl<'a> TxOutIter<'a> {
+    fn new(transaction: TransactionRef<'a>) -> Self {
+        Self {
+            transaction,
+            current_index: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for TxOutIter<'a> {
+    type Item = TxOutRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_index >= self.transaction.output_count() {
+            return None;
+        }
+
+        let index = self.current_index;
+        self.current_index += 1;
+
+        let tx_out_ptr =
+            unsafe { btck_transaction_get_output_at(self.transaction.as_ptr(), index) };
+
+        if tx_out_ptr.is_null() {
+            None
+        } else {
+            Some(unsafe { TxOutRef::from_ptr(tx_out_ptr) })
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self
+            .transaction
+            .input_count()
+            .saturating_sub(self.current_index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for TxOutIter<'a> {
+    fn len(&self) -> usize {
+        self.transaction
+            .output_count()
+            .saturating_sub(self.current_index)
+    }
+}
+
+/// The witness stack of a transaction input.
+///
+/// The kernel API does not expose witness data, so a `Witness` is decoded from
+/// the transaction's consensus encoding by [`TransactionExt::witnesses`]. The
+/// stack items are stored in a single buffer and returned as borrowed slices,
+/// so walking every item of every input does not allocate per item.
+///
+/// Inputs of transactions without witness data have an empty witness.
+///
+/// # Examples
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, Transaction, KernelError};
+/// # fn example(tx: &Transaction) -> Result<(), KernelError> {
+/// for (index, witness) in tx.witnesses()?.iter().enumerate() {
+///     if let Some(pubkey) = witness.get(1) {
+///         println!("Input {} reveals a {} byte key", index, pubkey.len());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Witness {
+    data: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl Witness {
+    fn from_items(items: &[&[u8]]) -> Self {
+        let mut data = Vec::with_capacity(items.iter().map(|item| item.len()).sum());
+        let mut ends = Vec::with_capacity(items.len());
+        for item in items {
+            data.extend_from_slice(item);
+            ends.push(data.len());
+        }
+        Witness { data, ends }
+    }
+
+    /// Returns the number of items on the stack.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if the stack has no items.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the stack item at `index`, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let end = *self.ends.get(index)?;
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1],
+        };
+        Some(&self.data[start..end])
+    }
+
+    /// Returns the last stack item, such as the witness script of a P2WSH
+    /// spend, or `None` if the stack is empty.
+    pub fn last(&self) -> Option<&[u8]> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the stack items, from first to last.
+    pub fn iter(&self) -> WitnessIter<'_> {
+        WitnessIter {
+            witness: self,
             current_index: 0,
         }
     }
-}
 
-impl<'a> Iterator for TxOutIter<'a> {
-    type Item = TxOutRef<'a>;
+    /// Returns the size of the witness in bytes when serialized, including
+    /// the stack item count and the length prefix of every item.
+    ///
+    /// This is the number of witness bytes the input contributes to its
+    /// transaction, each of which counts as one weight unit.
+    pub fn serialized_size(&self) -> usize {
+        compact_size_len(self.len())
+            + self
+                .iter()
+                .map(|item| compact_size_len(item.len()) + item.len())
+                .sum::<usize>()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.current_index >= self.transaction.output_count() {
-            return None;
+    /// Serializes the witness to Bitcoin wire format: the stack item count
+    /// followed by every item prefixed with its length.
+    pub fn consensus_encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.serialized_size());
+        write_compact_size(&mut out, self.len());
+        for item in self {
+            write_compact_size(&mut out, item.len());
+            out.extend_from_slice(item);
         }
+        out
+    }
+}
 
-        let index = self.current_index;
-        self.current_index += 1;
+impl<'a> IntoIterator for &'a Witness {
+    type Item = &'a [u8];
+    type IntoIter = WitnessIter<'a>;
 
-        let tx_out_ptr =
-            unsafe { btck_transaction_get_output_at(self.transaction.as_ptr(), index) };
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
 
-        if tx_out_ptr.is_null() {
-            None
-        } else {
-            Some(unsafe { TxOutRef::from_ptr(tx_out_ptr) })
-        }
+/// Iterator over the items of a [`Witness`] stack.
+pub struct WitnessIter<'a> {
+    witness: &'a Witness,
+    current_index: usize,
+}
+
+impl<'a> Iterator for WitnessIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.witness.get(self.current_index)?;
+        self.current_index += 1;
+        Some(item)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self
-            .transaction
-            .input_count()
-            .saturating_sub(self.current_index);
+        let remaining = self.witness.len().saturating_sub(self.current_index);
         (remaining, Some(remaining))
     }
 }
 
-impl<'a> ExactSizeIterator for TxOutIter<'a> {
+impl<'a> ExactSizeIterator for WitnessIter<'a> {
     fn len(&self) -> usize {
-        self.transaction
-            .output_count()
-            .saturating_sub(self.current_index)
+        self.witness.len().saturating_sub(self.current_index)
     }
 }
 
@@ -914,7 +1758,6 @@ pub trait TxOutExt: AsPtr<btck_TransactionOutput> {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug)]
 pub struct TxOut {
     inner: *mut btck_TransactionOutput,
 }
@@ -945,6 +1788,37 @@ impl TxOut {
         }
     }
 
+    /// Creates a new transaction output, rejecting amounts that no valid
+    /// transaction could carry.
+    ///
+    /// Unlike [`new`](Self::new), which accepts any raw `i64`, this checks
+    /// that `amount` lies within `0..=`[`MAX_MONEY`].
+    ///
+    /// # Errors
+    /// Returns [`KernelError::InvalidAmount`] if `amount` is negative or
+    /// exceeds [`MAX_MONEY`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, TxOut, ScriptPubkey, MAX_MONEY};
+    /// # fn example() -> Result<(), bitcoinkernel::KernelError> {
+    /// let script = ScriptPubkey::new(&[0x76, 0xa9])?;
+    /// let output = TxOut::new_checked(&script, 50000)?;
+    /// assert_eq!(output.value(), 50000);
+    /// assert!(TxOut::new_checked(&script, MAX_MONEY + 1).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_checked(
+        script_pubkey: &impl ScriptPubkeyExt,
+        amount: i64,
+    ) -> Result<Self, KernelError> {
+        if !(0..=MAX_MONEY).contains(&amount) {
+            return Err(KernelError::InvalidAmount(amount));
+        }
+        Ok(TxOut::new(script_pubkey, amount))
+    }
+
     /// Creates a borrowed reference to this output.
     ///
     /// This allows converting from an owned [`TxOut`] to a [`TxOutRef`]
@@ -971,6 +1845,12 @@ impl FromMutPtr<btck_TransactionOutput> for TxOut {
 
 impl TxOutExt for TxOut {}
 
+impl HasScriptPubkey for TxOut {
+    fn locking_script(&self) -> ScriptPubkeyRef<'_> {
+        TxOutExt::script_pubkey(self)
+    }
+}
+
 impl Clone for TxOut {
     fn clone(&self) -> Self {
         TxOut {
@@ -979,12 +1859,28 @@ impl Clone for TxOut {
     }
 }
 
+impl Debug for TxOut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_tx_out(self, f)
+    }
+}
+
 impl Drop for TxOut {
     fn drop(&mut self) {
         unsafe { btck_transaction_output_destroy(self.inner) }
     }
 }
 
+/// Generates outputs with an arbitrary [`ScriptPubkey`](script::ScriptPubkey)
+/// and an amount within `0..=`[`MAX_MONEY`].
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for TxOut {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let script_pubkey: script::ScriptPubkey = u.arbitrary()?;
+        Ok(TxOut::new(&script_pubkey, u.int_in_range(0..=MAX_MONEY)?))
+    }
+}
+
 /// A borrowed reference to a transaction output.
 ///
 /// Provides zero-copy access to output data. It implements [`Copy`],
@@ -1012,6 +1908,25 @@ impl<'a> TxOutRef<'a> {
             inner: unsafe { btck_transaction_output_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> TxOut {
+        self.to_owned()
+    }
+
+    /// Returns a reference to the script pubkey that defines how this output
+    /// can be spent.
+    ///
+    /// Same as [`TxOutExt::script_pubkey`], except that the script is borrowed
+    /// from the underlying output rather than from this reference, so it may
+    /// outlive it.
+    pub fn script_pubkey(&self) -> ScriptPubkeyRef<'a> {
+        let ptr = unsafe { btck_transaction_output_get_script_pubkey(self.inner) };
+        unsafe { ScriptPubkeyRef::from_ptr(ptr) }
+    }
 }
 
 impl<'a> AsPtr<btck_TransactionOutput> for TxOutRef<'a> {
@@ -1031,6 +1946,12 @@ impl<'a> FromPtr<btck_TransactionOutput> for TxOutRef<'a> {
 
 impl<'a> TxOutExt for TxOutRef<'a> {}
 
+impl<'a> HasScriptPubkey for TxOutRef<'a> {
+    fn locking_script(&self) -> ScriptPubkeyRef<'_> {
+        TxOutExt::script_pubkey(self)
+    }
+}
+
 impl<'a> Clone for TxOutRef<'a> {
     fn clone(&self) -> Self {
         *self
@@ -1039,6 +1960,21 @@ impl<'a> Clone for TxOutRef<'a> {
 
 impl<'a> Copy for TxOutRef<'a> {}
 
+impl<'a> Debug for TxOutRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_tx_out(self, f)
+    }
+}
+
+/// Formats an output as its value in satoshis and its hex-encoded script.
+fn fmt_tx_out(output: &impl TxOutExt, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(f, "TxOut(value: {}, script_pubkey: ", output.value())?;
+    for byte in output.script_pubkey().as_bytes() {
+        write!(f, "{:02x}", byte)?;
+    }
+    write!(f, ")")
+}
+
 /// Common operations for transaction inputs, implemented by both owned and borrowed types.
 ///
 /// This trait provides shared functionality for [`TxIn`] and [`TxInRef`],
@@ -1096,6 +2032,25 @@ pub trait TxInExt: AsPtr<btck_TransactionInput> {
     fn sequence(&self) -> u32 {
         unsafe { btck_transaction_input_get_sequence(self.as_ptr()) }
     }
+
+    /// Returns `true` if this input spends the null outpoint, as the single
+    /// input of a coinbase transaction does.
+    ///
+    /// Equivalent to checking [`TxOutPointExt::is_null`] on the
+    /// [`outpoint`](Self::outpoint), for code that only holds the input. A
+    /// non-coinbase transaction with a null outpoint input fails
+    /// [`TransactionExt::check`], so only trust this on validated transactions.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, Transaction};
+    /// # fn example(tx: &Transaction) {
+    /// let spending: Vec<_> = tx.inputs().filter(|input| !input.is_coinbase_input()).collect();
+    /// # }
+    /// ```
+    fn is_coinbase_input(&self) -> bool {
+        self.outpoint().is_null()
+    }
 }
 
 /// A single transaction input referencing a previous output to be spent.
@@ -1179,6 +2134,24 @@ impl<'a> TxInRef<'a> {
             inner: unsafe { btck_transaction_input_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> TxIn {
+        self.to_owned()
+    }
+
+    /// Returns the outpoint this input spends.
+    ///
+    /// Same as [`TxInExt::outpoint`], except that the outpoint is borrowed
+    /// from the underlying transaction rather than from this reference, so it
+    /// may outlive it.
+    pub fn outpoint(&self) -> TxOutPointRef<'a> {
+        let ptr = unsafe { btck_transaction_input_get_out_point(self.inner) };
+        unsafe { TxOutPointRef::from_ptr(ptr) }
+    }
 }
 
 impl<'a> AsPtr<btck_TransactionInput> for TxInRef<'a> {
@@ -1377,6 +2350,21 @@ impl Drop for TxOutPoint {
     }
 }
 
+impl PartialEq for TxOutPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.index() == other.index() && self.txid() == other.txid()
+    }
+}
+
+impl Eq for TxOutPoint {}
+
+impl Hash for TxOutPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.txid().to_bytes().hash(state);
+        self.index().hash(state);
+    }
+}
+
 /// A borrowed reference to an outpoint.
 ///
 /// Provides zero-copy access to outpoint data. It implements [`Copy`],
@@ -1404,6 +2392,14 @@ impl<'a> TxOutPointRef<'a> {
             inner: unsafe { btck_transaction_out_point_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> TxOutPoint {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_TransactionOutPoint> for TxOutPointRef<'a> {
@@ -1599,6 +2595,12 @@ impl PartialEq<TxidRef<'_>> for Txid {
 
 impl Eq for Txid {}
 
+impl Hash for Txid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state);
+    }
+}
+
 impl Debug for Txid {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "Txid({:?})", self.to_bytes())
@@ -1642,6 +2644,14 @@ impl<'a> TxidRef<'a> {
             inner: unsafe { btck_txid_copy(self.inner) },
         }
     }
+
+    /// Consumes this reference and returns an owned copy.
+    ///
+    /// Equivalent to [`to_owned`](Self::to_owned), for call sites that hold
+    /// the reference by value and do not use it afterwards.
+    pub fn into_owned(self) -> Txid {
+        self.to_owned()
+    }
 }
 
 impl<'a> AsPtr<btck_Txid> for TxidRef<'a> {
@@ -1835,6 +2845,24 @@ mod tests {
         (tx1, tx2)
     }
 
+    /// Builds a transaction with `lock_time` and one input per entry of
+    /// `sequences`, each spending the first outpoint of the test transaction.
+    fn build_with_sequences(lock_time: u32, sequences: &[u32]) -> Transaction {
+        let (tx, _) = get_test_transactions();
+        let input = tx.input(0).unwrap();
+        let outpoint = input.outpoint();
+        let op_true = ScriptPubkey::new(&[0x51]).unwrap();
+        sequences
+            .iter()
+            .fold(TransactionBuilder::new(), |builder, sequence| {
+                builder.add_input(&outpoint, *sequence)
+            })
+            .lock_time(lock_time)
+            .add_output(&op_true, 1_000)
+            .build()
+            .unwrap()
+    }
+
     fn get_test_txids() -> (Txid, Txid) {
         let (tx1, tx2) = get_test_transactions();
         (tx1.txid().to_owned(), tx2.txid().to_owned())
@@ -1940,6 +2968,10 @@ mod tests {
         let encoded = tx.consensus_encode().unwrap();
         let new_tx = Transaction::try_from(encoded.as_slice());
         assert!(new_tx.is_ok());
+
+        let new_tx = Transaction::try_from(encoded.clone()).unwrap();
+        assert_eq!(new_tx.consensus_encode().unwrap(), encoded);
+        assert!(Transaction::try_from(Vec::new()).is_err());
     }
 
     #[test]
@@ -1971,6 +3003,21 @@ mod tests {
         assert!(matches!(output, Err(KernelError::OutOfBounds)));
     }
 
+    #[test]
+    fn test_transaction_output_value() {
+        let (tx, _) = get_test_transactions();
+        for index in 0..tx.output_count() {
+            assert_eq!(
+                tx.output_value(index).unwrap(),
+                tx.output(index).unwrap().value()
+            );
+        }
+        assert!(matches!(
+            tx.output_value(tx.output_count()),
+            Err(KernelError::OutOfBounds)
+        ));
+    }
+
     #[test]
     fn test_transaction_input() {
         let (tx, _) = get_test_transactions();
@@ -2015,6 +3062,28 @@ mod tests {
         assert_eq!(bytes2, bytes3);
     }
 
+    #[test]
+    fn test_transaction_consensus_encode_to() {
+        let (tx, _) = get_test_transactions();
+        let expected = tx.consensus_encode().unwrap();
+
+        let mut buffer = Vec::new();
+        tx.consensus_encode_to(&mut buffer).unwrap();
+        assert_eq!(buffer, expected);
+
+        let mut buffer = Vec::new();
+        tx.as_ref().consensus_encode_to(&mut buffer).unwrap();
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_transaction_consensus_encode_hex() {
+        let (tx, _) = get_test_transactions();
+        let expected = hex::encode(tx.consensus_encode().unwrap());
+        assert_eq!(tx.consensus_encode_hex().unwrap(), expected);
+        assert_eq!(tx.as_ref().consensus_encode_hex().unwrap(), expected);
+    }
+
     #[test]
     fn test_transaction_try_into_vec() {
         let (tx, _) = get_test_transactions();
@@ -2048,6 +3117,21 @@ mod tests {
         assert_eq!(tx.input_count(), owned_tx.input_count());
     }
 
+    #[test]
+    fn test_ref_into_owned() {
+        let (tx, _) = get_test_transactions();
+        let owned_tx = tx.as_ref().into_owned();
+        assert_eq!(tx.txid(), owned_tx.txid());
+
+        let owned_output = tx.output(0).unwrap().into_owned();
+        assert_eq!(owned_output.value(), tx.output(0).unwrap().value());
+
+        let owned_outpoint = tx.input(0).unwrap().outpoint().into_owned();
+        let owned_txid = owned_outpoint.txid().into_owned();
+        drop(tx);
+        assert_eq!(owned_txid, owned_outpoint.txid().to_owned());
+    }
+
     #[test]
     fn test_transaction_multiple_outputs() {
         let (tx, _) = get_test_transactions();
@@ -2088,6 +3172,105 @@ mod tests {
         assert_eq!(tx.locktime(), 0);
     }
 
+    #[test]
+    fn test_transaction_is_final_height_lock() {
+        let (tx, _) = get_test_transactions();
+        assert_eq!(tx.locktime(), 204);
+        assert!(!tx.is_final(203, 0));
+        assert!(!tx.is_final(204, 0));
+        assert!(tx.is_final(205, 0));
+        // Time has no effect on a height-based lock time.
+        assert!(!tx.is_final(204, u32::MAX));
+    }
+
+    #[test]
+    fn test_transaction_is_final_zero_locktime() {
+        let (_, tx) = get_test_transactions();
+        assert!(tx.is_final(0, 0));
+        assert!(tx.is_final(i32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn test_transaction_is_final_all_sequences_final() {
+        // A lock time in the future is ignored when every input is final.
+        let height_locked = build_with_sequences(1_000, &[SEQUENCE_FINAL, SEQUENCE_FINAL]);
+        assert!(height_locked.is_final(10, 0));
+        let time_locked = build_with_sequences(LOCKTIME_THRESHOLD + 1_000, &[SEQUENCE_FINAL]);
+        assert!(time_locked.is_final(10, LOCKTIME_THRESHOLD));
+
+        // A single non-final input enforces it again.
+        assert!(
+            !build_with_sequences(1_000, &[SEQUENCE_FINAL, SEQUENCE_FINAL - 1]).is_final(10, 0)
+        );
+    }
+
+    #[test]
+    fn test_transaction_is_rbf_signaling() {
+        let (tx, _) = get_test_transactions();
+        assert_eq!(tx.input(0).unwrap().sequence(), MAX_BIP125_RBF_SEQUENCE);
+        assert!(tx.is_rbf_signaling());
+
+        let (coinbase, _) = get_test_coinbase_transactions();
+        assert!(!coinbase.is_rbf_signaling());
+
+        assert!(!build_with_sequences(0, &[SEQUENCE_FINAL - 1]).is_rbf_signaling());
+        assert!(!build_with_sequences(0, &[SEQUENCE_FINAL, SEQUENCE_FINAL - 1]).is_rbf_signaling());
+        assert!(build_with_sequences(0, &[SEQUENCE_FINAL, 0]).is_rbf_signaling());
+    }
+
+    #[test]
+    fn test_transaction_legacy_sigop_count() {
+        let (tx, _) = get_test_transactions();
+        assert_eq!(tx.legacy_sigop_count().unwrap(), 0);
+
+        let (coinbase, _) = get_test_coinbase_transactions();
+        assert_eq!(coinbase.legacy_sigop_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_transaction_sigop_cost() {
+        let (tx, _) = get_test_transactions();
+        assert_eq!(tx.input_count(), 1);
+
+        let p2wpkh = ScriptPubkey::new(
+            &hex::decode("0014eb82059311a807fc289d46a715ea4801608e5662").unwrap(),
+        )
+        .unwrap();
+        let spent_outputs = vec![TxOut::new(&p2wpkh, 100_000)];
+        assert_eq!(tx.sigop_cost(&spent_outputs).unwrap(), 1);
+
+        let p2pkh = ScriptPubkey::new(
+            &hex::decode("76a914eb82059311a807fc289d46a715ea4801608e566288ac").unwrap(),
+        )
+        .unwrap();
+        let spent_outputs = vec![TxOut::new(&p2pkh, 100_000)];
+        assert_eq!(tx.sigop_cost(&spent_outputs).unwrap(), 0);
+
+        assert!(matches!(
+            tx.sigop_cost(&Vec::<TxOut>::new()),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+
+        let (coinbase, _) = get_test_coinbase_transactions();
+        assert_eq!(coinbase.sigop_cost(&Vec::<TxOut>::new()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_transaction_vsize_rounds_up() {
+        let (tx1, tx2) = get_test_transactions();
+
+        // 561 weight units is not a whole number of vbytes.
+        assert_eq!(tx1.weight().unwrap(), 561);
+        assert_eq!(tx1.vsize().unwrap(), 141);
+
+        assert_eq!(tx2.weight().unwrap(), 928);
+        assert_eq!(tx2.vsize().unwrap(), 232);
+
+        let (coinbase, _) = get_test_coinbase_transactions();
+        assert_eq!(coinbase.weight().unwrap(), 568);
+        assert_eq!(coinbase.vsize().unwrap(), 142);
+    }
+
     #[test]
     fn test_transaction_check_valid() {
         let (tx, _) = get_test_transactions();
@@ -2123,6 +3306,40 @@ mod tests {
         assert_eq!(tx.as_ref().check(), TxCheckResult::Valid);
     }
 
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_transaction() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let data: Vec<u8> = (0..4096u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..16 {
+            let Ok(tx) = Transaction::arbitrary(&mut u) else {
+                break;
+            };
+            assert!((1..=MAX_ARBITRARY_INPUTS).contains(&tx.input_count()));
+            assert!(tx.output_count() <= MAX_ARBITRARY_OUTPUTS);
+            for output in tx.outputs() {
+                assert!((0..=MAX_MONEY).contains(&output.value()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_transaction_debug() {
+        let (tx, _) = get_test_transactions();
+        let output_value: i64 = tx.outputs().map(|output| output.value()).sum();
+        let expected = format!(
+            "Transaction(txid: {}, inputs: 1, outputs: {}, output_value: {})",
+            tx.txid(),
+            tx.output_count(),
+            output_value
+        );
+
+        assert_eq!(format!("{:?}", tx), expected);
+        assert_eq!(format!("{:?}", tx.as_ref()), expected);
+    }
+
     // TxOut tests
     #[test]
     fn test_txout_new() {
@@ -2168,6 +3385,16 @@ mod tests {
         assert_eq!(txout.value(), owned_txout.value());
     }
 
+    #[test]
+    fn test_txout_debug() {
+        let script = ScriptPubkey::new([0x76, 0xa9, 0x14].as_slice()).unwrap();
+        let txout = TxOut::new(&script, 50000);
+        let expected = "TxOut(value: 50000, script_pubkey: 76a914)";
+
+        assert_eq!(format!("{:?}", txout), expected);
+        assert_eq!(format!("{:?}", txout.as_ref()), expected);
+    }
+
     #[test]
     fn test_txout_zero_value() {
         let script = ScriptPubkey::new([0x51].as_slice()).unwrap();
@@ -2183,6 +3410,26 @@ mod tests {
         assert_eq!(txout.value(), amount);
     }
 
+    #[test]
+    fn test_txout_new_checked() {
+        let script = ScriptPubkey::new([0x51].as_slice()).unwrap();
+
+        assert_eq!(TxOut::new_checked(&script, 0).unwrap().value(), 0);
+        assert_eq!(
+            TxOut::new_checked(&script, MAX_MONEY).unwrap().value(),
+            MAX_MONEY
+        );
+        assert!(matches!(
+            TxOut::new_checked(&script, -1),
+            Err(KernelError::InvalidAmount(-1))
+        ));
+        assert!(matches!(
+            TxOut::new_checked(&script, MAX_MONEY + 1),
+            Err(KernelError::InvalidAmount(_))
+        ));
+        assert!(TxOut::new_checked(&script, i64::MIN).is_err());
+    }
+
     #[test]
     fn test_txout_from_transaction() {
         let (tx, _) = get_test_transactions();
@@ -2223,6 +3470,17 @@ mod tests {
         assert_eq!(txin.outpoint().index(), owned_txin.outpoint().index());
     }
 
+    #[test]
+    fn test_txin_is_coinbase_input() {
+        let (coinbase, _) = get_test_coinbase_transactions();
+        let input = coinbase.input(0).unwrap();
+        assert!(input.is_coinbase_input());
+        assert!(input.to_owned().is_coinbase_input());
+
+        let (tx, _) = get_test_transactions();
+        assert!(tx.inputs().all(|input| !input.is_coinbase_input()));
+    }
+
     #[test]
     fn test_txin_sequence() {
         let (tx, _) = get_test_transactions();
@@ -2473,6 +3731,151 @@ mod tests {
         assert_eq!(iter_count, count);
     }
 
+    #[test]
+    fn test_transaction_outputs_enumerated() {
+        let (tx, _) = get_test_transactions();
+
+        let mut expected_vout = 0u32;
+        for (vout, output) in tx.outputs_enumerated() {
+            assert_eq!(vout, expected_vout);
+            assert_eq!(output.value(), tx.output(vout as usize).unwrap().value());
+            expected_vout += 1;
+        }
+
+        assert_eq!(expected_vout as usize, tx.output_count());
+    }
+
+    #[test]
+    fn test_transaction_spent_outpoints() {
+        let (tx, _) = get_test_transactions();
+
+        let spent: Vec<_> = tx.spent_outpoints().collect();
+        assert_eq!(spent.len(), tx.input_count());
+        for (outpoint, input) in spent.iter().zip(tx.inputs()) {
+            assert_eq!(outpoint.txid(), input.outpoint().txid());
+            assert_eq!(outpoint.index(), input.outpoint().index());
+            assert!(!outpoint.is_null());
+        }
+
+        // Outpoints borrow from the transaction, not from the input reference.
+        let via_inputs: Vec<_> = tx.inputs().map(|input| input.outpoint()).collect();
+        assert_eq!(via_inputs.len(), spent.len());
+        for (outpoint, spent) in via_inputs.iter().zip(spent.iter()) {
+            assert_eq!(outpoint.txid(), spent.txid());
+            assert_eq!(outpoint.index(), spent.index());
+        }
+
+        let block = Block::new(&read_block_data()[204]).unwrap();
+        let coinbase = block.transaction(0).unwrap();
+        let spent: Vec<_> = coinbase.spent_outpoints().collect();
+        assert_eq!(spent.len(), 1);
+        assert!(spent[0].is_null());
+    }
+
+    #[test]
+    fn test_transaction_builder() {
+        let (previous, _) = get_test_transactions();
+        let previous_input = previous.input(0).unwrap();
+        let outpoint = previous_input.outpoint();
+        let op_true = ScriptPubkey::new(&[0x51]).unwrap();
+        let op_return = ScriptPubkey::new(&[0x6a, 0x01, 0x00]).unwrap();
+
+        let tx = TransactionBuilder::new()
+            .version(1)
+            .lock_time(100)
+            .add_input(&outpoint, 0xfffffffe)
+            .add_output(&op_true, 10_000)
+            .add_output(&op_return, 0)
+            .build()
+            .unwrap();
+
+        assert_eq!(tx.input_count(), 1);
+        assert_eq!(tx.output_count(), 2);
+        assert_eq!(tx.locktime(), 100);
+        assert_eq!(tx.consensus_encode().unwrap()[..4], 1u32.to_le_bytes());
+        let input = tx.input(0).unwrap();
+        assert_eq!(input.outpoint().txid(), outpoint.txid());
+        assert_eq!(input.outpoint().index(), outpoint.index());
+        assert_eq!(input.sequence(), 0xfffffffe);
+        assert_eq!(tx.output(0).unwrap().value(), 10_000);
+        assert_eq!(tx.output(0).unwrap().script_pubkey().as_bytes(), &[0x51]);
+        assert_eq!(
+            tx.output(1).unwrap().script_pubkey().as_bytes(),
+            &[0x6a, 0x01, 0x00]
+        );
+
+        // An empty scriptSig satisfies an OP_TRUE output.
+        let spent_outputs = [TxOut::new(&op_true, 20_000)];
+        crate::verify_transaction(&tx, &spent_outputs, None).unwrap();
+
+        // Building without an input is rejected before serializing.
+        assert!(matches!(
+            TransactionBuilder::new().build(),
+            Err(KernelError::InvalidOptions(_))
+        ));
+        let builder: TransactionBuilder = vec![TxOut::new(&op_true, 1)].into_iter().collect();
+        assert!(matches!(
+            builder.build(),
+            Err(KernelError::InvalidOptions(_))
+        ));
+        let tx = builder.add_input(&outpoint, 0).build().unwrap();
+        assert_eq!(tx.output_count(), 1);
+        assert_eq!(tx.input(0).unwrap().outpoint().txid(), outpoint.txid());
+    }
+
+    #[test]
+    fn test_transaction_witnesses() {
+        let (tx1, tx2) = get_test_transactions();
+
+        let witnesses = tx2.witnesses().unwrap();
+        assert_eq!(witnesses.len(), tx2.input_count());
+        for witness in &witnesses {
+            assert_eq!(witness.len(), 2);
+            assert_eq!(witness.get(0).unwrap().len(), 71);
+            assert_eq!(witness.get(1).unwrap().len(), 33);
+            assert_eq!(witness.last(), witness.get(1));
+            assert!(witness.get(2).is_none());
+            assert!(witness.get(usize::MAX).is_none());
+
+            let items: Vec<&[u8]> = witness.iter().collect();
+            assert_eq!(
+                items,
+                vec![witness.get(0).unwrap(), witness.get(1).unwrap()]
+            );
+            assert_eq!(witness.iter().len(), 2);
+        }
+
+        assert_eq!(tx1.witnesses().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_witness_empty() {
+        let witness = Witness::default();
+        assert!(witness.is_empty());
+        assert!(witness.get(0).is_none());
+        assert!(witness.last().is_none());
+        assert_eq!(witness.iter().count(), 0);
+
+        let witness = Witness::from_items(&[&[], &[0x51]]);
+        assert_eq!(witness.len(), 2);
+        assert_eq!(witness.get(0), Some(&[][..]));
+        assert_eq!(witness.get(1), Some(&[0x51][..]));
+    }
+
+    #[test]
+    fn test_witness_consensus_encode() {
+        let witness = Witness::default();
+        assert_eq!(witness.consensus_encode(), vec![0x00]);
+        assert_eq!(witness.serialized_size(), 1);
+
+        let witness = Witness::from_items(&[&[], &[0x51], &[0xab; 0xfd]]);
+        let encoded = witness.consensus_encode();
+        assert_eq!(&encoded[..5], &[0x03, 0x00, 0x01, 0x51, 0xfd]);
+        assert_eq!(&encoded[5..8], &[0xfd, 0x00, 0xab]);
+        assert_eq!(encoded.len(), 1 + 1 + 2 + 3 + 0xfd);
+        assert_eq!(witness.serialized_size(), encoded.len());
+    }
+
     #[test]
     fn test_txid_display() {
         let (tx, _) = get_test_transactions();
@@ -0,0 +1,272 @@
+//! Tracking the unspent transaction outputs created by a sequence of blocks.
+//!
+//! A [`UtxoMap`] is the UTXO-tracking loop most indexers need: apply each
+//! connected block in order and it removes the outputs the block spends and
+//! adds the outputs it creates. Outputs spent later in the same block and
+//! provably unspendable outputs are never added, matching how Bitcoin Core
+//! maintains its coins database. Reverting a disconnected block restores the
+//! spent outputs from its undo data, so the map can follow reorgs.
+
+use std::collections::{hash_map, HashMap};
+
+use crate::KernelError;
+
+use super::{
+    block::{BlockSpentOutputsExt, CoinExt},
+    script::{ScriptPubkeyExt, MAX_SCRIPT_SIZE},
+    transaction::{TransactionExt, TxOutPointExt, TxidExt},
+    Block, TxOut,
+};
+
+/// Script opcode marking an output as provably unspendable.
+const OP_RETURN: u8 = 0x6a;
+
+/// A copy of an outpoint's txid and output index, used as the key of a
+/// [`UtxoMap`] and of a [`SpentOutputsCoinProvider`](crate::SpentOutputsCoinProvider).
+///
+/// A [`TxOutPoint`](crate::TxOutPoint) can only be obtained from an input
+/// spending it, so outputs that are not spent yet are keyed by this type
+/// instead. It converts from any outpoint and compares equal to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UtxoOutPoint {
+    txid: [u8; 32],
+    index: u32,
+}
+
+impl UtxoOutPoint {
+    /// Returns the txid of the transaction that created the output, in
+    /// internal byte order.
+    pub fn txid(&self) -> [u8; 32] {
+        self.txid
+    }
+
+    /// Returns the index of the output in the transaction that created it.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<T: TxOutPointExt> From<&T> for UtxoOutPoint {
+    fn from(outpoint: &T) -> Self {
+        UtxoOutPoint {
+            txid: outpoint.txid().to_bytes(),
+            index: outpoint.index(),
+        }
+    }
+}
+
+impl<T: TxOutPointExt> PartialEq<T> for UtxoOutPoint {
+    fn eq(&self, other: &T) -> bool {
+        self.index == other.index() && self.txid == other.txid().to_bytes()
+    }
+}
+
+/// An unspent output tracked by a [`UtxoMap`].
+#[derive(Debug, Clone)]
+pub struct UtxoEntry {
+    output: TxOut,
+    height: u32,
+    is_coinbase: bool,
+}
+
+impl UtxoEntry {
+    /// Returns the unspent output.
+    pub fn output(&self) -> &TxOut {
+        &self.output
+    }
+
+    /// Returns the height of the block that created the output.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns `true` if the output was created by a coinbase transaction.
+    pub fn is_coinbase(&self) -> bool {
+        self.is_coinbase
+    }
+}
+
+/// A map of unspent transaction outputs, keyed by outpoint.
+///
+/// Blocks must be applied in chain order with
+/// [`apply_block`](UtxoMap::apply_block). Inputs spending outputs the map has
+/// never seen are ignored, so a map may start at any height and will then
+/// only hold outputs created from that height on.
+///
+/// # Example
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, ChainstateManager, KernelError, UtxoMap};
+/// # fn example(chainman: &ChainstateManager) -> Result<(), KernelError> {
+/// let mut utxos = UtxoMap::new();
+/// for entry in chainman.active_chain().iter().skip(1) {
+///     let block = chainman.read_block_data(&entry)?;
+///     let spent_outputs = chainman.read_spent_outputs(&entry)?;
+///     utxos.apply_block(&block, entry.height() as u32, &spent_outputs)?;
+/// }
+/// println!("{} unspent outputs", utxos.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UtxoMap {
+    utxos: HashMap<UtxoOutPoint, UtxoEntry>,
+}
+
+impl UtxoMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of unspent outputs.
+    pub fn len(&self) -> usize {
+        self.utxos.len()
+    }
+
+    /// Returns `true` if the map holds no unspent outputs.
+    pub fn is_empty(&self) -> bool {
+        self.utxos.is_empty()
+    }
+
+    /// Returns the unspent output at `outpoint`, if any.
+    pub fn get(&self, outpoint: &impl TxOutPointExt) -> Option<&UtxoEntry> {
+        self.utxos.get(&UtxoOutPoint::from(outpoint))
+    }
+
+    /// Returns `true` if the output at `outpoint` is unspent.
+    pub fn contains(&self, outpoint: &impl TxOutPointExt) -> bool {
+        self.get(outpoint).is_some()
+    }
+
+    /// Returns an iterator over the unspent outputs and their outpoints.
+    pub fn iter(&self) -> hash_map::Iter<'_, UtxoOutPoint, UtxoEntry> {
+        self.utxos.iter()
+    }
+
+    /// Applies a block connected at `height` to the map.
+    ///
+    /// Transactions are applied in block order: each transaction's inputs are
+    /// removed before its outputs are added, so an output created and spent
+    /// within the block never appears in the map. The spent outputs are only
+    /// used to check that they belong to `block`, which is done before the
+    /// map is modified.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::MismatchedOutputsSize`] if `spent_outputs` does
+    /// not correspond to `block`. The map is left unchanged.
+    pub fn apply_block(
+        &mut self,
+        block: &Block,
+        height: u32,
+        spent_outputs: &impl BlockSpentOutputsExt,
+    ) -> Result<(), KernelError> {
+        spent_outputs.matches_block(block)?;
+        self.apply_transactions(block, height);
+        Ok(())
+    }
+
+    /// Reverts a block previously applied with
+    /// [`apply_block`](UtxoMap::apply_block), as when it is disconnected
+    /// during a reorg.
+    ///
+    /// Transactions are reverted in reverse block order: each transaction's
+    /// outputs are removed before the outputs its inputs spent are restored
+    /// from `spent_outputs`, the block's undo data. Applying a block and then
+    /// reverting it leaves the map as it was, provided the map held every
+    /// output the block spends. Restored outputs are added even if the map
+    /// never held them, for example because it started above their height.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::MismatchedOutputsSize`] if `spent_outputs` does
+    /// not correspond to `block`. The map is left unchanged.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, BlockTreeEntry, ChainstateManager, KernelError, UtxoMap};
+    /// # fn example(chainman: &ChainstateManager, utxos: &mut UtxoMap, entry: &BlockTreeEntry) -> Result<(), KernelError> {
+    /// let block = chainman.read_block_data(entry)?;
+    /// let spent_outputs = chainman.read_spent_outputs(entry)?;
+    /// utxos.revert_block(&block, &spent_outputs)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn revert_block(
+        &mut self,
+        block: &Block,
+        spent_outputs: &impl BlockSpentOutputsExt,
+    ) -> Result<(), KernelError> {
+        spent_outputs.matches_block(block)?;
+
+        let transactions: Vec<_> = block.transactions().collect();
+        let spent: Vec<_> = spent_outputs.iter().collect();
+        for (tx_index, tx) in transactions.iter().enumerate().rev() {
+            let txid = tx.txid().to_bytes();
+            for index in 0..tx.output_count() as u32 {
+                self.utxos.remove(&UtxoOutPoint { txid, index });
+            }
+
+            if tx_index == 0 {
+                continue;
+            }
+            for (input, coin) in tx.inputs().zip(spent[tx_index - 1].coins()) {
+                if is_unspendable(coin.script_pubkey().as_bytes()) {
+                    continue;
+                }
+                self.utxos.insert(
+                    UtxoOutPoint::from(&input.outpoint()),
+                    UtxoEntry {
+                        output: coin.output().to_owned(),
+                        height: coin.confirmation_height(),
+                        is_coinbase: coin.is_coinbase(),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_transactions(&mut self, block: &Block, height: u32) {
+        for (tx_index, tx) in block.transactions().enumerate() {
+            let is_coinbase = tx_index == 0;
+            if !is_coinbase {
+                for input in tx.inputs() {
+                    self.utxos.remove(&UtxoOutPoint::from(&input.outpoint()));
+                }
+            }
+
+            let txid = tx.txid().to_bytes();
+            for (index, output) in tx.outputs_enumerated() {
+                if is_unspendable(output.script_pubkey().as_bytes()) {
+                    continue;
+                }
+                self.utxos.insert(
+                    UtxoOutPoint { txid, index },
+                    UtxoEntry {
+                        output: output.to_owned(),
+                        height,
+                        is_coinbase,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Mirrors Bitcoin Core's `CScript::IsUnspendable`.
+fn is_unspendable(script: &[u8]) -> bool {
+    script.first() == Some(&OP_RETURN) || script.len() > MAX_SCRIPT_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_unspendable() {
+        assert!(is_unspendable(&[OP_RETURN]));
+        assert!(is_unspendable(&[OP_RETURN, 0x01, 0x00]));
+        assert!(is_unspendable(&vec![0x51; MAX_SCRIPT_SIZE + 1]));
+        assert!(!is_unspendable(&[0x51]));
+        assert!(!is_unspendable(&[]));
+    }
+}
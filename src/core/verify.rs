@@ -147,10 +147,13 @@
 //!
 //! The [`verify`] function is thread-safe and can be called concurrently from multiple
 //! threads. All types used in verification are `Send + Sync`.
+//! To verify from async code without blocking the executor, submit
+//! transactions to a [`VerifyPool`](crate::VerifyPool).
 
 use std::{
     error::Error,
     fmt::{self, Display, Formatter},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not},
 };
 
 use libbitcoinkernel_sys::{
@@ -168,48 +171,282 @@ use libbitcoinkernel_sys::{
 };
 
 use crate::{
-    c_helpers, ffi::sealed::AsPtr, KernelError, ScriptPubkeyExt, TransactionExt, TxOutExt,
+    c_helpers, ffi::sealed::AsPtr, ChainType, KernelError, ScriptPubkey, ScriptPubkeyExt,
+    Transaction, TransactionExt, TxOut, TxOutExt,
 };
 
-/// Bitmask of flags controlling which consensus rules [`verify`] enforces.
-pub type ScriptVerificationFlags = btck_ScriptVerificationFlags;
+use super::encoding::decode_hex;
+
+/// Set of flags controlling which consensus rules [`verify`] enforces.
+///
+/// Flags are combined with `|` and tested with [`contains`](Self::contains).
+/// Conversions to and from `u32` are provided for the raw representation used
+/// by the kernel; a value converted from `u32` may carry unknown bits, which
+/// [`validate_flags`] and [`verify`] reject.
+///
+/// # Examples
+/// ```
+/// use bitcoinkernel::{VERIFY_ALL_PRE_TAPROOT, VERIFY_P2SH, VERIFY_TAPROOT, VERIFY_WITNESS};
+///
+/// let flags = VERIFY_P2SH | VERIFY_WITNESS;
+/// assert!(flags.contains(VERIFY_WITNESS));
+/// assert!(VERIFY_ALL_PRE_TAPROOT.contains(flags));
+/// assert!(!VERIFY_ALL_PRE_TAPROOT.contains(VERIFY_TAPROOT));
+/// assert_eq!(flags.to_string(), "P2SH | WITNESS");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ScriptVerificationFlags(btck_ScriptVerificationFlags);
+
+impl ScriptVerificationFlags {
+    const NAMED: [(ScriptVerificationFlags, &'static str); 7] = [
+        (VERIFY_P2SH, "P2SH"),
+        (VERIFY_DERSIG, "DERSIG"),
+        (VERIFY_NULLDUMMY, "NULLDUMMY"),
+        (VERIFY_CHECKLOCKTIMEVERIFY, "CHECKLOCKTIMEVERIFY"),
+        (VERIFY_CHECKSEQUENCEVERIFY, "CHECKSEQUENCEVERIFY"),
+        (VERIFY_WITNESS, "WITNESS"),
+        (VERIFY_TAPROOT, "TAPROOT"),
+    ];
+
+    /// Returns the raw bits of these flags.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the flags for `bits`, or `None` if `bits` contains a bit that
+    /// does not correspond to a known flag.
+    pub const fn from_bits(bits: u32) -> Option<Self> {
+        if bits & !VERIFY_ALL.0 != 0 {
+            return None;
+        }
+        Some(ScriptVerificationFlags(bits))
+    }
+
+    /// Returns `true` if no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all flags in `other` are also set in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the flags set in either `self` or `other`.
+    pub const fn union(self, other: Self) -> Self {
+        ScriptVerificationFlags(self.0 | other.0)
+    }
+
+    /// Sets the flags in `other`.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Clears the flags in `other`.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Returns the flags Bitcoin Core enforces for a block at `height` on
+    /// `chain_type`.
+    ///
+    /// Mirrors Bitcoin Core's `GetBlockScriptFlags`. [`VERIFY_DERSIG`],
+    /// [`VERIFY_CHECKLOCKTIMEVERIFY`], [`VERIFY_CHECKSEQUENCEVERIFY`] and
+    /// [`VERIFY_NULLDUMMY`] are added from the buried activation heights of
+    /// BIP 66, BIP 65, BIP 112 and BIP 141. [`VERIFY_P2SH`],
+    /// [`VERIFY_WITNESS`] and [`VERIFY_TAPROOT`] are enforced at every height,
+    /// as Bitcoin Core does: every historical block is valid under them except
+    /// one mainnet block violating BIP 16, one mainnet block violating
+    /// Taproot and one testnet block violating BIP 16. Bitcoin Core exempts
+    /// those blocks by hash, which this function does not.
+    ///
+    /// # Examples
+    /// ```
+    /// use bitcoinkernel::{
+    ///     ChainType, ScriptVerificationFlags, VERIFY_ALL, VERIFY_CHECKLOCKTIMEVERIFY,
+    ///     VERIFY_NULLDUMMY,
+    /// };
+    ///
+    /// let flags = ScriptVerificationFlags::consensus(400_000, ChainType::Mainnet);
+    /// assert!(flags.contains(VERIFY_CHECKLOCKTIMEVERIFY));
+    /// assert!(!flags.contains(VERIFY_NULLDUMMY));
+    /// assert_eq!(ScriptVerificationFlags::consensus(1, ChainType::Regtest), VERIFY_ALL);
+    /// ```
+    pub const fn consensus(height: i32, chain_type: ChainType) -> Self {
+        // Activation heights of BIP 66, BIP 65, BIP 112 and BIP 141.
+        let (dersig_height, cltv_height, csv_height, segwit_height) = match chain_type {
+            ChainType::Mainnet => (363_725, 388_381, 419_328, 481_824),
+            ChainType::Testnet => (330_776, 581_885, 770_112, 834_624),
+            ChainType::Testnet4 | ChainType::Signet => (1, 1, 1, 1),
+            ChainType::Regtest => (1, 1, 1, 0),
+        };
+        let mut flags = VERIFY_P2SH.union(VERIFY_WITNESS).union(VERIFY_TAPROOT);
+        if height >= dersig_height {
+            flags = flags.union(VERIFY_DERSIG);
+        }
+        if height >= cltv_height {
+            flags = flags.union(VERIFY_CHECKLOCKTIMEVERIFY);
+        }
+        if height >= csv_height {
+            flags = flags.union(VERIFY_CHECKSEQUENCEVERIFY);
+        }
+        if height >= segwit_height {
+            flags = flags.union(VERIFY_NULLDUMMY);
+        }
+        flags
+    }
+}
+
+impl From<u32> for ScriptVerificationFlags {
+    fn from(bits: u32) -> Self {
+        ScriptVerificationFlags(bits)
+    }
+}
+
+impl From<ScriptVerificationFlags> for u32 {
+    fn from(flags: ScriptVerificationFlags) -> Self {
+        flags.0
+    }
+}
+
+impl BitOr for ScriptVerificationFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for ScriptVerificationFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+impl BitAnd for ScriptVerificationFlags {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        ScriptVerificationFlags(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for ScriptVerificationFlags {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Not for ScriptVerificationFlags {
+    type Output = Self;
+
+    /// Returns the known flags that are not set in `self`.
+    fn not(self) -> Self {
+        ScriptVerificationFlags(!self.0 & VERIFY_ALL.0)
+    }
+}
+
+impl Display for ScriptVerificationFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "NONE");
+        }
+        let mut first = true;
+        let mut write_name = |f: &mut Formatter<'_>, name: &dyn Display| {
+            let separator = if first { "" } else { " | " };
+            first = false;
+            write!(f, "{}{}", separator, name)
+        };
+        for (flag, name) in Self::NAMED {
+            if self.contains(flag) {
+                write_name(f, &name)?;
+            }
+        }
+        let unknown = self.0 & !VERIFY_ALL.0;
+        if unknown != 0 {
+            write_name(f, &format_args!("{:#x}", unknown))?;
+        }
+        Ok(())
+    }
+}
 
 /// No verification flags.
-pub const VERIFY_NONE: ScriptVerificationFlags = btck_ScriptVerificationFlags_NONE;
+pub const VERIFY_NONE: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_NONE);
 
 /// Validate Pay-to-Script-Hash (BIP 16).
-pub const VERIFY_P2SH: ScriptVerificationFlags = btck_ScriptVerificationFlags_P2SH;
+pub const VERIFY_P2SH: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_P2SH);
 
 /// Require strict DER encoding for ECDSA signatures (BIP 66).
-pub const VERIFY_DERSIG: ScriptVerificationFlags = btck_ScriptVerificationFlags_DERSIG;
+pub const VERIFY_DERSIG: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_DERSIG);
 
 /// Require the dummy element in OP_CHECKMULTISIG to be empty (BIP 147).
-pub const VERIFY_NULLDUMMY: ScriptVerificationFlags = btck_ScriptVerificationFlags_NULLDUMMY;
+pub const VERIFY_NULLDUMMY: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_NULLDUMMY);
 
 /// Enable OP_CHECKLOCKTIMEVERIFY (BIP 65).
 pub const VERIFY_CHECKLOCKTIMEVERIFY: ScriptVerificationFlags =
-    btck_ScriptVerificationFlags_CHECKLOCKTIMEVERIFY;
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_CHECKLOCKTIMEVERIFY);
 
 /// Enable OP_CHECKSEQUENCEVERIFY (BIP 112).
 pub const VERIFY_CHECKSEQUENCEVERIFY: ScriptVerificationFlags =
-    btck_ScriptVerificationFlags_CHECKSEQUENCEVERIFY;
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_CHECKSEQUENCEVERIFY);
 
 /// Validate Segregated Witness programs (BIP 141/143).
-pub const VERIFY_WITNESS: ScriptVerificationFlags = btck_ScriptVerificationFlags_WITNESS;
+pub const VERIFY_WITNESS: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_WITNESS);
 
 /// Validate Taproot spends (BIP 341/342). Requires spent outputs.
-pub const VERIFY_TAPROOT: ScriptVerificationFlags = btck_ScriptVerificationFlags_TAPROOT;
+pub const VERIFY_TAPROOT: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_TAPROOT);
 
 /// All consensus rules.
-pub const VERIFY_ALL: ScriptVerificationFlags = btck_ScriptVerificationFlags_ALL;
+pub const VERIFY_ALL: ScriptVerificationFlags =
+    ScriptVerificationFlags(btck_ScriptVerificationFlags_ALL);
 
 /// All consensus rules except Taproot.
 pub const VERIFY_ALL_PRE_TAPROOT: ScriptVerificationFlags = VERIFY_P2SH
-    | VERIFY_DERSIG
-    | VERIFY_NULLDUMMY
-    | VERIFY_CHECKLOCKTIMEVERIFY
-    | VERIFY_CHECKSEQUENCEVERIFY
-    | VERIFY_WITNESS;
+    .union(VERIFY_DERSIG)
+    .union(VERIFY_NULLDUMMY)
+    .union(VERIFY_CHECKLOCKTIMEVERIFY)
+    .union(VERIFY_CHECKSEQUENCEVERIFY)
+    .union(VERIFY_WITNESS);
+
+/// Checks that `flags` is a combination [`verify`] accepts.
+///
+/// Performs the same checks as the kernel before it runs any script, so an
+/// invalid combination can be rejected without a verification call:
+///
+/// - Every set bit must correspond to a flag in [`VERIFY_ALL`].
+/// - [`VERIFY_WITNESS`] requires [`VERIFY_P2SH`].
+///
+/// # Errors
+/// * [`ScriptVerifyError::InvalidFlags`] - `flags` contains unknown bits
+/// * [`ScriptVerifyError::InvalidFlagsCombination`] - `flags` enables a rule
+///   without a rule it depends on
+///
+/// # Examples
+/// ```
+/// use bitcoinkernel::{validate_flags, ScriptVerifyError, VERIFY_ALL, VERIFY_P2SH, VERIFY_WITNESS};
+///
+/// assert!(validate_flags(VERIFY_ALL).is_ok());
+/// assert!(validate_flags(VERIFY_P2SH | VERIFY_WITNESS).is_ok());
+/// assert_eq!(
+///     validate_flags(VERIFY_WITNESS),
+///     Err(ScriptVerifyError::InvalidFlagsCombination)
+/// );
+/// ```
+pub fn validate_flags(flags: ScriptVerificationFlags) -> Result<(), ScriptVerifyError> {
+    if ScriptVerificationFlags::from_bits(flags.bits()).is_none() {
+        return Err(ScriptVerifyError::InvalidFlags);
+    }
+    if flags.contains(VERIFY_WITNESS) && !flags.contains(VERIFY_P2SH) {
+        return Err(ScriptVerifyError::InvalidFlagsCombination);
+    }
+    Ok(())
+}
 
 /// Precomputed transaction data for verifying a transaction's scripts.
 ///
@@ -247,6 +484,7 @@ pub const VERIFY_ALL_PRE_TAPROOT: ScriptVerificationFlags = VERIFY_P2SH
 #[derive(Debug)]
 pub struct PrecomputedTransactionData {
     inner: *mut btck_PrecomputedTransactionData,
+    has_spent_outputs: bool,
 }
 
 impl PrecomputedTransactionData {
@@ -278,7 +516,16 @@ impl PrecomputedTransactionData {
                 "Failed to create PrecomputedTransactionData".to_string(),
             ));
         }
-        Ok(PrecomputedTransactionData { inner })
+        Ok(PrecomputedTransactionData {
+            inner,
+            has_spent_outputs: !spent_outputs.is_empty(),
+        })
+    }
+
+    /// Returns `true` if this data was created with the transaction's spent
+    /// outputs, which taproot verification requires.
+    pub fn has_spent_outputs(&self) -> bool {
+        self.has_spent_outputs
     }
 }
 
@@ -292,6 +539,7 @@ impl Clone for PrecomputedTransactionData {
     fn clone(&self) -> Self {
         PrecomputedTransactionData {
             inner: unsafe { btck_precomputed_transaction_data_copy(self.inner) },
+            has_spent_outputs: self.has_spent_outputs,
         }
     }
 }
@@ -313,6 +561,12 @@ unsafe impl Sync for PrecomputedTransactionData {}
 /// satisfies the spending conditions defined by the output script. The verification
 /// process depends on the script type and the consensus rules specified by the flags.
 ///
+/// The input's scriptSig and its full witness stack are taken from `tx_to`, so
+/// nothing beyond the transaction needs to be passed for segwit or taproot
+/// spends. For taproot script-path spends this includes the script, the control
+/// block and an optional annex, which is recognized and removed from the stack
+/// as specified in BIP 341 when [`VERIFY_TAPROOT`] is set.
+///
 /// # Arguments
 ///
 /// * `script_pubkey` - The output script (locking script) to verify against
@@ -395,20 +649,149 @@ pub fn verify(
     flags: Option<ScriptVerificationFlags>,
     precomputed_txdata: &PrecomputedTransactionData,
 ) -> Result<(), KernelError> {
+    verify_detailed(
+        script_pubkey,
+        amount,
+        tx_to,
+        input_index,
+        flags,
+        precomputed_txdata,
+    )?;
+    Ok(())
+}
+
+/// Verifies a transaction input given the output script and spending
+/// transaction as hex strings.
+///
+/// A convenience around [`verify`] for pasting test vectors, such as those in
+/// Bitcoin Core's `script_tests.json`. No spent outputs are passed to the
+/// [`PrecomputedTransactionData`], so taproot spends fail with
+/// [`ScriptVerifyError::SpentOutputsRequired`]; use [`verify`] for those.
+///
+/// # Errors
+/// Returns [`KernelError::InvalidHexCharacter`] or
+/// [`KernelError::InvalidLength`] if either string is not valid hex, an error
+/// if either fails to decode, and otherwise the errors of [`verify`].
+///
+/// # Examples
+/// ```no_run
+/// # use bitcoinkernel::{verify_hex, KernelError, VERIFY_ALL_PRE_TAPROOT};
+/// # fn example(tx_hex: &str) -> Result<(), KernelError> {
+/// verify_hex(
+///     "76a9144bfbaf6afb76cc5771bc6404810d1cc041a6933988ac",
+///     tx_hex,
+///     0,
+///     None,
+///     Some(VERIFY_ALL_PRE_TAPROOT),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify_hex(
+    script_pubkey_hex: &str,
+    tx_hex: &str,
+    input_index: usize,
+    amount: Option<i64>,
+    flags: Option<ScriptVerificationFlags>,
+) -> Result<(), KernelError> {
+    let script_pubkey = ScriptPubkey::new(&decode_hex(script_pubkey_hex)?)?;
+    let tx = Transaction::new(&decode_hex(tx_hex)?)?;
+    let tx_data = PrecomputedTransactionData::new(&tx, &Vec::<TxOut>::new())?;
+    verify(&script_pubkey, amount, &tx, input_index, flags, &tx_data)
+}
+
+/// Which consensus rules a successful [`verify_detailed`] call enforced.
+///
+/// The script itself is always evaluated, but the soft-fork rules applied on
+/// top of it depend on the verification flags. A script that passes with
+/// [`VERIFY_NONE`] or [`VERIFY_ALL_PRE_TAPROOT`] may still be invalid under
+/// the full rule set, so test harnesses can use this to confirm that the rules
+/// they care about actually ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VerifyReport {
+    flags: ScriptVerificationFlags,
+    spent_outputs_provided: bool,
+}
+
+impl VerifyReport {
+    /// Returns the verification flags that were enforced.
+    ///
+    /// If `None` was passed to [`verify_detailed`], these are [`VERIFY_ALL`].
+    pub fn flags(&self) -> ScriptVerificationFlags {
+        self.flags
+    }
+
+    /// Returns `true` if the [`PrecomputedTransactionData`] carried the
+    /// transaction's spent outputs.
+    pub fn spent_outputs_provided(&self) -> bool {
+        self.spent_outputs_provided
+    }
+
+    /// Returns `true` if segwit witness rules were enforced.
+    pub fn witness_checked(&self) -> bool {
+        self.flags.contains(VERIFY_WITNESS)
+    }
+
+    /// Returns `true` if taproot rules were enforced.
+    ///
+    /// The kernel refuses to verify with [`VERIFY_TAPROOT`] unless the spent
+    /// outputs are provided, so a successful verification only reports
+    /// taproot as checked when both were present.
+    pub fn taproot_checked(&self) -> bool {
+        self.flags.contains(VERIFY_TAPROOT) && self.spent_outputs_provided
+    }
+
+    /// Returns `true` if no soft-fork rules were enforced, i.e. the script was
+    /// verified with [`VERIFY_NONE`].
+    pub fn is_base_only(&self) -> bool {
+        self.flags == VERIFY_NONE
+    }
+}
+
+/// Verifies a transaction input like [`verify`], reporting which rules ran.
+///
+/// Takes the same arguments and returns the same errors as [`verify`]. On
+/// success, the returned [`VerifyReport`] records the flags that were
+/// enforced and whether spent outputs were available, so callers can tell a
+/// full verification apart from one where rules such as taproot were
+/// skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, PrecomputedTransactionData, Transaction, TxOut, verify_detailed, VERIFY_ALL_PRE_TAPROOT};
+/// # let tx_bytes = vec![];
+/// # let spending_tx = Transaction::new(&tx_bytes).unwrap();
+/// # let prev_output = spending_tx.output(0).unwrap();
+/// let tx_data = PrecomputedTransactionData::new(&spending_tx, &Vec::<TxOut>::new()).unwrap();
+///
+/// let report = verify_detailed(
+///     &prev_output.script_pubkey(),
+///     Some(prev_output.value()),
+///     &spending_tx,
+///     0,
+///     Some(VERIFY_ALL_PRE_TAPROOT),
+///     &tx_data,
+/// )
+/// .unwrap();
+/// assert!(!report.taproot_checked());
+/// ```
+pub fn verify_detailed(
+    script_pubkey: &impl ScriptPubkeyExt,
+    amount: Option<i64>,
+    tx_to: &impl TransactionExt,
+    input_index: usize,
+    flags: Option<ScriptVerificationFlags>,
+    precomputed_txdata: &PrecomputedTransactionData,
+) -> Result<VerifyReport, KernelError> {
     let input_count = tx_to.input_count();
 
     if input_index >= input_count {
         return Err(KernelError::ScriptVerify(ScriptVerifyError::TxInputIndex));
     }
 
-    let kernel_flags = if let Some(flag) = flags {
-        if (flag & !VERIFY_ALL) != 0 {
-            return Err(KernelError::ScriptVerify(ScriptVerifyError::InvalidFlags));
-        }
-        flag
-    } else {
-        VERIFY_ALL
-    };
+    let kernel_flags = flags.unwrap_or(VERIFY_ALL);
+    validate_flags(kernel_flags).map_err(KernelError::ScriptVerify)?;
 
     let kernel_amount = amount.unwrap_or_default();
     let mut status = ScriptVerifyStatus::Ok.into();
@@ -420,7 +803,7 @@ pub fn verify(
             tx_to.as_ptr(),
             precomputed_txdata.as_ptr(),
             input_index as u32,
-            kernel_flags,
+            kernel_flags.bits(),
             &mut status,
         )
     };
@@ -439,10 +822,68 @@ pub fn verify(
         };
         Err(KernelError::ScriptVerify(err))
     } else {
-        Ok(())
+        Ok(VerifyReport {
+            flags: kernel_flags,
+            spent_outputs_provided: precomputed_txdata.has_spent_outputs(),
+        })
     }
 }
 
+/// Verifies every input of a transaction against the outputs it spends.
+///
+/// `spent_outputs` must hold the output spent by each input, in input order.
+/// Each input is verified as by [`verify`] with the amount and script of its
+/// spent output, stopping at the first input that fails.
+///
+/// # Errors
+///
+/// * `Err(KernelError::MismatchedOutputsSize)` - `spent_outputs` does not hold
+///   exactly one output per input
+/// * `Err(KernelError::InputVerify { input_index, error })` - The input at
+///   `input_index` failed verification with `error`
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bitcoinkernel::{prelude::*, Transaction, TxOut, verify_transaction, KernelError};
+/// # let tx_bytes = vec![];
+/// # let spending_tx = Transaction::new(&tx_bytes).unwrap();
+/// # let spent_outputs: Vec<TxOut> = vec![];
+/// match verify_transaction(&spending_tx, &spent_outputs, None) {
+///     Ok(()) => println!("All inputs are valid"),
+///     Err(KernelError::InputVerify { input_index, error }) => {
+///         println!("Input {} failed: {}", input_index, error)
+///     }
+///     Err(err) => println!("Could not verify: {}", err),
+/// }
+/// ```
+pub fn verify_transaction(
+    tx_to: &impl TransactionExt,
+    spent_outputs: &[impl TxOutExt],
+    flags: Option<ScriptVerificationFlags>,
+) -> Result<(), KernelError> {
+    if spent_outputs.len() != tx_to.input_count() {
+        return Err(KernelError::MismatchedOutputsSize);
+    }
+    let tx_data = PrecomputedTransactionData::new(tx_to, spent_outputs)?;
+
+    for (input_index, spent_output) in spent_outputs.iter().enumerate() {
+        verify(
+            &spent_output.script_pubkey(),
+            Some(spent_output.value()),
+            tx_to,
+            input_index,
+            flags,
+            &tx_data,
+        )
+        .map_err(|err| match err {
+            KernelError::ScriptVerify(error) => KernelError::InputVerify { input_index, error },
+            err => err,
+        })?;
+    }
+    Ok(())
+}
+
 /// Internal status codes from the C verification function.
 ///
 /// These are used internally to distinguish between setup errors (invalid flags,
@@ -501,7 +942,11 @@ impl From<btck_ScriptVerifyStatus> for ScriptVerifyStatus {
 ///
 /// These errors represent both configuration problems (incorrect parameters)
 /// and actual verification failures (invalid scripts).
-#[derive(Debug)]
+///
+/// The kernel only reports whether a script passed, not the script error or
+/// the position of the failing opcode, so [`Invalid`](Self::Invalid) carries
+/// no further detail. [`verify_transaction`] reports which input failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ScriptVerifyError {
     /// The specified input index is out of bounds.
     ///
@@ -552,21 +997,50 @@ mod tests {
 
     #[test]
     fn test_verify_constants() {
-        assert_eq!(VERIFY_NONE, btck_ScriptVerificationFlags_NONE);
-        assert_eq!(VERIFY_P2SH, btck_ScriptVerificationFlags_P2SH);
-        assert_eq!(VERIFY_DERSIG, btck_ScriptVerificationFlags_DERSIG);
-        assert_eq!(VERIFY_NULLDUMMY, btck_ScriptVerificationFlags_NULLDUMMY);
+        assert_eq!(VERIFY_NONE.bits(), btck_ScriptVerificationFlags_NONE);
+        assert_eq!(VERIFY_P2SH.bits(), btck_ScriptVerificationFlags_P2SH);
+        assert_eq!(VERIFY_DERSIG.bits(), btck_ScriptVerificationFlags_DERSIG);
         assert_eq!(
-            VERIFY_CHECKLOCKTIMEVERIFY,
+            VERIFY_NULLDUMMY.bits(),
+            btck_ScriptVerificationFlags_NULLDUMMY
+        );
+        assert_eq!(
+            VERIFY_CHECKLOCKTIMEVERIFY.bits(),
             btck_ScriptVerificationFlags_CHECKLOCKTIMEVERIFY
         );
         assert_eq!(
-            VERIFY_CHECKSEQUENCEVERIFY,
+            VERIFY_CHECKSEQUENCEVERIFY.bits(),
             btck_ScriptVerificationFlags_CHECKSEQUENCEVERIFY
         );
-        assert_eq!(VERIFY_WITNESS, btck_ScriptVerificationFlags_WITNESS);
-        assert_eq!(VERIFY_TAPROOT, btck_ScriptVerificationFlags_TAPROOT);
-        assert_eq!(VERIFY_ALL, btck_ScriptVerificationFlags_ALL);
+        assert_eq!(VERIFY_WITNESS.bits(), btck_ScriptVerificationFlags_WITNESS);
+        assert_eq!(VERIFY_TAPROOT.bits(), btck_ScriptVerificationFlags_TAPROOT);
+        assert_eq!(VERIFY_ALL.bits(), btck_ScriptVerificationFlags_ALL);
+    }
+
+    #[test]
+    fn test_verify_report() {
+        let report = VerifyReport {
+            flags: VERIFY_ALL,
+            spent_outputs_provided: true,
+        };
+        assert!(report.witness_checked());
+        assert!(report.taproot_checked());
+        assert!(!report.is_base_only());
+
+        let report = VerifyReport {
+            flags: VERIFY_ALL_PRE_TAPROOT,
+            spent_outputs_provided: true,
+        };
+        assert!(report.witness_checked());
+        assert!(!report.taproot_checked());
+
+        let report = VerifyReport {
+            flags: VERIFY_NONE,
+            spent_outputs_provided: false,
+        };
+        assert!(!report.witness_checked());
+        assert!(!report.taproot_checked());
+        assert!(report.is_base_only());
     }
 
     #[test]
@@ -580,27 +1054,144 @@ mod tests {
 
         assert_eq!(VERIFY_ALL_PRE_TAPROOT, expected);
 
-        assert_eq!(VERIFY_ALL_PRE_TAPROOT & VERIFY_TAPROOT, 0);
+        assert!((VERIFY_ALL_PRE_TAPROOT & VERIFY_TAPROOT).is_empty());
     }
 
     #[test]
     fn test_verification_flag_combinations() {
         let flags = VERIFY_P2SH | VERIFY_WITNESS;
-        assert!(flags & VERIFY_P2SH != 0);
-        assert!(flags & VERIFY_WITNESS != 0);
-        assert!(flags & VERIFY_TAPROOT == 0);
+        assert!(flags.contains(VERIFY_P2SH));
+        assert!(flags.contains(VERIFY_WITNESS));
+        assert!(!flags.contains(VERIFY_TAPROOT));
     }
 
     #[test]
-    #[allow(clippy::assertions_on_constants)]
     fn test_verify_all_includes_all_flags() {
-        assert!((VERIFY_ALL & VERIFY_P2SH) != 0);
-        assert!((VERIFY_ALL & VERIFY_DERSIG) != 0);
-        assert!((VERIFY_ALL & VERIFY_NULLDUMMY) != 0);
-        assert!((VERIFY_ALL & VERIFY_CHECKLOCKTIMEVERIFY) != 0);
-        assert!((VERIFY_ALL & VERIFY_CHECKSEQUENCEVERIFY) != 0);
-        assert!((VERIFY_ALL & VERIFY_WITNESS) != 0);
-        assert!((VERIFY_ALL & VERIFY_TAPROOT) != 0);
+        assert!(VERIFY_ALL.contains(VERIFY_P2SH));
+        assert!(VERIFY_ALL.contains(VERIFY_DERSIG));
+        assert!(VERIFY_ALL.contains(VERIFY_NULLDUMMY));
+        assert!(VERIFY_ALL.contains(VERIFY_CHECKLOCKTIMEVERIFY));
+        assert!(VERIFY_ALL.contains(VERIFY_CHECKSEQUENCEVERIFY));
+        assert!(VERIFY_ALL.contains(VERIFY_WITNESS));
+        assert!(VERIFY_ALL.contains(VERIFY_TAPROOT));
+    }
+
+    #[test]
+    fn test_flags_insert_remove() {
+        let mut flags = VERIFY_NONE;
+        assert!(flags.is_empty());
+
+        flags.insert(VERIFY_P2SH);
+        flags |= VERIFY_WITNESS;
+        assert_eq!(flags, VERIFY_P2SH | VERIFY_WITNESS);
+
+        flags.remove(VERIFY_P2SH);
+        assert_eq!(flags, VERIFY_WITNESS);
+
+        flags &= VERIFY_P2SH;
+        assert!(flags.is_empty());
+
+        assert_eq!(VERIFY_ALL & !VERIFY_TAPROOT, VERIFY_ALL_PRE_TAPROOT);
+        assert_eq!(!VERIFY_ALL, VERIFY_NONE);
+    }
+
+    #[test]
+    fn test_flags_from_bits() {
+        assert_eq!(
+            ScriptVerificationFlags::from_bits(VERIFY_ALL.bits()),
+            Some(VERIFY_ALL)
+        );
+        assert_eq!(ScriptVerificationFlags::from_bits(1 << 1), None);
+
+        let raw: u32 = (VERIFY_P2SH | VERIFY_DERSIG).into();
+        assert_eq!(raw, 0b101);
+        assert_eq!(
+            ScriptVerificationFlags::from(raw),
+            VERIFY_P2SH | VERIFY_DERSIG
+        );
+        assert_eq!(ScriptVerificationFlags::from(1 << 1).bits(), 1 << 1);
+    }
+
+    #[test]
+    fn test_flags_consensus() {
+        let always = VERIFY_P2SH | VERIFY_WITNESS | VERIFY_TAPROOT;
+        assert_eq!(
+            ScriptVerificationFlags::consensus(0, ChainType::Mainnet),
+            always
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(363_724, ChainType::Mainnet),
+            always
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(363_725, ChainType::Mainnet),
+            always | VERIFY_DERSIG
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(419_328, ChainType::Mainnet),
+            VERIFY_ALL & !VERIFY_NULLDUMMY
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(481_824, ChainType::Mainnet),
+            VERIFY_ALL
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(481_824, ChainType::Testnet),
+            always | VERIFY_DERSIG
+        );
+
+        assert_eq!(
+            ScriptVerificationFlags::consensus(0, ChainType::Signet),
+            always
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(1, ChainType::Testnet4),
+            VERIFY_ALL
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(0, ChainType::Regtest),
+            always | VERIFY_NULLDUMMY
+        );
+        assert_eq!(
+            ScriptVerificationFlags::consensus(1, ChainType::Regtest),
+            VERIFY_ALL
+        );
+    }
+
+    #[test]
+    fn test_flags_display() {
+        assert_eq!(VERIFY_NONE.to_string(), "NONE");
+        assert_eq!(VERIFY_TAPROOT.to_string(), "TAPROOT");
+        assert_eq!(
+            VERIFY_ALL.to_string(),
+            "P2SH | DERSIG | NULLDUMMY | CHECKLOCKTIMEVERIFY | CHECKSEQUENCEVERIFY | WITNESS | TAPROOT"
+        );
+        assert_eq!(
+            (VERIFY_P2SH | ScriptVerificationFlags::from(1 << 1)).to_string(),
+            "P2SH | 0x2"
+        );
+    }
+
+    #[test]
+    fn test_validate_flags() {
+        assert_eq!(validate_flags(VERIFY_NONE), Ok(()));
+        assert_eq!(validate_flags(VERIFY_ALL), Ok(()));
+        assert_eq!(validate_flags(VERIFY_ALL_PRE_TAPROOT), Ok(()));
+        assert_eq!(validate_flags(VERIFY_P2SH | VERIFY_WITNESS), Ok(()));
+        assert_eq!(validate_flags(VERIFY_TAPROOT), Ok(()));
+
+        assert_eq!(
+            validate_flags(VERIFY_WITNESS),
+            Err(ScriptVerifyError::InvalidFlagsCombination)
+        );
+        assert_eq!(
+            validate_flags(VERIFY_DERSIG | VERIFY_WITNESS),
+            Err(ScriptVerifyError::InvalidFlagsCombination)
+        );
+        assert_eq!(
+            validate_flags(ScriptVerificationFlags::from(1 << 1)),
+            Err(ScriptVerifyError::InvalidFlags)
+        );
     }
 
     #[test]
@@ -0,0 +1,341 @@
+//! Running script verification on a pool of background threads.
+//!
+//! [`verify_transaction`](crate::verify_transaction) blocks the calling thread
+//! for the duration of the FFI calls, which stalls an async executor when
+//! called from a task. A [`VerifyPool`] owns a fixed set of worker threads and
+//! hands back a [`VerifyFuture`] for every submitted transaction, so a server
+//! can `.await` the result without blocking the executor. The future does not
+//! depend on a particular runtime; outside of async code, call
+//! [`VerifyFuture::wait`] instead.
+//!
+//! # Example
+//! ```no_run
+//! # use bitcoinkernel::{KernelError, Transaction, TxOut, VerifyPool};
+//! # async fn example(pool: &VerifyPool, tx: Transaction, spent_outputs: Vec<TxOut>) -> Result<(), KernelError> {
+//! pool.verify_transaction(tx, spent_outputs, None).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Condvar, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+};
+
+use crate::{state::context::DEFAULT_THREAD_NAME_PREFIX, KernelError};
+
+use super::{verify::verify_transaction, ScriptVerificationFlags, Transaction, TxOut};
+
+/// A transaction submitted to a [`VerifyPool`], together with the slot its
+/// result is delivered to.
+struct Job {
+    tx: Transaction,
+    spent_outputs: Vec<TxOut>,
+    flags: Option<ScriptVerificationFlags>,
+    completion: Completion,
+}
+
+/// Result slot shared between a [`Job`] and its [`VerifyFuture`].
+#[derive(Default)]
+struct Shared {
+    state: Mutex<SharedState>,
+    done: Condvar,
+}
+
+#[derive(Default)]
+struct SharedState {
+    result: Option<Result<(), KernelError>>,
+    waker: Option<Waker>,
+}
+
+impl Shared {
+    fn set(&self, result: Result<(), KernelError>) {
+        let mut state = self.state.lock().unwrap();
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.done.notify_all();
+    }
+}
+
+/// Write side of a [`Shared`] slot. If it is dropped without a result, for
+/// example because the verification panicked, the future resolves to an error
+/// instead of waiting forever.
+struct Completion {
+    shared: Option<Arc<Shared>>,
+}
+
+impl Completion {
+    fn complete(mut self, result: Result<(), KernelError>) {
+        if let Some(shared) = self.shared.take() {
+            shared.set(result);
+        }
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            shared.set(Err(KernelError::Internal(
+                "Verification job was dropped before completing.".to_string(),
+            )));
+        }
+    }
+}
+
+/// A fixed-size pool of threads that verify transactions off the calling
+/// thread.
+///
+/// Submitted transactions are verified with
+/// [`verify_transaction`](crate::verify_transaction) in submission order by
+/// whichever worker is free. The pool is not tied to a [`Context`](crate::Context),
+/// so its worker threads always use the default thread name prefix and are
+/// named `btck-verify-N`.
+///
+/// Dropping the pool finishes the transactions already submitted, so their
+/// futures still resolve, and then joins the worker threads.
+///
+/// # Thread Safety
+/// [`VerifyPool`] is `Send` and `Sync`, so it can be shared behind an
+/// [`Arc`] by every task of a server.
+pub struct VerifyPool {
+    sender: Option<Mutex<Sender<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl VerifyPool {
+    /// Creates a pool with `threads` worker threads. A `threads` value of 0 is
+    /// treated as 1.
+    ///
+    /// # Panics
+    /// Panics if a worker thread cannot be spawned.
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads.max(1))
+            .map(|index| {
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("{}-verify-{}", DEFAULT_THREAD_NAME_PREFIX, index))
+                    .spawn(move || run_worker(&receiver))
+                    .expect("failed to spawn verify thread")
+            })
+            .collect();
+
+        VerifyPool {
+            sender: Some(Mutex::new(sender)),
+            workers,
+        }
+    }
+
+    /// Returns the number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Submits every input of `tx` for verification against `spent_outputs`
+    /// and returns a future resolving to the result.
+    ///
+    /// The arguments and result are those of
+    /// [`verify_transaction`](crate::verify_transaction). Verification starts
+    /// as soon as a worker is free, whether or not the future is polled.
+    pub fn verify_transaction(
+        &self,
+        tx: Transaction,
+        spent_outputs: Vec<TxOut>,
+        flags: Option<ScriptVerificationFlags>,
+    ) -> VerifyFuture {
+        let shared = Arc::new(Shared::default());
+        let job = Job {
+            tx,
+            spent_outputs,
+            flags,
+            completion: Completion {
+                shared: Some(Arc::clone(&shared)),
+            },
+        };
+        if let Some(sender) = &self.sender {
+            // A send error drops the job, which resolves the future to an error.
+            let _ = sender.lock().unwrap().send(job);
+        }
+        VerifyFuture { shared }
+    }
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // The lock is released before verifying, so other workers can take
+        // the next job.
+        let job = match receiver.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => break,
+        };
+        let result = verify_transaction(&job.tx, &job.spent_outputs, job.flags);
+        job.completion.complete(result);
+    }
+}
+
+impl Drop for VerifyPool {
+    fn drop(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// The pending result of a transaction submitted to a [`VerifyPool`].
+///
+/// Resolves to the result of
+/// [`verify_transaction`](crate::verify_transaction) for the submitted
+/// transaction. Dropping the future does not cancel the verification.
+pub struct VerifyFuture {
+    shared: Arc<Shared>,
+}
+
+impl VerifyFuture {
+    /// Blocks the current thread until the verification has finished and
+    /// returns its result.
+    pub fn wait(self) -> Result<(), KernelError> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self.shared.done.wait(state).unwrap();
+        }
+    }
+}
+
+impl Future for VerifyFuture {
+    type Output = Result<(), KernelError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Wake;
+
+    use super::*;
+    use crate::{ScriptPubkey, ScriptVerifyError};
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Spends a single `OP_TRUE` output with an empty scriptSig.
+    fn op_true_spend() -> (Transaction, Vec<TxOut>) {
+        let tx_hex = format!(
+            "0100000001{}{}00ffffffff01{}0000000000",
+            "00".repeat(32),
+            "00000000",
+            "00".repeat(8)
+        );
+        let tx = Transaction::new(&hex::decode(tx_hex).unwrap()).unwrap();
+        let spent_output = TxOut::new(&ScriptPubkey::new(&[0x51]).unwrap(), 0);
+        (tx, vec![spent_output])
+    }
+
+    #[test]
+    fn test_verify_pool_threads() {
+        assert_eq!(VerifyPool::new(0).threads(), 1);
+        assert_eq!(VerifyPool::new(3).threads(), 3);
+    }
+
+    #[test]
+    fn test_verify_pool_thread_names() {
+        let pool = VerifyPool::new(2);
+        let names: Vec<_> = pool
+            .workers
+            .iter()
+            .map(|worker| worker.thread().name().unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["btck-verify-0", "btck-verify-1"]);
+    }
+
+    #[test]
+    fn test_verify_pool_wait() {
+        let pool = VerifyPool::new(2);
+        let futures: Vec<_> = (0..8)
+            .map(|_| {
+                let (tx, spent_outputs) = op_true_spend();
+                pool.verify_transaction(tx, spent_outputs, None)
+            })
+            .collect();
+        for future in futures {
+            future.wait().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_verify_pool_await() {
+        let pool = VerifyPool::new(1);
+
+        let (tx, spent_outputs) = op_true_spend();
+        block_on(pool.verify_transaction(tx, spent_outputs, None)).unwrap();
+
+        let (tx, _) = op_true_spend();
+        let op_false = TxOut::new(&ScriptPubkey::new(&[0x00]).unwrap(), 0);
+        let result = block_on(pool.verify_transaction(tx, vec![op_false], None));
+        assert!(matches!(
+            result,
+            Err(KernelError::InputVerify {
+                input_index: 0,
+                error: ScriptVerifyError::Invalid
+            })
+        ));
+
+        let (tx, _) = op_true_spend();
+        let result = block_on(pool.verify_transaction(tx, Vec::new(), None));
+        assert!(matches!(result, Err(KernelError::MismatchedOutputsSize)));
+    }
+
+    #[test]
+    fn test_verify_pool_drop_finishes_pending() {
+        let pool = VerifyPool::new(1);
+        let futures: Vec<_> = (0..4)
+            .map(|_| {
+                let (tx, spent_outputs) = op_true_spend();
+                pool.verify_transaction(tx, spent_outputs, None)
+            })
+            .collect();
+        drop(pool);
+        for future in futures {
+            future.wait().unwrap();
+        }
+    }
+}
@@ -100,8 +100,8 @@
 //!         registry.register_warning_set(|warning, message| {
 //!             eprintln!("Warning: {} - {}", warning, message);
 //!         });
-//!         registry.register_flush_error(|message| {
-//!             eprintln!("Flush error: {}", message);
+//!         registry.register_flush_error(|kind, message| {
+//!             eprintln!("Flush error ({}): {}", kind, message);
 //!             // Consider tearing down context and terminating operations
 //!         });
 //!         registry.register_fatal_error(|message| {
@@ -156,6 +156,27 @@
 //! - **Borrowed types** (e.g., `BlockRef`, `TransactionRef`): Zero-copy views into data
 //! - **Extension traits**: Provide ergonomic methods (use `prelude::*` to import)
 //!
+//! ## Allocations
+//!
+//! Owned types are allocated by `libbitcoinkernel` itself, but most accessors
+//! on them do not allocate on the Rust side. The following operations are
+//! allocation-free and work with borrowed or fixed-size buffers:
+//!
+//! - [`ScriptPubkeyExt::as_bytes`]: zero-copy view of a script
+//! - [`BlockHeaderExt::consensus_encode`](crate::core::BlockHeaderExt::consensus_encode): fixed `[u8; 80]`
+//! - [`BlockHashExt::to_bytes`](crate::core::BlockHashExt::to_bytes) and
+//!   [`TxidExt::to_bytes`](crate::core::TxidExt::to_bytes): fixed `[u8; 32]`
+//! - [`Block::consensus_encode_to`] and
+//!   [`TransactionExt::consensus_encode_to`]: stream into any [`std::io::Write`]
+//! - [`TxOutExt::value`] and [`TxOutExt::script_pubkey`]
+//! - [`verify`] with a [`PrecomputedTransactionData`] created ahead of time
+//!
+//! The following operations allocate a `Vec` on the Rust side:
+//!
+//! - [`Block::consensus_encode`] and [`TransactionExt::consensus_encode`]
+//! - [`ScriptPubkeyExt::to_bytes`]
+//! - [`PrecomputedTransactionData::new`], which collects the spent output pointers
+//!
 //! ## Error Handling
 //!
 //! The crate provides multiple layers of error handling:
@@ -233,6 +254,63 @@ where
     }
 }
 
+/// Serializes data using a C callback function pattern, streaming the bytes
+/// into the given writer.
+///
+/// Unlike [`c_serialize`], this does not collect the output into an
+/// intermediate buffer.
+fn c_serialize_to<W, F>(writer: &mut W, c_function: F) -> Result<(), KernelError>
+where
+    W: std::io::Write + ?Sized,
+    F: FnOnce(
+        unsafe extern "C" fn(*const std::ffi::c_void, usize, *mut std::ffi::c_void) -> i32,
+        *mut std::ffi::c_void,
+    ) -> i32,
+{
+    struct WriterHolder<'a> {
+        writer: &'a mut dyn std::io::Write,
+    }
+
+    unsafe extern "C" fn write_callback(
+        data: *const std::ffi::c_void,
+        len: usize,
+        user_data: *mut std::ffi::c_void,
+    ) -> i32 {
+        panic::catch_unwind(|| {
+            let holder = &mut *(user_data as *mut WriterHolder);
+            let slice = std::slice::from_raw_parts(data as *const u8, len);
+            c_helpers::to_c_result(holder.writer.write_all(slice).is_ok())
+        })
+        .unwrap_or_else(|_| c_helpers::to_c_result(false))
+    }
+
+    let mut writer = writer;
+    let mut holder = WriterHolder {
+        writer: &mut writer,
+    };
+    let result = c_function(
+        write_callback,
+        &mut holder as *mut WriterHolder as *mut std::ffi::c_void,
+    );
+
+    if c_helpers::success(result) {
+        Ok(())
+    } else {
+        Err(KernelError::SerializationFailed)
+    }
+}
+
+/// Encodes bytes as a lowercase hex string.
+fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize] as char);
+        hex.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    hex
+}
+
 /// A collection of errors emitted by this library
 #[derive(Debug)]
 pub enum KernelError {
@@ -241,9 +319,59 @@ pub enum KernelError {
     InvalidOptions(String),
     OutOfBounds,
     ScriptVerify(ScriptVerifyError),
+    InputVerify {
+        input_index: usize,
+        error: ScriptVerifyError,
+    },
     SerializationFailed,
     MismatchedOutputsSize,
-    InvalidLength { expected: usize, actual: usize },
+    InvalidLength {
+        expected: usize,
+        actual: usize,
+    },
+    DatabaseError(String),
+    InvalidAmount(i64),
+    InvalidMagic {
+        expected: [u8; 4],
+        actual: [u8; 4],
+    },
+    InvalidBlock(BlockValidationResult),
+    InvalidHexCharacter {
+        character: char,
+        index: usize,
+    },
+}
+
+impl KernelError {
+    /// Returns `true` if the operation that failed may succeed when retried.
+    ///
+    /// Only [`DatabaseError`](KernelError::DatabaseError) is retryable: it
+    /// reports a flush failure, for example from a full disk, that goes away
+    /// once the underlying condition is resolved. The failure is recorded on
+    /// the [`Context`], so the retry must recreate both the `Context` and the
+    /// [`ChainstateManager`] before reopening the data directory; a new manager
+    /// built from the same `Context` keeps returning the error. See the
+    /// "Flush Errors" section of [`ChainstateManager`]. Every other error, such
+    /// as an invalid block, a bad path or malformed input, fails the same way
+    /// on every attempt.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            KernelError::DatabaseError(_) => true,
+            KernelError::Internal(_)
+            | KernelError::CStringCreationFailed(_)
+            | KernelError::InvalidOptions(_)
+            | KernelError::OutOfBounds
+            | KernelError::ScriptVerify(_)
+            | KernelError::InputVerify { .. }
+            | KernelError::SerializationFailed
+            | KernelError::MismatchedOutputsSize
+            | KernelError::InvalidLength { .. }
+            | KernelError::InvalidAmount(_)
+            | KernelError::InvalidMagic { .. }
+            | KernelError::InvalidBlock(_)
+            | KernelError::InvalidHexCharacter { .. } => false,
+        }
+    }
 }
 
 impl From<NulError> for KernelError {
@@ -262,11 +390,27 @@ impl fmt::Display for KernelError {
             KernelError::InvalidOptions(msg) => write!(f, "Invalid options: {}", msg),
             KernelError::OutOfBounds => write!(f, "Out of bounds"),
             KernelError::ScriptVerify(err) => write!(f, "Script verification error: {}", err),
+            KernelError::InputVerify { input_index, error } => write!(
+                f,
+                "Script verification error at input {}: {}",
+                input_index, error
+            ),
             KernelError::SerializationFailed => write!(f, "Serialization failed"),
             KernelError::MismatchedOutputsSize => write!(f, "Number of outputs size does not correspond to the number of inputs of the transaction."),
             KernelError::InvalidLength { expected, actual } => {
                 write!(f, "Invalid length: expected {}, got {}", expected, actual)
             }
+            KernelError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            KernelError::InvalidAmount(amount) => write!(f, "Invalid amount: {}", amount),
+            KernelError::InvalidMagic { expected, actual } => write!(
+                f,
+                "Invalid network magic: expected {:02x?}, got {:02x?}",
+                expected, actual
+            ),
+            KernelError::InvalidBlock(result) => write!(f, "Invalid block: {:?}", result),
+            KernelError::InvalidHexCharacter { character, index } => {
+                write!(f, "Invalid hex character {:?} at index {}", character, index)
+            }
         }
     }
 }
@@ -275,31 +419,39 @@ impl std::error::Error for KernelError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             KernelError::ScriptVerify(err) => Some(err),
+            KernelError::InputVerify { error, .. } => Some(error),
             _ => None,
         }
     }
 }
 
 pub use crate::core::{
-    verify, Block, BlockCheckFlags, BlockCheckResult, BlockHash, BlockHeader, BlockSpentOutputs,
-    BlockSpentOutputsRef, BlockTreeEntry, Coin, CoinRef, PrecomputedTransactionData, ScriptPubkey,
-    ScriptPubkeyRef, ScriptVerificationFlags, ScriptVerifyError, Transaction, TransactionRef,
-    TransactionSpentOutputs, TransactionSpentOutputsRef, TxCheckResult, TxIn, TxInRef, TxOut,
-    TxOutPoint, TxOutPointRef, TxOutRef, Txid, TxidRef,
+    block_subsidy, verify, verify_detailed, verify_hex, verify_transaction, Block, BlockCheckFlags,
+    BlockCheckResult, BlockFileReader, BlockFileReaderStats, BlockHash, BlockHeader,
+    BlockSpentOutputs, BlockSpentOutputsRef, BlockTreeEntry, Coin, CoinProvider, CoinRef,
+    CompactTarget, FeeRate, HasScriptPubkey, PrecomputedTransactionData, ScriptPubkey,
+    ScriptPubkeyRef, ScriptVerificationFlags, ScriptVerifyError, SpentOutputsCoinProvider,
+    Transaction, TransactionBuilder, TransactionRef, TransactionSpentOutputs,
+    TransactionSpentOutputsRef, TxCheckResult, TxIn, TxInRef, TxOut, TxOutPoint, TxOutPointRef,
+    TxOutRef, Txid, TxidRef, UtxoEntry, UtxoMap, UtxoOutPoint, VerifyFuture, VerifyPool,
+    VerifyReport, Witness, WitnessIter, COIN, COINBASE_MATURITY, LOCKTIME_THRESHOLD,
+    MAX_BIP125_RBF_SEQUENCE, MAX_MONEY, MAX_SCRIPT_SIZE, SEQUENCE_FINAL,
 };
 
 pub use crate::log::{disable_logging, Log, LogCategory, LogLevel, Logger};
 
 pub use crate::notifications::{
     BlockCheckedCallback, BlockTipCallback, BlockValidationResult, BlockValidationStateRef,
-    FatalErrorCallback, FlushErrorCallback, HeaderTipCallback, NotificationCallbackRegistry,
-    ProgressCallback, SynchronizationState, ValidationCallbackRegistry, ValidationMode, Warning,
-    WarningSetCallback, WarningUnsetCallback,
+    FatalErrorCallback, FlushErrorCallback, FlushErrorKind, HeaderTipCallback,
+    KernelNotificationHandler, NotificationCallbackRegistry, ProgressCallback,
+    SynchronizationState, ValidationCallbackRegistry, ValidationMode, Warning, WarningSetCallback,
+    WarningUnsetCallback,
 };
 
 pub use crate::state::{
     Chain, ChainParams, ChainType, ChainstateManager, ChainstateManagerBuilder, Context,
-    ContextBuilder, ProcessBlockHeaderResult, ProcessBlockResult,
+    ContextBuilder, ParallelBlockIter, ProcessBlockHeaderResult, ProcessBlockResult,
+    BLOCK_STREAM_CAPACITY,
 };
 
 pub use crate::core::block_check_flags::{
@@ -307,13 +459,14 @@ pub use crate::core::block_check_flags::{
 };
 
 pub use crate::core::verify_flags::{
-    VERIFY_ALL, VERIFY_ALL_PRE_TAPROOT, VERIFY_CHECKLOCKTIMEVERIFY, VERIFY_CHECKSEQUENCEVERIFY,
-    VERIFY_DERSIG, VERIFY_NONE, VERIFY_NULLDUMMY, VERIFY_P2SH, VERIFY_TAPROOT, VERIFY_WITNESS,
+    validate_flags, VERIFY_ALL, VERIFY_ALL_PRE_TAPROOT, VERIFY_CHECKLOCKTIMEVERIFY,
+    VERIFY_CHECKSEQUENCEVERIFY, VERIFY_DERSIG, VERIFY_NONE, VERIFY_NULLDUMMY, VERIFY_P2SH,
+    VERIFY_TAPROOT, VERIFY_WITNESS,
 };
 
 pub mod prelude {
     pub use crate::core::{
-        BlockHashExt, BlockHeaderExt, BlockSpentOutputsExt, CoinExt, ScriptPubkeyExt,
+        BlockHashExt, BlockHeaderExt, BlockSpentOutputsExt, CoinExt, CoinProvider, ScriptPubkeyExt,
         TransactionExt, TransactionSpentOutputsExt, TxInExt, TxOutExt, TxOutPointExt, TxidExt,
     };
     pub use crate::notifications::BlockValidationStateExt;
@@ -37,6 +37,17 @@ unsafe extern "C" fn destroy_log_callback<T>(user_data: *mut c_void) {
 /// The logger object logs kernel log messages into a user-defined log function.
 /// Messages logged by the kernel before this object is created are buffered in
 /// a 1MB buffer. The kernel library internally uses a global logging instance.
+///
+/// # Scope
+/// A [`Logger`] is a guard for its callback. Several loggers can be installed
+/// at once, each receiving every message, and dropping one unregisters only
+/// its own callback and drops its [`Log`] value. Dropping the last logger
+/// switches the kernel back to buffering, the state before any logger was
+/// created, so tests can install and tear down loggers independently.
+///
+/// Categories, levels and [`LoggingOptions`] are global and are not restored
+/// on drop, since the kernel offers no way to read their previous values.
+/// Tests that change them should set them back explicitly.
 pub struct Logger {
     inner: *mut btck_LoggingConnection,
 }
@@ -427,6 +438,40 @@ mod tests {
         assert!(_logger.is_ok());
     }
 
+    #[test]
+    fn test_logger_drop_releases_callback() {
+        let messages_1 = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let messages_2 = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let logger_1 = Logger::new(TestLog {
+            messages: messages_1.clone(),
+        })
+        .unwrap();
+        let logger_2 = Logger::new(TestLog {
+            messages: messages_2.clone(),
+        })
+        .unwrap();
+        assert_eq!(std::sync::Arc::strong_count(&messages_1), 2);
+        assert_eq!(std::sync::Arc::strong_count(&messages_2), 2);
+
+        // Dropping one logger leaves the other installed.
+        drop(logger_1);
+        assert_eq!(std::sync::Arc::strong_count(&messages_1), 1);
+        assert_eq!(std::sync::Arc::strong_count(&messages_2), 2);
+
+        drop(logger_2);
+        assert_eq!(std::sync::Arc::strong_count(&messages_2), 1);
+
+        // A logger can be installed again after all were dropped.
+        let logger_3 = Logger::new(TestLog {
+            messages: messages_1.clone(),
+        })
+        .unwrap();
+        assert_eq!(std::sync::Arc::strong_count(&messages_1), 2);
+        drop(logger_3);
+        assert_eq!(std::sync::Arc::strong_count(&messages_1), 1);
+    }
+
     #[test]
     fn test_logger_set_level_category() {
         let messages = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
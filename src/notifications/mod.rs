@@ -3,13 +3,14 @@ pub mod types;
 pub mod validation;
 
 pub use types::{
-    BlockValidationResult, BlockValidationStateExt, BlockValidationStateRef, SynchronizationState,
-    ValidationMode, Warning,
+    BlockValidationResult, BlockValidationStateExt, BlockValidationStateRef, FlushErrorKind,
+    SynchronizationState, ValidationMode, Warning,
 };
 
 pub use notification::{
     BlockTipCallback, FatalErrorCallback, FlushErrorCallback, HeaderTipCallback,
-    NotificationCallbackRegistry, ProgressCallback, WarningSetCallback, WarningUnsetCallback,
+    KernelNotificationHandler, NotificationCallbackRegistry, ProgressCallback, WarningSetCallback,
+    WarningUnsetCallback,
 };
 
 pub use validation::{BlockCheckedCallback, ValidationCallbackRegistry};
@@ -1,4 +1,8 @@
-use std::ffi::{c_char, c_void};
+use std::{
+    ffi::{c_char, c_void},
+    sync::{Arc, Condvar, Mutex, OnceLock},
+    time::Duration,
+};
 
 use libbitcoinkernel_sys::{
     btck_BlockTreeEntry, btck_SynchronizationState, btck_Warning,
@@ -11,7 +15,7 @@ use crate::{
     BlockHash,
 };
 
-use super::{SynchronizationState, Warning};
+use super::{FlushErrorKind, SynchronizationState, Warning};
 
 /// The chain's tip was updated to the provided block hash.
 pub trait BlockTipCallback: Send + Sync {
@@ -51,7 +55,7 @@ pub trait WarningUnsetCallback: Send + Sync {
 
 /// An error was encountered when flushing data to disk.
 pub trait FlushErrorCallback: Send + Sync {
-    fn on_flush_error(&self, message: String);
+    fn on_flush_error(&self, kind: FlushErrorKind, message: String);
 }
 
 /// An un-recoverable system error was encountered by the library.
@@ -112,10 +116,10 @@ where
 
 impl<F> FlushErrorCallback for F
 where
-    F: Fn(String) + Send + Sync + 'static,
+    F: Fn(FlushErrorKind, String) + Send + Sync + 'static,
 {
-    fn on_flush_error(&self, message: String) {
-        self(message)
+    fn on_flush_error(&self, kind: FlushErrorKind, message: String) {
+        self(kind, message)
     }
 }
 
@@ -128,6 +132,101 @@ where
     }
 }
 
+/// A single handler for all kernel notifications.
+///
+/// Every method has an empty default body, so implementors only override the
+/// notifications they care about. Register it with
+/// [`NotificationCallbackRegistry::register_handler`] or
+/// [`ContextBuilder::with_notifications`](crate::ContextBuilder::with_notifications).
+///
+/// # Example
+/// ```no_run
+/// use bitcoinkernel::{
+///     BlockHash, ContextBuilder, KernelError, KernelNotificationHandler, SynchronizationState,
+/// };
+///
+/// struct TipLogger;
+///
+/// impl KernelNotificationHandler for TipLogger {
+///     fn on_block_tip(&self, _state: SynchronizationState, hash: BlockHash, _progress: f64) {
+///         println!("New tip: {}", hash);
+///     }
+///
+///     fn on_fatal_error(&self, message: String) {
+///         eprintln!("Fatal: {}", message);
+///     }
+/// }
+///
+/// let context = ContextBuilder::new().with_notifications(TipLogger).build()?;
+/// # Ok::<(), KernelError>(())
+/// ```
+pub trait KernelNotificationHandler: Send + Sync {
+    /// See [`BlockTipCallback`].
+    fn on_block_tip(&self, _state: SynchronizationState, _hash: BlockHash, _progress: f64) {}
+
+    /// See [`HeaderTipCallback`].
+    fn on_header_tip(
+        &self,
+        _state: SynchronizationState,
+        _height: i64,
+        _timestamp: i64,
+        _presync: bool,
+    ) {
+    }
+
+    /// See [`ProgressCallback`].
+    fn on_progress(&self, _title: String, _percent: i32, _resume_possible: bool) {}
+
+    /// See [`WarningSetCallback`].
+    fn on_warning_set(&self, _warning: Warning, _message: String) {}
+
+    /// See [`WarningUnsetCallback`].
+    fn on_warning_unset(&self, _warning: Warning) {}
+
+    /// See [`FlushErrorCallback`].
+    fn on_flush_error(&self, _kind: FlushErrorKind, _message: String) {}
+
+    /// See [`FatalErrorCallback`].
+    fn on_fatal_error(&self, _message: String) {}
+}
+
+/// The first flush error reported for a context, shared with every
+/// [`ChainstateManager`](crate::ChainstateManager) created from it.
+pub(crate) type FlushFailure = Arc<OnceLock<(FlushErrorKind, String)>>;
+
+/// Counts the block tip notifications of a context, so that a
+/// [`ChainstateManager`](crate::ChainstateManager) created from it can wait
+/// for its tip to change.
+#[derive(Default)]
+pub(crate) struct TipSignal {
+    updates: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl TipSignal {
+    fn notify(&self) {
+        let mut updates = self.updates.lock().unwrap_or_else(|err| err.into_inner());
+        *updates += 1;
+        self.changed.notify_all();
+    }
+
+    /// Returns the number of tip updates so far.
+    pub(crate) fn updates(&self) -> u64 {
+        *self.updates.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Waits until the number of tip updates exceeds `seen`, returning
+    /// `false` if `timeout` elapses first.
+    pub(crate) fn wait_past(&self, seen: u64, timeout: Duration) -> bool {
+        let updates = self.updates.lock().unwrap_or_else(|err| err.into_inner());
+        let (updates, _) = self
+            .changed
+            .wait_timeout_while(updates, timeout, |updates| *updates <= seen)
+            .unwrap_or_else(|err| err.into_inner());
+        *updates > seen
+    }
+}
+
 /// Registry for managing notification interface callback handlers.
 #[derive(Default)]
 pub struct NotificationCallbackRegistry {
@@ -138,6 +237,8 @@ pub struct NotificationCallbackRegistry {
     warning_unset_handler: Option<Box<dyn WarningUnsetCallback>>,
     flush_error_handler: Option<Box<dyn FlushErrorCallback>>,
     fatal_error_handler: Option<Box<dyn FatalErrorCallback>>,
+    flush_failure: FlushFailure,
+    tip_signal: Arc<TipSignal>,
 }
 
 impl NotificationCallbackRegistry {
@@ -200,6 +301,52 @@ impl NotificationCallbackRegistry {
         self.fatal_error_handler = Some(Box::new(handler) as Box<dyn FatalErrorCallback>);
         self
     }
+
+    /// Registers every method of `handler` as the corresponding callback,
+    /// replacing any previously registered callbacks.
+    pub fn register_handler<T>(&mut self, handler: T) -> &mut Self
+    where
+        T: KernelNotificationHandler + 'static,
+    {
+        let handler = Arc::new(handler);
+
+        let h = Arc::clone(&handler);
+        self.register_block_tip(
+            move |state: SynchronizationState, hash: BlockHash, progress: f64| {
+                h.on_block_tip(state, hash, progress)
+            },
+        );
+        let h = Arc::clone(&handler);
+        self.register_header_tip(
+            move |state: SynchronizationState, height: i64, timestamp: i64, presync: bool| {
+                h.on_header_tip(state, height, timestamp, presync)
+            },
+        );
+        let h = Arc::clone(&handler);
+        self.register_progress(move |title: String, percent: i32, resume_possible: bool| {
+            h.on_progress(title, percent, resume_possible)
+        });
+        let h = Arc::clone(&handler);
+        self.register_warning_set(move |warning: Warning, message: String| {
+            h.on_warning_set(warning, message)
+        });
+        let h = Arc::clone(&handler);
+        self.register_warning_unset(move |warning: Warning| h.on_warning_unset(warning));
+        let h = Arc::clone(&handler);
+        self.register_flush_error(move |kind: FlushErrorKind, message: String| {
+            h.on_flush_error(kind, message)
+        });
+        self.register_fatal_error(move |message: String| handler.on_fatal_error(message));
+        self
+    }
+
+    pub(crate) fn flush_failure(&self) -> FlushFailure {
+        Arc::clone(&self.flush_failure)
+    }
+
+    pub(crate) fn tip_signal(&self) -> Arc<TipSignal> {
+        Arc::clone(&self.tip_signal)
+    }
 }
 
 pub(crate) unsafe extern "C" fn notification_user_data_destroy_wrapper(user_data: *mut c_void) {
@@ -215,6 +362,7 @@ pub(crate) unsafe extern "C" fn notification_block_tip_wrapper(
     verification_progress: f64,
 ) {
     let registry = &*(user_data as *mut NotificationCallbackRegistry);
+    registry.tip_signal.notify();
 
     if let Some(ref handler) = registry.block_tip_handler {
         let hash_ptr = btck_block_tree_entry_get_block_hash(entry);
@@ -283,8 +431,11 @@ pub(crate) unsafe extern "C" fn notification_flush_error_wrapper(
     message_len: usize,
 ) {
     let registry = &*(user_data as *mut NotificationCallbackRegistry);
+    let message = c_helpers::to_string(message, message_len);
+    let kind = FlushErrorKind::from_message(&message);
+    let _ = registry.flush_failure.set((kind, message.clone()));
     if let Some(ref handler) = registry.flush_error_handler {
-        handler.on_flush_error(c_helpers::to_string(message, message_len));
+        handler.on_flush_error(kind, message);
     }
 }
 
@@ -305,6 +456,23 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_tip_signal() {
+        let signal = Arc::new(TipSignal::default());
+        assert_eq!(signal.updates(), 0);
+        assert!(!signal.wait_past(0, Duration::from_millis(1)));
+
+        signal.notify();
+        assert_eq!(signal.updates(), 1);
+        assert!(signal.wait_past(0, Duration::ZERO));
+        assert!(!signal.wait_past(1, Duration::from_millis(1)));
+
+        let notifier = Arc::clone(&signal);
+        let handle = std::thread::spawn(move || notifier.notify());
+        assert!(signal.wait_past(1, Duration::from_secs(60)));
+        handle.join().unwrap();
+    }
+
     #[test]
     fn test_registry_default() {
         let registry = NotificationCallbackRegistry::default();
@@ -341,7 +509,7 @@ mod tests {
             let _ = warning;
         });
 
-        registry.register_flush_error(|message: String| {
+        registry.register_flush_error(|_kind: FlushErrorKind, message: String| {
             assert!(message.contains("test"));
         });
 
@@ -375,13 +543,76 @@ mod tests {
         let warning_unset_handler = |_warning| {};
         let _: Box<dyn WarningUnsetCallback> = Box::new(warning_unset_handler);
 
-        let flush_error_handler = |_message| {};
+        let flush_error_handler = |_kind, _message| {};
         let _: Box<dyn FlushErrorCallback> = Box::new(flush_error_handler);
 
         let fatal_error_handler = |_message| {};
         let _: Box<dyn FatalErrorCallback> = Box::new(fatal_error_handler);
     }
 
+    #[test]
+    fn test_register_handler() {
+        struct Recorder {
+            events: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl KernelNotificationHandler for Recorder {
+            fn on_progress(&self, title: String, percent: i32, _resume_possible: bool) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}: {}", title, percent));
+            }
+
+            fn on_flush_error(&self, kind: FlushErrorKind, message: String) {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}: {}", kind, message));
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = NotificationCallbackRegistry::new();
+        registry.register_handler(Recorder {
+            events: Arc::clone(&events),
+        });
+
+        assert!(registry.block_tip_handler.is_some());
+        assert!(registry.header_tip_handler.is_some());
+        assert!(registry.progress_handler.is_some());
+        assert!(registry.warning_set_handler.is_some());
+        assert!(registry.warning_unset_handler.is_some());
+        assert!(registry.flush_error_handler.is_some());
+        assert!(registry.fatal_error_handler.is_some());
+
+        // Methods left at their default are no-ops.
+        if let Some(ref handler) = registry.block_tip_handler {
+            handler.on_block_tip(
+                SynchronizationState::PostInit,
+                BlockHash::from([1u8; 32]),
+                1.0,
+            );
+        }
+        if let Some(ref handler) = registry.fatal_error_handler {
+            handler.on_fatal_error("fatal".to_string());
+        }
+        if let Some(ref handler) = registry.progress_handler {
+            handler.on_progress("Loading".to_string(), 50, false);
+        }
+        if let Some(ref handler) = registry.flush_error_handler {
+            handler.on_flush_error(FlushErrorKind::UndoFile, "disk full".to_string());
+        }
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "Loading: 50".to_string(),
+                format!("{}: disk full", FlushErrorKind::UndoFile)
+            ]
+        );
+    }
+
     #[test]
     fn test_block_tip_callback_invocation() {
         let called = Arc::new(Mutex::new(false));
@@ -484,18 +715,39 @@ mod tests {
         let called_clone = called.clone();
 
         let mut registry = NotificationCallbackRegistry::new();
-        registry.register_flush_error(move |message| {
+        registry.register_flush_error(move |kind, message| {
             *called_clone.lock().unwrap() = true;
+            assert_eq!(kind, FlushErrorKind::Other);
             assert_eq!(message, "Disk error");
         });
 
         if let Some(ref handler) = registry.flush_error_handler {
-            handler.on_flush_error("Disk error".to_string());
+            handler.on_flush_error(FlushErrorKind::Other, "Disk error".to_string());
         }
 
         assert!(*called.lock().unwrap());
     }
 
+    #[test]
+    fn test_flush_error_wrapper_records_failure() {
+        let registry = Box::into_raw(Box::new(NotificationCallbackRegistry::new()));
+        let flush_failure = unsafe { (*registry).flush_failure() };
+        let message = "Flushing undo file to disk failed.";
+
+        unsafe {
+            notification_flush_error_wrapper(
+                registry as *mut c_void,
+                message.as_ptr() as *const c_char,
+                message.len(),
+            );
+            notification_user_data_destroy_wrapper(registry as *mut c_void);
+        }
+
+        let (kind, recorded) = flush_failure.get().unwrap();
+        assert_eq!(*kind, FlushErrorKind::UndoFile);
+        assert_eq!(recorded, message);
+    }
+
     #[test]
     fn test_fatal_error_callback_invocation() {
         let called = Arc::new(Mutex::new(false));
@@ -104,6 +104,54 @@ impl std::fmt::Display for Warning {
     }
 }
 
+/// The kind of data the kernel failed to flush to disk.
+///
+/// Reported alongside the message of a flush error notification. The kernel
+/// raises flush errors when syncing block files and undo files; failures to
+/// write the block index or coins database are reported as fatal errors
+/// instead.
+///
+/// The C API passes only a message, so the kind is a best-effort
+/// classification of its text. A message the kernel rewords in a later
+/// version is reported as [`Other`](FlushErrorKind::Other).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlushErrorKind {
+    /// Flushing a block file (`blk*.dat`) failed.
+    BlockFile,
+
+    /// Flushing an undo file (`rev*.dat`) failed.
+    UndoFile,
+
+    /// The failure could not be attributed to a specific kind of file.
+    Other,
+}
+
+impl FlushErrorKind {
+    /// Classifies a flush error from the message reported by the kernel,
+    /// matching the wording of `BlockManager::FlushUndoFile` and
+    /// `BlockManager::FlushBlockFile`.
+    pub(crate) fn from_message(message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        if message.contains("undo file") {
+            FlushErrorKind::UndoFile
+        } else if message.contains("block file") {
+            FlushErrorKind::BlockFile
+        } else {
+            FlushErrorKind::Other
+        }
+    }
+}
+
+impl std::fmt::Display for FlushErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlushErrorKind::BlockFile => write!(f, "Block file"),
+            FlushErrorKind::UndoFile => write!(f, "Undo file"),
+            FlushErrorKind::Other => write!(f, "Other"),
+        }
+    }
+}
+
 /// Result of data structure validation.
 ///
 /// Indicates whether a validated data structure (block, transaction, etc.)
@@ -423,6 +471,46 @@ mod tests {
         assert_eq!(warning, cloned);
     }
 
+    // FlushErrorKind tests
+    const BLOCK_FILE_FLUSH_ERROR: &str =
+        "Flushing block file to disk failed. This is likely the result of an I/O error.";
+    const UNDO_FILE_FLUSH_ERROR: &str =
+        "Flushing undo file to disk failed. This is likely the result of an I/O error.";
+
+    #[test]
+    fn test_flush_error_kind_from_message() {
+        assert_eq!(
+            FlushErrorKind::from_message(BLOCK_FILE_FLUSH_ERROR),
+            FlushErrorKind::BlockFile
+        );
+        assert_eq!(
+            FlushErrorKind::from_message(UNDO_FILE_FLUSH_ERROR),
+            FlushErrorKind::UndoFile
+        );
+        assert_eq!(
+            FlushErrorKind::from_message("Disk error"),
+            FlushErrorKind::Other
+        );
+    }
+
+    /// Fails if the vendored kernel rewords or adds a flush error, so that
+    /// [`FlushErrorKind::from_message`] is updated along with it.
+    #[test]
+    fn test_flush_error_messages_match_kernel_source() {
+        let source =
+            std::fs::read_to_string("libbitcoinkernel-sys/bitcoin/src/node/blockstorage.cpp")
+                .unwrap();
+        let messages: Vec<&str> = source
+            .split("notifications.flushError(_(\"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect();
+        assert_eq!(
+            messages,
+            vec![UNDO_FILE_FLUSH_ERROR, BLOCK_FILE_FLUSH_ERROR]
+        );
+    }
+
     // ValidationMode tests
     #[test]
     fn test_validation_mode_conversions() {
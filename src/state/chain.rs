@@ -37,6 +37,11 @@ use super::ChainstateManager;
 /// - [`Chain::tip()`] - O(1) access to chain tip (instead of `.last()`)
 /// - [`Chain::at_height()`] - O(1) access to specific height (instead of `.nth()`)
 ///
+/// # Snapshot
+/// The tip height is read once when the iterator is created, so iterating N
+/// blocks does not look up the tip N times. Blocks connected after creation
+/// are not yielded; create a new iterator to pick them up.
+///
 /// # Lifetime
 /// The iterator is tied to the lifetime of the [`Chain`] it was created from,
 /// which in turn is tied to the [`ChainstateManager`]. The iterator becomes
@@ -58,6 +63,7 @@ use super::ChainstateManager;
 pub struct ChainIterator<'a> {
     chain: Chain<'a>,
     current_height: usize,
+    end_height: usize,
 }
 
 impl<'a> ChainIterator<'a> {
@@ -65,6 +71,7 @@ impl<'a> ChainIterator<'a> {
         Self {
             chain,
             current_height: 0,
+            end_height: usize::try_from(chain.height() + 1).unwrap_or(0),
         }
     }
 }
@@ -74,12 +81,22 @@ impl<'a> Iterator for ChainIterator<'a> {
 
     /// Returns the next block in the chain.
     ///
-    /// Yields blocks sequentially from genesis (height 0) to the current tip.
-    /// Returns `None` when all blocks have been iterated.
+    /// Yields blocks sequentially from genesis (height 0) to the tip at the
+    /// time the iterator was created. Returns `None` when all blocks have been
+    /// iterated.
     fn next(&mut self) -> Option<Self::Item> {
+        if self.current_height >= self.end_height {
+            return None;
+        }
         let height = self.current_height;
         self.current_height += 1;
-        self.chain.at_height(height)
+
+        let ptr = unsafe { btck_chain_get_by_height(self.chain.inner, height as i32) };
+        if ptr.is_null() {
+            self.current_height = self.end_height;
+            return None;
+        }
+        Some(unsafe { BlockTreeEntry::from_ptr(ptr) })
     }
 }
 
@@ -93,8 +110,21 @@ impl<'a> Iterator for ChainIterator<'a> {
 /// The [`Chain`] is tied to the lifetime of the [`ChainstateManager`] that created
 /// it. It becomes invalid when the manager is dropped.
 ///
+/// # Live View
+/// A [`Chain`] is a view on the manager's active chain, not a snapshot. Blocks
+/// connected by [`ChainstateManager::process_block`] after the view was obtained
+/// are visible through it without refreshing, and a reorganization changes the
+/// entries it returns. Values read from it are only consistent until the manager
+/// processes more data, so a reader that needs a stable range should copy the
+/// entries or hashes it needs first.
+///
+/// To read while another thread processes blocks, share the manager as
+/// described under [Thread Safety](ChainstateManager#thread-safety).
+///
 /// # Thread Safety
-/// [`Chain`] is `Copy` and can be safely shared across threads.
+/// [`Chain`] is `Copy`, `Send` and `Sync`. Every query takes the kernel's
+/// chainstate lock, so copies can be read from several threads while the
+/// manager processes blocks.
 ///
 /// # Examples
 /// ```no_run
@@ -127,6 +157,9 @@ pub struct Chain<'a> {
     marker: PhantomData<&'a ChainstateManager>,
 }
 
+unsafe impl Send for Chain<'_> {}
+unsafe impl Sync for Chain<'_> {}
+
 impl<'a> Chain<'a> {
     /// Returns the tip (highest block) of the chain.
     ///
@@ -198,6 +231,88 @@ impl<'a> Chain<'a> {
         Some(unsafe { BlockTreeEntry::from_ptr(ptr) })
     }
 
+    /// Returns up to `n` consecutive block tree entries starting at `start_height`.
+    ///
+    /// The window is truncated at the chain tip, so fewer than `n` entries are
+    /// returned if the chain ends first and an empty vector is returned if
+    /// `start_height` is above the tip. Each lookup is O(1), so this is
+    /// equivalent to `chain.iter().skip(start_height).take(n)` without walking
+    /// the skipped heights.
+    ///
+    /// # Arguments
+    /// * `start_height` - The height of the first entry in the window
+    /// * `n` - The maximum number of entries to return
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ChainstateManager, ChainType, ContextBuilder, KernelError};
+    ///
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// let chain = chainman.active_chain();
+    ///
+    /// // Blocks 100 through 109
+    /// for entry in chain.window(100, 10) {
+    ///     println!("Block {} at height {}", entry.block_hash(), entry.height());
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn window(&self, start_height: usize, n: usize) -> Vec<BlockTreeEntry<'a>> {
+        (start_height..start_height.saturating_add(n))
+            .map_while(|height| self.at_height(height))
+            .collect()
+    }
+
+    /// Splits the heights from genesis to tip into `n` contiguous ranges of
+    /// nearly equal length.
+    ///
+    /// Each range is a half-open `(start, end)` pair of heights, so together
+    /// they cover `0..=tip` without overlapping. Range lengths differ by at
+    /// most one, with the longer ranges first. Fewer than `n` ranges are
+    /// returned if the chain has fewer than `n` blocks, and none if `n` is
+    /// zero. No entries are looked up, so each range can be handed to a
+    /// worker that reads it with [`at_height`](Self::at_height) or
+    /// [`window`](Self::window).
+    ///
+    /// The heights are taken from the tip at the time of the call; blocks
+    /// connected afterwards are not covered.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ChainstateManager, ChainType, ContextBuilder, KernelError};
+    ///
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// let chain = chainman.active_chain();
+    ///
+    /// std::thread::scope(|scope| {
+    ///     for (start, end) in chain.ranges(4) {
+    ///         scope.spawn(move || {
+    ///             for entry in chain.window(start, end - start) {
+    ///                 println!("Block {} at height {}", entry.block_hash(), entry.height());
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn ranges(&self, n: usize) -> Vec<(usize, usize)> {
+        let count = usize::try_from(self.height()).map_or(0, |height| height + 1);
+        if n == 0 {
+            return Vec::new();
+        }
+        let (length, remainder) = (count / n, count % n);
+        let mut start = 0;
+        (0..n.min(count))
+            .map(|index| {
+                let end = start + length + usize::from(index < remainder);
+                let range = (start, end);
+                start = end;
+                range
+            })
+            .collect()
+    }
+
     /// Checks if the given block entry is part of the chain.
     ///
     /// Determines whether a block is in the chain.
@@ -236,7 +351,8 @@ impl<'a> Chain<'a> {
     ///
     /// Creates a [`ChainIterator`] that yields [`BlockTreeEntry`] items
     /// for each block in the chain, starting from the genesis block (height 0) and
-    /// continuing sequentially to the current tip.
+    /// continuing sequentially to the current tip. The tip is the one at the
+    /// time of this call; blocks connected while iterating are not yielded.
     ///
     /// # Performance
     /// **Warning:** Avoid calling `.last()` on this iterator, as it requires O(N)
@@ -248,7 +364,9 @@ impl<'a> Chain<'a> {
     ///
     /// # Example
     /// ```no_run
-    /// use bitcoinkernel::{ChainstateManager, ChainType, ContextBuilder, KernelError};
+    /// use bitcoinkernel::{
+    ///     prelude::*, ChainstateManager, ChainType, ContextBuilder, KernelError,
+    /// };
     ///
     /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
     /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
@@ -261,8 +379,8 @@ impl<'a> Chain<'a> {
     ///              entry.height());
     /// }
     ///
-    /// // Or with enumerate for explicit height tracking
-    /// for (height, entry) in chain.iter().enumerate() {
+    /// // Or paired with each entry's height
+    /// for (height, entry) in chain.iter_with_height() {
     ///     println!("Height {}: {}", height, entry.block_hash());
     /// }
     ///
@@ -270,12 +388,42 @@ impl<'a> Chain<'a> {
     /// let recent_blocks: Vec<_> = chain.iter()
     ///     .take(10)
     ///     .collect();
+    ///
+    /// // Walk towards the tip while a predicate holds
+    /// # let cutoff = 0;
+    /// let early_blocks: Vec<_> = chain.iter()
+    ///     .take_while(|entry| entry.header().timestamp() < cutoff)
+    ///     .collect();
     /// # Ok::<(), KernelError>(())
     /// ```
     pub fn iter(&self) -> ChainIterator<'a> {
         ChainIterator::new(*self)
     }
 
+    /// Returns an iterator over all block tree entries from genesis to tip,
+    /// each paired with its height.
+    ///
+    /// The height is read from the entry itself rather than derived from the
+    /// iteration position, so it stays correct when the iterator is combined
+    /// with adapters such as `skip` or `filter`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ChainstateManager, ChainType, ContextBuilder, KernelError};
+    ///
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// let chain = chainman.active_chain();
+    ///
+    /// for (height, entry) in chain.iter_with_height().skip(1) {
+    ///     println!("Height {}: {}", height, entry.block_hash());
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn iter_with_height(&self) -> impl Iterator<Item = (i32, BlockTreeEntry<'a>)> + 'a {
+        self.iter().map(|entry| (entry.height(), entry))
+    }
+
     /// Returns the height of the chain tip.
     ///
     /// The height is the zero-based index of the tip block in the chain,
@@ -25,7 +25,19 @@
 //! #     Ok(())
 //! # }
 
-use std::ffi::CString;
+use std::{
+    collections::HashMap,
+    ffi::{CString, OsStr},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, sync_channel, Receiver},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
 
 use libbitcoinkernel_sys::{
     btck_BlockHash, btck_ChainstateManager, btck_ChainstateManagerOptions, btck_block_read,
@@ -41,16 +53,27 @@ use libbitcoinkernel_sys::{
 };
 
 use crate::{
-    core::block::BlockHeader,
+    core::{
+        block::{BlockHeader, BLOCK_CHECK_ALL},
+        BlockFileReader, BlockHashExt, BlockHeaderExt,
+    },
     ffi::{
         c_helpers,
         sealed::{AsPtr, FromMutPtr, FromPtr},
     },
-    notifications::types::{BlockValidationState, BlockValidationStateExt},
-    Block, BlockHash, BlockSpentOutputs, BlockTreeEntry, KernelError, ValidationMode,
+    notifications::{
+        notification::{FlushFailure, TipSignal},
+        types::{BlockValidationState, BlockValidationStateExt},
+    },
+    Block, BlockCheckResult, BlockHash, BlockSpentOutputs, BlockTreeEntry, KernelError,
+    ValidationMode,
 };
 
-use super::{Chain, Context};
+use super::{context::check_flush_failure, Chain, ChainParams, ChainType, Context};
+
+/// Number of blocks [`ChainstateManager::spawn_block_stream`] and
+/// [`ChainstateManager::par_read_blocks`] read ahead of their consumer.
+pub const BLOCK_STREAM_CAPACITY: usize = 16;
 
 /// Result of processing a block with the [`ChainstateManager`].
 ///
@@ -97,6 +120,15 @@ impl ProcessBlockResult {
     pub fn is_rejected(&self) -> bool {
         matches!(self, Self::Rejected)
     }
+
+    /// Returns true if the block was accepted, whether it was new or already
+    /// known.
+    ///
+    /// Resubmitting a block is idempotent, so code that may process the same
+    /// block more than once can treat any accepted result as success.
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::NewBlock | Self::Duplicate)
+    }
 }
 
 /// The chainstate manager handles block validation and chain state.
@@ -106,18 +138,73 @@ impl ProcessBlockResult {
 /// disk, and query chain state.
 ///
 /// # Lifetime
-/// The chainstate manager holds a reference to the [`Context`] used to create it.
-/// It is recommended to keep the context in scope for the lifetime of the
-/// chainstate manager.
+/// The chainstate manager shares ownership of the kernel state behind the
+/// [`Context`] used to create it, so the context may be dropped while the
+/// manager is still in use. The kernel keeps that state, including the
+/// registered notification and validation callbacks, alive until the last
+/// manager created from it is dropped. Calling [`Context::interrupt`] still
+/// requires the context itself.
 ///
 /// # Thread Safety
 /// The chainstate manager is `Send` and `Sync`, allowing it to be shared
-/// across threads safely.
+/// across threads safely. Every method takes `&self`, and the kernel
+/// synchronizes access internally, so no separate read-only handle is needed:
+/// wrap the manager in an `Arc`, or borrow it from scoped threads, and call
+/// [`process_block`](Self::process_block) from a writer while request handlers
+/// call [`read_block_data`](Self::read_block_data),
+/// [`get_block_tree_entry`](Self::get_block_tree_entry) or
+/// [`active_chain`](Self::active_chain). The [`Chain`](crate::Chain) and
+/// [`BlockTreeEntry`](crate::BlockTreeEntry) values it returns are `Send` and
+/// `Sync` as well, borrowing from the manager.
+///
+/// Only one chainstate manager can be opened for a given data directory at a
+/// time. The kernel holds exclusive locks on its databases, so there is no way
+/// to observe a data directory that another manager is writing to. Share the
+/// single manager instead.
+///
+/// # Flushing
+/// The kernel writes chainstate to disk on its own schedule while blocks are
+/// processed, and forces a full flush of the block index and coins database
+/// when the chainstate manager is dropped. The C API does not expose an
+/// explicit flush, so dropping the manager is the way to reach a checkpoint
+/// after which reopening the data directory reflects every processed block
+/// without replay. Failures while flushing are reported through
+/// [`FlushErrorCallback`](crate::notifications::notification::FlushErrorCallback).
+///
+/// # Flush Errors
+/// A flush error, for example from a full disk, leaves the data on disk in an
+/// uncertain state. Once the kernel has reported one, [`import_blocks`](Self::import_blocks),
+/// [`read_block_data`](Self::read_block_data) and [`read_spent_outputs`](Self::read_spent_outputs)
+/// return [`KernelError::DatabaseError`] instead of touching the data directory.
+/// Recover by dropping the manager and its [`Context`], freeing disk space,
+/// and reopening the data directory.
+///
+/// # Reorganizations
+/// The C API has no equivalent of Bitcoin Core's `invalidateblock` and
+/// `reconsiderblock`, so a block cannot be invalidated or reconsidered by
+/// hash. The active chain only reorganizes when a branch with more work is
+/// processed with [`process_block`](Self::process_block). To exercise reorg
+/// handling, process a competing branch that outgrows the current tip and
+/// observe the disconnected blocks with
+/// [`register_block_disconnected`](crate::notifications::ValidationCallbackRegistry::register_block_disconnected).
+///
+/// # Bootstrapping
+/// The C API does not expose Bitcoin Core's assumeutxo snapshot loading, so a
+/// manager cannot be bootstrapped from a UTXO snapshot. A new manager builds
+/// its chainstate by validating every block, either processed with
+/// [`process_block`](Self::process_block) or imported from existing block
+/// files with [`import_blocks`](Self::import_blocks).
 ///
 /// # Examples
 /// See module-level documentation for usage examples.
 pub struct ChainstateManager {
     inner: *mut btck_ChainstateManager,
+    flush_failure: FlushFailure,
+    tip_signal: Arc<TipSignal>,
+    chain_type: ChainType,
+    chain_params: ChainParams,
+    blocks_dir: PathBuf,
+    thread_name_prefix: String,
 }
 
 unsafe impl Send for ChainstateManager {}
@@ -130,8 +217,8 @@ impl ChainstateManager {
     /// interface for configuring all available options before construction.
     ///
     /// # Arguments
-    /// * `context` - The [`Context`] that this chainstate manager will use. It is recommended
-    ///   to keep the context in scope for the lifetime of the chainstate manager.
+    /// * `context` - The [`Context`] that this chainstate manager will use. The manager
+    ///   keeps the context's kernel state alive, so the context may be dropped first.
     /// * `data_dir` - The directory path where the chainstate manager will write its data files.
     ///   This directory will be created if it doesn't exist.
     /// * `blocks_dir` - The directory path where block files will be stored.
@@ -175,8 +262,8 @@ impl ChainstateManager {
     /// or database wiping), use [`builder`](Self::builder) instead.
     ///
     /// # Arguments
-    /// * `context` - The [`Context`] that this chainstate manager will use. It is recommended
-    ///   to keep the context in scope for the lifetime of the chainstate manager.
+    /// * `context` - The [`Context`] that this chainstate manager will use. The manager
+    ///   keeps the context's kernel state alive, so the context may be dropped first.
     /// * `data_dir` - The directory path where the chainstate manager will write its data files.
     ///   This directory will be created if it doesn't exist.
     /// * `blocks_dir` - The directory path where block files will be stored.
@@ -234,6 +321,10 @@ impl ChainstateManager {
     ///   interface callback. Use [`ContextBuilder::with_block_checked_validation`](crate::ContextBuilder::with_block_checked_validation)
     ///   or [`ContextBuilder::validation`](crate::ContextBuilder::validation) to receive detailed
     ///   validation state through the block checked callback.
+    /// - Processing a block that was already accepted is a no-op that returns
+    ///   [`ProcessBlockResult::Duplicate`] rather than an error, so blocks can be
+    ///   resubmitted safely, e.g. after reconnecting to a peer. Check
+    ///   [`ProcessBlockResult::is_accepted`] to treat both outcomes alike.
     ///
     /// # Validation Details
     /// The block checked callback receives a [`BlockValidationStateRef`](crate::BlockValidationStateRef)
@@ -241,6 +332,14 @@ impl ChainstateManager {
     /// To detect when a block extends the active chain, use [`ContextBuilder::with_block_connected_validation`](crate::ContextBuilder::with_block_connected_validation)
     /// or [`ContextBuilder::validation`](crate::ContextBuilder::validation).
     ///
+    /// # Locking
+    /// Each call takes the kernel's chainstate lock while the block is
+    /// validated and releases it before returning. The kernel API offers no
+    /// way to hold the lock across calls, so there is no batched variant:
+    /// importing many blocks acquires the lock once per block. Calls from
+    /// several threads are serialized on the lock, so feeding blocks from a
+    /// single thread in height order is just as fast and avoids contention.
+    ///
     /// # Example
     /// ```no_run
     /// # use bitcoinkernel::{Block, ChainstateManager, ProcessBlockResult};
@@ -268,6 +367,39 @@ impl ChainstateManager {
         }
     }
 
+    /// Checks a block's validity without connecting it to the chain.
+    ///
+    /// Runs the context-free `CheckBlock` validation (proof of work, merkle
+    /// root, size, weight, coinbase, transactions and sigops) against the
+    /// chain parameters of the manager's [`Context`]. Unlike
+    /// [`process_block`](Self::process_block), this neither writes the block
+    /// to disk nor touches the block tree or chainstate, so it is suited to
+    /// rejecting invalid blocks received from peers early.
+    ///
+    /// Passing this check does not mean the block is valid in the context of
+    /// the chain: contextual checks and script verification are not run.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::InvalidBlock`] carrying the
+    /// [`BlockValidationResult`](crate::BlockValidationResult) if the block
+    /// fails validation.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{Block, ChainstateManager, KernelError};
+    /// # let chainman: ChainstateManager = unimplemented!();
+    /// # let block: Block = unimplemented!();
+    /// if let Err(err) = chainman.check_block(&block) {
+    ///     println!("Dropping invalid block {}: {}", block.hash(), err);
+    /// }
+    /// ```
+    pub fn check_block(&self, block: &Block) -> Result<(), KernelError> {
+        match block.check(&self.chain_params, BLOCK_CHECK_ALL) {
+            BlockCheckResult::Valid => Ok(()),
+            BlockCheckResult::Invalid(state) => Err(KernelError::InvalidBlock(state.result())),
+        }
+    }
+
     /// Process and validate a block header.
     ///
     /// Attempts to validate the block header and add it to the block tree. This
@@ -337,8 +469,10 @@ impl ChainstateManager {
     /// blockchain reindex.
     ///
     /// # Errors
-    /// Returns [`KernelError::Internal`] if initialization fails.
+    /// Returns [`KernelError::DatabaseError`] if a flush error was reported,
+    /// or [`KernelError::Internal`] if initialization fails.
     pub fn import_blocks(&self) -> Result<(), KernelError> {
+        check_flush_failure(&self.flush_failure)?;
         let result = unsafe {
             btck_chainstate_manager_import_blocks(
                 self.inner,
@@ -355,6 +489,77 @@ impl ChainstateManager {
         }
     }
 
+    /// Imports the `blkNNNNN.dat` files in `path` into the chainstate.
+    ///
+    /// The files are read in file number order and every block in them is
+    /// processed with [`process_block`](Self::process_block). A node writes
+    /// blocks in the order it receives them, which is not chain order, so a
+    /// block whose parent is not yet known is held in memory until the parent
+    /// has been processed. This lets a blocks directory copied from another
+    /// node bootstrap this one. Files obfuscated with the key in `xor.dat`,
+    /// as written by Bitcoin Core 28.0 and later, are read with that key if
+    /// `path` contains it. Blocks that are already known are processed again
+    /// and come back as [`ProcessBlockResult::Duplicate`], leaving the
+    /// chainstate unchanged. Other files in `path` are ignored.
+    ///
+    /// Blocks whose parent never appears, including the genesis block, are
+    /// not processed.
+    ///
+    /// # Returns
+    /// The number of blocks by which the active chain's height increased.
+    /// Blocks stored on a side branch are not counted.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::DatabaseError`] if a flush error was reported,
+    /// or the errors of [`BlockFileReader`](crate::BlockFileReader) if `path`
+    /// or one of its block files cannot be read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{ChainstateManager, KernelError};
+    /// # fn example(chainman: &ChainstateManager) -> Result<(), KernelError> {
+    /// let imported = chainman.import_blocks_from_dir("/mnt/bootstrap/blocks")?;
+    /// println!("Imported {} blocks", imported);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn import_blocks_from_dir(&self, path: &str) -> Result<u64, KernelError> {
+        check_flush_failure(&self.flush_failure)?;
+        let mut file_numbers: Vec<u32> = fs::read_dir(path)
+            .map_err(|err| {
+                KernelError::Internal(format!("Failed to read block directory {}: {}", path, err))
+            })?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| block_file_number(&entry.file_name()))
+            .collect();
+        file_numbers.sort_unstable();
+
+        let height_before = self.active_chain().height();
+        let mut orphans: HashMap<[u8; 32], Vec<Block>> = HashMap::new();
+        for file_number in file_numbers {
+            for block in BlockFileReader::open(path, file_number, self.chain_type)? {
+                let block = block?;
+                let prev_hash = block.header().prev_hash().to_owned();
+                if self.get_block_tree_entry(&prev_hash).is_none() {
+                    orphans.entry(prev_hash.to_bytes()).or_default().push(block);
+                    continue;
+                }
+
+                let mut ready = vec![block];
+                while let Some(block) = ready.pop() {
+                    self.process_block(&block);
+                    if let Some(children) = orphans.remove(&block.hash().to_bytes()) {
+                        ready.extend(children);
+                    }
+                }
+            }
+        }
+        check_flush_failure(&self.flush_failure)?;
+
+        let imported = self.active_chain().height() - height_before;
+        Ok(u64::try_from(imported).unwrap_or(0))
+    }
+
     /// Read a block's full data from disk.
     ///
     /// # Arguments
@@ -364,11 +569,14 @@ impl ChainstateManager {
     /// The complete [`Block`] including all transactions.
     ///
     /// # Errors
+    /// Returns [`KernelError::DatabaseError`] if a flush error was reported.
+    ///
     /// Returns [`KernelError::Internal`] if:
     /// - The block file cannot be read
     /// - The block data is corrupted
     /// - The block has been pruned
     pub fn read_block_data(&self, entry: &BlockTreeEntry) -> Result<Block, KernelError> {
+        check_flush_failure(&self.flush_failure)?;
         let inner = unsafe { btck_block_read(self.inner, entry.as_ptr()) };
         if inner.is_null() {
             return Err(KernelError::Internal("Failed to read block.".to_string()));
@@ -391,6 +599,8 @@ impl ChainstateManager {
     /// outputs that were consumed by the block's transactions.
     ///
     /// # Errors
+    /// Returns [`KernelError::DatabaseError`] if a flush error was reported.
+    ///
     /// Returns [`KernelError::Internal`] if:
     /// - The undo data file cannot be read
     /// - The undo data is corrupted
@@ -399,6 +609,7 @@ impl ChainstateManager {
         &self,
         entry: &BlockTreeEntry,
     ) -> Result<BlockSpentOutputs, KernelError> {
+        check_flush_failure(&self.flush_failure)?;
         let inner = unsafe { btck_block_spent_outputs_read(self.inner, entry.as_ptr()) };
         if inner.is_null() {
             return Err(KernelError::Internal(
@@ -424,6 +635,152 @@ impl ChainstateManager {
         unsafe { Chain::from_ptr(ptr) }
     }
 
+    /// Returns the 80-byte serialized header of the active chain's tip.
+    ///
+    /// The header is taken from the in-memory block tree, so this does not
+    /// read the tip block from disk. This makes it a cheap health-check for
+    /// the current chain state.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Internal`] if the header cannot be serialized.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// let header = chainman.tip_header()?;
+    /// assert_eq!(header.len(), 80);
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn tip_header(&self) -> Result<[u8; 80], KernelError> {
+        self.active_chain().tip().raw_header()
+    }
+
+    /// Returns the height of the active chain's tip.
+    ///
+    /// This is a shortcut for `active_chain().height()`. The genesis block is
+    /// at height 0.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// chainman.import_blocks()?;
+    /// println!("Synced to height {}", chainman.height());
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn height(&self) -> i32 {
+        self.active_chain().height()
+    }
+
+    /// Returns the hash of the active chain's tip.
+    ///
+    /// This is a shortcut for `active_chain().tip().block_hash()`, returning
+    /// an owned [`BlockHash`] that outlives the manager.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// println!("Tip {} at height {}", chainman.tip_hash(), chainman.height());
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn tip_hash(&self) -> BlockHash {
+        self.active_chain().tip().block_hash().to_owned()
+    }
+
+    /// Blocks until the active chain's tip changes, like the
+    /// `waitfornewblock` RPC.
+    ///
+    /// Returns the new tip, or `None` if `timeout` elapses first. The wait is
+    /// woken by the kernel's block tip notification rather than by polling,
+    /// so it works whether or not a
+    /// [`BlockTipCallback`](crate::BlockTipCallback) was registered. Only tip
+    /// updates after the call are waited for. The notification belongs to
+    /// the [`Context`], so an update from another manager of the same context
+    /// also ends the wait.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// match chainman.wait_for_tip_change(Duration::from_secs(30)) {
+    ///     Some(tip) => println!("New tip at height {}", tip.height()),
+    ///     None => println!("No new block within 30 seconds"),
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn wait_for_tip_change(&self, timeout: Duration) -> Option<BlockTreeEntry<'_>> {
+        let seen = self.tip_signal.updates();
+        if !self.tip_signal.wait_past(seen, timeout) {
+            return None;
+        }
+        Some(self.active_chain().tip())
+    }
+
+    /// Returns the hash of the active chain's block at `height`, like the
+    /// `getblockhash` RPC.
+    ///
+    /// Returns `None` if `height` is negative or above the tip.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// if let Some(hash) = chainman.block_hash_at_height(100) {
+    ///     println!("Block 100: {}", hash);
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn block_hash_at_height(&self, height: i32) -> Option<BlockHash> {
+        let height = usize::try_from(height).ok()?;
+        self.active_chain()
+            .at_height(height)
+            .map(|entry| entry.block_hash().to_owned())
+    }
+
+    /// Returns a block locator for the active chain's tip.
+    ///
+    /// The locator lists block hashes walking back from the tip: the ten most
+    /// recent blocks one by one, then with a step that doubles after each
+    /// entry. The genesis block hash is always the last element. This is the
+    /// list a node sends in a `getheaders` message while syncing.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// # let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// let locator = chainman.block_locator();
+    /// assert!(!locator.is_empty());
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn block_locator(&self) -> Vec<BlockHash> {
+        let chain = self.active_chain();
+        let mut locator = Vec::new();
+        let mut height = chain.height() as usize;
+        let mut step = 1;
+        loop {
+            if let Some(entry) = chain.at_height(height) {
+                locator.push(entry.block_hash().to_owned());
+            }
+            if height == 0 {
+                break;
+            }
+            height = height.saturating_sub(step);
+            if locator.len() > 10 {
+                step *= 2;
+            }
+        }
+        locator
+    }
+
     /// Get the block tree entry with the most known cumulative proof of work.
     ///
     /// This is tracked internally by the ChainstateManager.
@@ -472,6 +829,306 @@ impl ChainstateManager {
             Some(unsafe { BlockTreeEntry::from_ptr(ptr) })
         }
     }
+
+    /// Streams blocks of the active chain from `start_height` to the tip on a
+    /// background thread.
+    ///
+    /// A reader thread, named `<prefix>-block-stream` after the context's
+    /// [`thread_name_prefix`](crate::ContextBuilder::thread_name_prefix),
+    /// reads each block from disk and sends it over a bounded channel of
+    /// [`BLOCK_STREAM_CAPACITY`] blocks, so reading stays at most that far
+    /// ahead of the consumer. The stream ends after the block at the
+    /// active chain's tip, after the first read error, or once the receiver is
+    /// dropped. Since [`Block`] is `Send`, the receiver can hand blocks on to
+    /// other worker threads.
+    ///
+    /// The chain is re-queried for each height, so blocks connected while
+    /// streaming are included, while a reorganization below the current height
+    /// is not reflected.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// let chainman = Arc::new(ChainstateManager::builder(&context, "/data", "/blocks")?.build()?);
+    ///
+    /// for block in Arc::clone(&chainman).spawn_block_stream(0) {
+    ///     let block = block?;
+    ///     println!("Block {} has {} transactions", block.hash(), block.transaction_count());
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn spawn_block_stream(
+        self: Arc<Self>,
+        start_height: usize,
+    ) -> Receiver<Result<Block, KernelError>> {
+        let (sender, receiver) = sync_channel(BLOCK_STREAM_CAPACITY);
+        let name = format!("{}-block-stream", self.thread_name_prefix);
+        let spawned = thread::Builder::new().name(name).spawn(move || {
+            let mut height = start_height;
+            while let Some(entry) = self.active_chain().at_height(height) {
+                let block = self.read_block_data(&entry);
+                let failed = block.is_err();
+                if sender.send(block).is_err() || failed {
+                    break;
+                }
+                height += 1;
+            }
+        });
+        spawned.expect("failed to spawn block stream thread");
+        receiver
+    }
+
+    /// Reads blocks of the active chain in `start_height..end_height` on
+    /// `threads` background threads, yielding them in height order.
+    ///
+    /// The reader threads, named `<prefix>-block-reader-N` after the context's
+    /// [`thread_name_prefix`](crate::ContextBuilder::thread_name_prefix),
+    /// claim heights in turn and read the blocks from disk concurrently. The
+    /// returned [`ParallelBlockIter`] reorders them, and reading stays at most
+    /// [`BLOCK_STREAM_CAPACITY`] blocks ahead of the consumer. A `threads`
+    /// value of 0 is treated as 1.
+    ///
+    /// The iterator ends before `end_height`, after the active chain's tip,
+    /// or after the first read error, whichever comes first. Dropping it stops
+    /// the reader threads.
+    ///
+    /// The number of threads does not affect the result: while the active
+    /// chain does not change, the iterator yields the same blocks in the same
+    /// order as reading each height with [`read_block_data`](Self::read_block_data).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use bitcoinkernel::{ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// let chainman = Arc::new(ChainstateManager::builder(&context, "/data", "/blocks")?.build()?);
+    ///
+    /// let end = chainman.height() as usize + 1;
+    /// for block in Arc::clone(&chainman).par_read_blocks(0, end, 4) {
+    ///     let block = block?;
+    ///     println!("Block {} has {} transactions", block.hash(), block.transaction_count());
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn par_read_blocks(
+        self: Arc<Self>,
+        start_height: usize,
+        end_height: usize,
+        threads: usize,
+    ) -> ParallelBlockIter {
+        let (sender, receiver) = channel();
+        let window = Arc::new(ReadWindow {
+            state: Mutex::new(WindowState {
+                next_height: start_height,
+                cancelled: false,
+            }),
+            progress: Condvar::new(),
+        });
+        let claimed = Arc::new(AtomicUsize::new(start_height));
+
+        for index in 0..threads.max(1) {
+            let chainman = Arc::clone(&self);
+            let window = Arc::clone(&window);
+            let claimed = Arc::clone(&claimed);
+            let sender = sender.clone();
+            let name = format!("{}-block-reader-{}", self.thread_name_prefix, index);
+            let spawned = thread::Builder::new().name(name).spawn(move || loop {
+                let height = claimed.fetch_add(1, Ordering::Relaxed);
+                if height >= end_height || !window.wait_for(height) {
+                    break;
+                }
+                let block = chainman
+                    .active_chain()
+                    .at_height(height)
+                    .map(|entry| chainman.read_block_data(&entry));
+                if sender.send((height, block)).is_err() {
+                    break;
+                }
+            });
+            spawned.expect("failed to spawn block reader thread");
+        }
+
+        ParallelBlockIter {
+            receiver,
+            pending: HashMap::new(),
+            next_height: start_height,
+            end_height,
+            window,
+            done: false,
+        }
+    }
+
+    /// Reads the spent outputs of the active chain's blocks in
+    /// `start_height..end_height`, yielding them in height order.
+    ///
+    /// The iterator ends before `end_height` or after the active chain's tip,
+    /// whichever comes first. A block whose undo data cannot be read, for
+    /// example because it has been pruned, yields the error of
+    /// [`read_spent_outputs`](Self::read_spent_outputs) and iteration continues
+    /// with the next height. The genesis block yields empty spent outputs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    ///
+    /// for spent_outputs in chainman.read_spent_outputs_range(1, 100) {
+    ///     println!("{} transactions spend outputs", spent_outputs?.count());
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn read_spent_outputs_range(
+        &self,
+        start_height: usize,
+        end_height: usize,
+    ) -> impl Iterator<Item = Result<BlockSpentOutputs, KernelError>> + '_ {
+        let chain = self.active_chain();
+        (start_height..end_height)
+            .map_while(move |height| chain.at_height(height))
+            .map(move |entry| self.read_spent_outputs(&entry))
+    }
+
+    /// Reads the active chain's blocks in `start_height..end_height` together
+    /// with their spent outputs, yielding them in height order.
+    ///
+    /// The spent outputs are `None` for the genesis block, which spends no
+    /// outputs. Range and error handling follow
+    /// [`read_spent_outputs_range`](Self::read_spent_outputs_range): a height
+    /// whose block or undo data cannot be read yields an error and iteration
+    /// continues.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use bitcoinkernel::{prelude::*, ContextBuilder, ChainstateManager, ChainType, KernelError};
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    ///
+    /// for item in chainman.read_block_and_spent_range(0, 100) {
+    ///     let (block, spent_outputs) = item?;
+    ///     let spending = spent_outputs.map_or(0, |spent_outputs| spent_outputs.count());
+    ///     println!("{} of {} transactions spend outputs", spending, block.transaction_count());
+    /// }
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn read_block_and_spent_range(
+        &self,
+        start_height: usize,
+        end_height: usize,
+    ) -> impl Iterator<Item = Result<(Block, Option<BlockSpentOutputs>), KernelError>> + '_ {
+        let chain = self.active_chain();
+        (start_height..end_height)
+            .map_while(move |height| chain.at_height(height))
+            .map(move |entry| {
+                let block = self.read_block_data(&entry)?;
+                let spent_outputs = match entry.height() {
+                    0 => None,
+                    _ => Some(self.read_spent_outputs(&entry)?),
+                };
+                Ok((block, spent_outputs))
+            })
+    }
+
+    pub(crate) fn chain_type(&self) -> ChainType {
+        self.chain_type
+    }
+
+    pub(crate) fn blocks_dir(&self) -> &Path {
+        &self.blocks_dir
+    }
+}
+
+/// Heights handed out to [`ChainstateManager::par_read_blocks`] readers are
+/// held back until the consumer is within [`BLOCK_STREAM_CAPACITY`] of them.
+struct ReadWindow {
+    state: Mutex<WindowState>,
+    progress: Condvar,
+}
+
+struct WindowState {
+    next_height: usize,
+    cancelled: bool,
+}
+
+impl ReadWindow {
+    /// Blocks until `height` is inside the window. Returns `false` if reading
+    /// was cancelled.
+    fn wait_for(&self, height: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while !state.cancelled && height >= state.next_height + BLOCK_STREAM_CAPACITY {
+            state = self.progress.wait(state).unwrap();
+        }
+        !state.cancelled
+    }
+
+    fn advance(&self, next_height: usize) {
+        self.state.lock().unwrap().next_height = next_height;
+        self.progress.notify_all();
+    }
+
+    fn cancel(&self) {
+        self.state.lock().unwrap().cancelled = true;
+        self.progress.notify_all();
+    }
+}
+
+/// A block read by a [`ChainstateManager::par_read_blocks`] reader, or `None`
+/// if the height is above the active chain's tip.
+type ReadResult = (usize, Option<Result<Block, KernelError>>);
+
+/// Iterator over blocks read in parallel, in height order.
+///
+/// Created by [`ChainstateManager::par_read_blocks`].
+pub struct ParallelBlockIter {
+    receiver: Receiver<ReadResult>,
+    pending: HashMap<usize, Option<Result<Block, KernelError>>>,
+    next_height: usize,
+    end_height: usize,
+    window: Arc<ReadWindow>,
+    done: bool,
+}
+
+impl Iterator for ParallelBlockIter {
+    type Item = Result<Block, KernelError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.next_height >= self.end_height {
+            return None;
+        }
+
+        let block = loop {
+            if let Some(block) = self.pending.remove(&self.next_height) {
+                break block;
+            }
+            match self.receiver.recv() {
+                Ok((height, block)) => {
+                    self.pending.insert(height, block);
+                }
+                Err(_) => break None,
+            }
+        };
+
+        self.next_height += 1;
+        match block {
+            Some(Ok(block)) => {
+                self.window.advance(self.next_height);
+                Some(Ok(block))
+            }
+            result => {
+                self.done = true;
+                self.window.cancel();
+                result
+            }
+        }
+    }
+}
+
+impl Drop for ParallelBlockIter {
+    fn drop(&mut self) {
+        self.window.cancel();
+    }
 }
 
 impl Drop for ChainstateManager {
@@ -493,6 +1150,13 @@ impl Drop for ChainstateManager {
 /// by chaining method calls, then call [`build`](Self::build) to create the
 /// chainstate manager.
 ///
+/// # Cache Size
+/// There is no equivalent of Bitcoin Core's `-dbcache`. The C API does not
+/// expose the cache sizes, so the kernel always splits its default budget of
+/// 450 MiB between the block tree database, the coins database and the
+/// in-memory UTXO cache. A full sync therefore flushes the UTXO cache more
+/// often than a node started with a large `-dbcache`.
+///
 /// # Example
 /// ```no_run
 /// use bitcoinkernel::{ChainType, ChainstateManager, ContextBuilder, KernelError};
@@ -512,6 +1176,12 @@ impl Drop for ChainstateManager {
 /// ```
 pub struct ChainstateManagerBuilder {
     inner: *mut btck_ChainstateManagerOptions,
+    flush_failure: FlushFailure,
+    tip_signal: Arc<TipSignal>,
+    chain_type: ChainType,
+    chain_params: ChainParams,
+    blocks_dir: PathBuf,
+    thread_name_prefix: String,
 }
 
 impl ChainstateManagerBuilder {
@@ -521,8 +1191,9 @@ impl ChainstateManagerBuilder {
     ///
     /// # Arguments
     /// * `context` - The [`Context`] that configures chain parameters and
-    ///   notification callbacks. It is recommended to keep the context in scope
-    ///   for the lifetime of the [`ChainstateManager`].
+    ///   notification callbacks. The builder and the [`ChainstateManager`] it
+    ///   builds keep the context's kernel state alive, so the context may be
+    ///   dropped first.
     /// * `data_dir` - Path to the directory where chainstate data will be stored.
     ///   This includes the UTXO set database and other chain state information.
     /// * `blocks_dir` - Path to the directory where block data will be stored.
@@ -549,7 +1220,15 @@ impl ChainstateManagerBuilder {
                 "Failed to create chainstate manager options.".to_string(),
             ));
         }
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            flush_failure: context.flush_failure(),
+            tip_signal: context.tip_signal(),
+            chain_type: context.chain_type(),
+            chain_params: context.chain_params().clone(),
+            blocks_dir: PathBuf::from(blocks_dir),
+            thread_name_prefix: context.thread_name_prefix().to_string(),
+        })
     }
 
     /// Sets the number of worker threads for validation.
@@ -562,6 +1241,9 @@ impl ChainstateManagerBuilder {
     /// * `worker_threads` - Number of worker threads to use for validation.
     ///   Valid range is 0-15 (values outside this range are clamped). When set to 0,
     ///   no parallel verification is performed.
+    ///
+    /// The kernel names these threads `b-scriptch.NN`, independent of the
+    /// context's [`thread_name_prefix`](crate::ContextBuilder::thread_name_prefix).
     pub fn worker_threads(self, worker_threads: i32) -> Self {
         unsafe {
             btck_chainstate_manager_options_set_worker_threads_num(self.inner, worker_threads);
@@ -627,6 +1309,43 @@ impl ChainstateManagerBuilder {
         }
     }
 
+    /// Configures a full reindex from the existing block files.
+    ///
+    /// When `reindex` is true, both the block tree and chainstate databases are
+    /// wiped on initialization, and the next call to
+    /// [`ChainstateManager::import_blocks`] rebuilds them from the block files
+    /// already in the blocks directory. Unlike starting from an empty data
+    /// directory, no block data has to be provided again. This is the remedy
+    /// for a corrupt chainstate or block index.
+    ///
+    /// This is equivalent to [`wipe_db(true, true)`](Self::wipe_db) and
+    /// replaces any earlier `wipe_db` setting. Passing `false` disables wiping
+    /// of both databases. To rebuild only the chainstate (UTXO set) while
+    /// keeping the block index, use `wipe_db(false, true)` instead.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use bitcoinkernel::{ChainType, ChainstateManager, ContextBuilder, KernelError};
+    /// # fn main() -> Result<(), KernelError> {
+    /// # let context = ContextBuilder::new().chain_type(ChainType::Regtest).build()?;
+    /// let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?
+    ///     .reindex(true)
+    ///     .build()?;
+    /// chainman.import_blocks()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reindex(self, reindex: bool) -> Self {
+        unsafe {
+            btck_chainstate_manager_options_set_wipe_dbs(
+                self.inner,
+                c_helpers::to_c_bool(reindex),
+                c_helpers::to_c_bool(reindex),
+            );
+        }
+        self
+    }
+
     /// Configures the block tree database to run entirely in memory.
     ///
     /// When enabled, the block tree database (which stores the block index and
@@ -692,10 +1411,27 @@ impl ChainstateManagerBuilder {
                 "Failed to create chainstate manager.".to_string(),
             ));
         }
-        Ok(ChainstateManager { inner })
+        Ok(ChainstateManager {
+            inner,
+            flush_failure: self.flush_failure.clone(),
+            tip_signal: Arc::clone(&self.tip_signal),
+            chain_type: self.chain_type,
+            chain_params: self.chain_params.clone(),
+            blocks_dir: self.blocks_dir.clone(),
+            thread_name_prefix: self.thread_name_prefix.clone(),
+        })
     }
 }
 
+/// Returns the file number of a block file named `blkNNNNN.dat`.
+fn block_file_number(name: &OsStr) -> Option<u32> {
+    let digits = name.to_str()?.strip_prefix("blk")?.strip_suffix(".dat")?;
+    if digits.len() < 5 || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
 impl Drop for ChainstateManagerBuilder {
     fn drop(&mut self) {
         unsafe {
@@ -707,7 +1443,7 @@ impl Drop for ChainstateManagerBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{test_utils::TempDir, ChainType, ContextBuilder};
+    use crate::{test_utils::TempDir, ChainType, ContextBuilder, FlushErrorKind};
 
     fn create_test_context() -> Context {
         ContextBuilder::new()
@@ -716,6 +1452,31 @@ mod tests {
             .unwrap()
     }
 
+    #[test]
+    fn test_chainstate_manager_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<ChainstateManager>();
+        assert_send_sync::<crate::Chain<'_>>();
+        assert_send_sync::<crate::state::ChainIterator<'_>>();
+        assert_send_sync::<crate::BlockTreeEntry<'_>>();
+    }
+
+    #[test]
+    fn test_block_file_number() {
+        assert_eq!(block_file_number(OsStr::new("blk00000.dat")), Some(0));
+        assert_eq!(block_file_number(OsStr::new("blk01234.dat")), Some(1234));
+        assert_eq!(
+            block_file_number(OsStr::new("blk100000.dat")),
+            Some(100_000)
+        );
+        assert_eq!(block_file_number(OsStr::new("rev00000.dat")), None);
+        assert_eq!(block_file_number(OsStr::new("blk0000.dat")), None);
+        assert_eq!(block_file_number(OsStr::new("blk0000a.dat")), None);
+        assert_eq!(block_file_number(OsStr::new("blk00000.dat.tmp")), None);
+        assert_eq!(block_file_number(OsStr::new("xor.dat")), None);
+    }
+
     #[test]
     fn test_chainstate_manager_options_new() {
         let context = create_test_context();
@@ -771,6 +1532,35 @@ mod tests {
         assert!(chainman.is_ok());
     }
 
+    #[test]
+    fn test_flush_failure_returns_database_error() {
+        let context = create_test_context();
+        let test_dir = TempDir::new("test_chainman");
+
+        let chainman =
+            ChainstateManagerBuilder::new(&context, test_dir.data_dir(), test_dir.blocks_dir())
+                .unwrap()
+                .build()
+                .unwrap();
+        assert!(chainman.import_blocks().is_ok());
+
+        context
+            .flush_failure()
+            .set((FlushErrorKind::BlockFile, "Disk full".to_string()))
+            .unwrap();
+
+        assert!(matches!(
+            chainman.import_blocks(),
+            Err(KernelError::DatabaseError(_))
+        ));
+        assert!(chainman.import_blocks().unwrap_err().is_retryable());
+        let tip = chainman.active_chain().tip();
+        assert!(matches!(
+            chainman.read_block_data(&tip),
+            Err(KernelError::DatabaseError(_))
+        ));
+    }
+
     #[test]
     fn test_process_block_result_new_block() {
         let result = ProcessBlockResult::NewBlock;
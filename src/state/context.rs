@@ -54,12 +54,13 @@
 //! ```
 
 use std::ffi::c_void;
+use std::sync::Arc;
 
 use libbitcoinkernel_sys::{
     btck_ChainParameters, btck_ChainType, btck_ChainType_MAINNET, btck_ChainType_REGTEST,
     btck_ChainType_SIGNET, btck_ChainType_TESTNET, btck_ChainType_TESTNET_4, btck_Context,
     btck_ContextOptions, btck_NotificationInterfaceCallbacks, btck_ValidationInterfaceCallbacks,
-    btck_chain_parameters_create, btck_chain_parameters_create_signet,
+    btck_chain_parameters_copy, btck_chain_parameters_create, btck_chain_parameters_create_signet,
     btck_chain_parameters_destroy, btck_context_create, btck_context_destroy,
     btck_context_interrupt, btck_context_options_create, btck_context_options_destroy,
     btck_context_options_set_chainparams, btck_context_options_set_notifications,
@@ -74,9 +75,9 @@ use crate::{
             notification_flush_error_wrapper, notification_header_tip_wrapper,
             notification_progress_wrapper, notification_user_data_destroy_wrapper,
             notification_warning_set_wrapper, notification_warning_unset_wrapper, BlockTipCallback,
-            FatalErrorCallback, FlushErrorCallback, HeaderTipCallback,
-            NotificationCallbackRegistry, ProgressCallback, WarningSetCallback,
-            WarningUnsetCallback,
+            FatalErrorCallback, FlushErrorCallback, FlushFailure, HeaderTipCallback,
+            KernelNotificationHandler, NotificationCallbackRegistry, ProgressCallback, TipSignal,
+            WarningSetCallback, WarningUnsetCallback,
         },
         validation::{
             validation_block_checked_wrapper, validation_block_connected_wrapper,
@@ -165,6 +166,14 @@ impl ChainParams {
     }
 }
 
+impl Clone for ChainParams {
+    fn clone(&self) -> Self {
+        ChainParams {
+            inner: unsafe { btck_chain_parameters_copy(self.inner) },
+        }
+    }
+}
+
 impl Drop for ChainParams {
     fn drop(&mut self) {
         unsafe {
@@ -208,6 +217,11 @@ impl AsPtr<btck_ChainParameters> for ChainParams {
 /// ```
 pub struct Context {
     inner: *mut btck_Context,
+    flush_failure: FlushFailure,
+    tip_signal: Arc<TipSignal>,
+    chain_type: ChainType,
+    chain_params: ChainParams,
+    thread_name_prefix: String,
 }
 
 unsafe impl Send for Context {}
@@ -255,6 +269,27 @@ impl Context {
         ContextBuilder::new().build()
     }
 
+    /// Creates a shared context for `chain_type` with no callbacks registered.
+    ///
+    /// This is a shortcut for the common case of
+    /// `Arc::new(ContextBuilder::new().chain_type(chain_type).build()?)`.
+    /// Use [`Context::builder`] to register notification or validation
+    /// callbacks.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ChainstateManager, ChainType, Context, KernelError};
+    ///
+    /// let context = Context::for_chain(ChainType::Regtest)?;
+    /// let chainman = ChainstateManager::builder(&context, "/data", "/blocks")?.build()?;
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn for_chain(chain_type: ChainType) -> Result<Arc<Context>, KernelError> {
+        Ok(Arc::new(
+            ContextBuilder::new().chain_type(chain_type).build()?,
+        ))
+    }
+
     /// Interrupts any ongoing operations in the context.
     ///
     /// This signals the context to stop any long-running operations that
@@ -284,6 +319,51 @@ impl Context {
             ));
         }
     }
+
+    /// Returns the [`ChainType`] this context was built for.
+    ///
+    /// A context configured with [`ContextBuilder::signet`] reports
+    /// [`ChainType::Signet`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ChainType, Context, KernelError};
+    ///
+    /// let context = Context::for_chain(ChainType::Signet)?;
+    /// assert_eq!(context.chain_type(), ChainType::Signet);
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn chain_type(&self) -> ChainType {
+        self.chain_type
+    }
+
+    pub(crate) fn flush_failure(&self) -> FlushFailure {
+        self.flush_failure.clone()
+    }
+
+    pub(crate) fn tip_signal(&self) -> Arc<TipSignal> {
+        Arc::clone(&self.tip_signal)
+    }
+
+    pub(crate) fn chain_params(&self) -> &ChainParams {
+        &self.chain_params
+    }
+
+    pub(crate) fn thread_name_prefix(&self) -> &str {
+        &self.thread_name_prefix
+    }
+}
+
+/// Returns [`KernelError::DatabaseError`] if the kernel has reported a flush
+/// error for the context owning `flush_failure`.
+pub(crate) fn check_flush_failure(flush_failure: &FlushFailure) -> Result<(), KernelError> {
+    match flush_failure.get() {
+        Some((kind, message)) => Err(KernelError::DatabaseError(format!(
+            "{} flush failed: {}",
+            kind, message
+        ))),
+        None => Ok(()),
+    }
 }
 
 impl AsPtr<btck_Context> for Context {
@@ -300,6 +380,10 @@ impl Drop for Context {
     }
 }
 
+/// Prefix for the names of threads spawned by this crate, unless overridden
+/// with [`ContextBuilder::thread_name_prefix`].
+pub(crate) const DEFAULT_THREAD_NAME_PREFIX: &str = "btck";
+
 /// Builder for creating a [`Context`] with custom configuration.
 ///
 /// The builder pattern allows flexible configuration of the Bitcoin Kernel
@@ -363,6 +447,9 @@ impl Drop for Context {
 /// ```
 pub struct ContextBuilder {
     inner: *mut btck_ContextOptions,
+    chain_type: ChainType,
+    chain_params: ChainParams,
+    thread_name_prefix: String,
     notification_registry: Option<NotificationCallbackRegistry>,
     validation_registry: Option<ValidationCallbackRegistry>,
 }
@@ -390,6 +477,9 @@ impl ContextBuilder {
     pub fn new() -> ContextBuilder {
         ContextBuilder {
             inner: unsafe { btck_context_options_create() },
+            chain_type: ChainType::Mainnet,
+            chain_params: ChainParams::new(ChainType::Mainnet),
+            thread_name_prefix: DEFAULT_THREAD_NAME_PREFIX.to_string(),
             notification_registry: None,
             validation_registry: None,
         }
@@ -414,9 +504,12 @@ impl ContextBuilder {
     /// # Ok::<(), KernelError>(())
     /// ```
     pub fn build(mut self) -> Result<Context, KernelError> {
-        if let Some(registry) = self.notification_registry.take() {
-            self.setup_notification_interface(registry);
-        }
+        // The notification interface is always installed so that flush errors
+        // are recorded even when no handler was registered for them.
+        let registry = self.notification_registry.take().unwrap_or_default();
+        let flush_failure = registry.flush_failure();
+        let tip_signal = registry.tip_signal();
+        self.setup_notification_interface(registry);
         if let Some(registry) = self.validation_registry.take() {
             self.setup_validation_interface(registry);
         }
@@ -425,7 +518,14 @@ impl ContextBuilder {
         if inner.is_null() {
             return Err(KernelError::Internal("Invalid context.".to_string()));
         }
-        Ok(Context { inner })
+        Ok(Context {
+            inner,
+            flush_failure,
+            tip_signal,
+            chain_type: self.chain_type,
+            chain_params: self.chain_params.clone(),
+            thread_name_prefix: std::mem::take(&mut self.thread_name_prefix),
+        })
     }
 
     fn setup_notification_interface(&self, registry: NotificationCallbackRegistry) {
@@ -480,9 +580,10 @@ impl ContextBuilder {
     ///     .build()?;
     /// # Ok::<(), KernelError>(())
     /// ```
-    pub fn chain_type(self, chain_type: ChainType) -> ContextBuilder {
-        let chain_params = ChainParams::new(chain_type);
-        unsafe { btck_context_options_set_chainparams(self.inner, chain_params.inner) };
+    pub fn chain_type(mut self, chain_type: ChainType) -> ContextBuilder {
+        self.chain_type = chain_type;
+        self.chain_params = ChainParams::new(chain_type);
+        unsafe { btck_context_options_set_chainparams(self.inner, self.chain_params.inner) };
         self
     }
 
@@ -507,9 +608,38 @@ impl ContextBuilder {
     ///     .build()?;
     /// # Ok::<(), KernelError>(())
     /// ```
-    pub fn signet(self, challenge: &[u8]) -> ContextBuilder {
-        let chain_params = ChainParams::new_signet(challenge);
-        unsafe { btck_context_options_set_chainparams(self.inner, chain_params.inner) };
+    pub fn signet(mut self, challenge: &[u8]) -> ContextBuilder {
+        self.chain_type = ChainType::Signet;
+        self.chain_params = ChainParams::new_signet(challenge);
+        unsafe { btck_context_options_set_chainparams(self.inner, self.chain_params.inner) };
+        self
+    }
+
+    /// Sets the prefix used to name threads spawned by this crate.
+    ///
+    /// Threads the crate spawns on behalf of a [`ChainstateManager`](crate::ChainstateManager)
+    /// created from this context, such as the reader of
+    /// [`spawn_block_stream`](crate::ChainstateManager::spawn_block_stream),
+    /// are named `<prefix>-<role>`, making them identifiable in `top` and
+    /// profilers. The default prefix is `btck`. Null bytes are removed, and
+    /// most platforms truncate thread names to 15 bytes.
+    ///
+    /// The kernel's own script verification workers, configured with
+    /// [`worker_threads`](crate::ChainstateManagerBuilder::worker_threads),
+    /// are named by the kernel as `b-scriptch.NN`. The C API provides no way
+    /// to rename them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ContextBuilder, KernelError};
+    ///
+    /// let context = ContextBuilder::new()
+    ///     .thread_name_prefix("node")
+    ///     .build()?;
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn thread_name_prefix(mut self, prefix: &str) -> ContextBuilder {
+        self.thread_name_prefix = prefix.replace('\0', "");
         self
     }
 
@@ -708,8 +838,13 @@ impl ContextBuilder {
     ///
     /// # Arguments
     /// * `handler` - The callback function or closure that receives:
+    ///   - `kind` - The [`FlushErrorKind`](crate::FlushErrorKind) of data that failed to flush
     ///   - `message` - The error message as a [`String`]
     ///
+    /// Once a flush error has been reported, fallible operations on a
+    /// [`ChainstateManager`](crate::ChainstateManager) created from this
+    /// context return [`KernelError::DatabaseError`].
+    ///
     /// # Returns
     /// The builder instance for method chaining.
     ///
@@ -718,8 +853,8 @@ impl ContextBuilder {
     /// use bitcoinkernel::{ContextBuilder, KernelError};
     ///
     /// let context = ContextBuilder::new()
-    ///     .with_flush_error_notification(|message| {
-    ///         eprintln!("Flush error: {}", message);
+    ///     .with_flush_error_notification(|kind, message| {
+    ///         eprintln!("Flush error ({}): {}", kind, message);
     ///     })
     ///     .build()?;
     /// # Ok::<(), KernelError>(())
@@ -771,6 +906,47 @@ impl ContextBuilder {
         self
     }
 
+    /// Registers a single handler for all notification callbacks.
+    ///
+    /// Each method of the [`KernelNotificationHandler`] is registered as the
+    /// corresponding notification callback, replacing any registered before.
+    /// Methods the handler does not override are no-ops.
+    ///
+    /// # Type Parameters
+    /// * `T` - A type implementing [`KernelNotificationHandler`]
+    ///
+    /// # Arguments
+    /// * `handler` - The handler receiving every notification
+    ///
+    /// # Returns
+    /// The builder instance for method chaining.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bitcoinkernel::{ContextBuilder, KernelError, KernelNotificationHandler};
+    ///
+    /// struct ProgressPrinter;
+    ///
+    /// impl KernelNotificationHandler for ProgressPrinter {
+    ///     fn on_progress(&self, title: String, percent: i32, _resume_possible: bool) {
+    ///         println!("{}: {}%", title, percent);
+    ///     }
+    /// }
+    ///
+    /// let context = ContextBuilder::new()
+    ///     .with_notifications(ProgressPrinter)
+    ///     .build()?;
+    /// # Ok::<(), KernelError>(())
+    /// ```
+    pub fn with_notifications<T>(mut self, handler: T) -> Self
+    where
+        T: KernelNotificationHandler + 'static,
+    {
+        self.get_or_create_notification_registry()
+            .register_handler(handler);
+        self
+    }
+
     /// Configures multiple notification callbacks at once.
     ///
     /// This method provides access to the [`NotificationCallbackRegistry`]
@@ -1047,6 +1223,15 @@ impl Drop for ContextBuilder {
 /// * [`Signet`](ChainType::Signet) - Test network with controlled, regular block production
 /// * [`Regtest`](ChainType::Regtest) - Regression test network for local development
 ///
+/// # Regtest Parameters
+/// Regtest uses Bitcoin Core's default regtest consensus parameters: the
+/// subsidy halves every 150 blocks and the difficulty never retargets, so
+/// blocks can be mined at the minimum difficulty. The kernel API does not
+/// expose Bitcoin Core's regtest overrides, so these parameters cannot be
+/// changed before [`ContextBuilder::build`]. Tests that depend on the halving
+/// schedule can query it with
+/// [`subsidy_halving_interval`](ChainType::subsidy_halving_interval).
+///
 /// # Examples
 /// ```no_run
 /// use bitcoinkernel::{ChainType, ContextBuilder, KernelError};
@@ -1077,6 +1262,97 @@ pub enum ChainType {
     Regtest = btck_ChainType_REGTEST,
 }
 
+impl ChainType {
+    /// Returns the network magic bytes that prefix messages and each block
+    /// record in a block file on this network.
+    ///
+    /// For [`ChainType::Signet`] this is the magic of the default signet. A
+    /// custom signet derives its magic from its challenge.
+    pub const fn magic(&self) -> [u8; 4] {
+        match self {
+            ChainType::Mainnet => [0xf9, 0xbe, 0xb4, 0xd9],
+            ChainType::Testnet => [0x0b, 0x11, 0x09, 0x07],
+            ChainType::Testnet4 => [0x1c, 0x16, 0x3f, 0x28],
+            ChainType::Signet => [0x0a, 0x03, 0xcf, 0x40],
+            ChainType::Regtest => [0xfa, 0xbf, 0xb5, 0xda],
+        }
+    }
+
+    /// Returns the number of blocks after which the block subsidy halves.
+    pub const fn subsidy_halving_interval(&self) -> i32 {
+        match self {
+            ChainType::Regtest => 150,
+            ChainType::Mainnet | ChainType::Testnet | ChainType::Testnet4 | ChainType::Signet => {
+                210_000
+            }
+        }
+    }
+
+    /// Returns `true` if the proof-of-work target never retargets on this
+    /// network, so every block may be mined at the minimum difficulty.
+    pub const fn pow_no_retargeting(&self) -> bool {
+        matches!(self, ChainType::Regtest)
+    }
+
+    /// Returns the highest, easiest, proof-of-work target allowed on this
+    /// network, in the same internal byte order as
+    /// [`BlockHashExt::to_bytes`](crate::BlockHashExt::to_bytes).
+    ///
+    /// For [`ChainType::Signet`] this is the limit of the default signet. The
+    /// kernel's chain parameters are opaque through the C API, so this mirrors
+    /// Bitcoin Core's consensus parameters for the network.
+    pub const fn pow_limit(&self) -> [u8; 32] {
+        let mut limit = [0u8; 32];
+        match self {
+            ChainType::Mainnet | ChainType::Testnet | ChainType::Testnet4 => {
+                let mut i = 0;
+                while i < 28 {
+                    limit[i] = 0xff;
+                    i += 1;
+                }
+            }
+            ChainType::Signet => {
+                limit[27] = 0xae;
+                limit[28] = 0x77;
+                limit[29] = 0x03;
+            }
+            ChainType::Regtest => {
+                let mut i = 0;
+                while i < 31 {
+                    limit[i] = 0xff;
+                    i += 1;
+                }
+                limit[31] = 0x7f;
+            }
+        }
+        limit
+    }
+
+    /// Returns the target time between blocks in seconds, 600 on every
+    /// network.
+    ///
+    /// The kernel's chain parameters are opaque through the C API, so this
+    /// mirrors Bitcoin Core's consensus parameters for the network.
+    pub const fn pow_target_spacing(&self) -> i64 {
+        10 * 60
+    }
+
+    /// Returns the number of blocks between difficulty adjustments.
+    ///
+    /// This is 2016 blocks, two weeks of target spacing, on every network
+    /// except regtest, whose one day timespan gives 144 blocks. Regtest never
+    /// actually retargets, see [`pow_no_retargeting`](Self::pow_no_retargeting).
+    pub const fn difficulty_adjustment_interval(&self) -> i64 {
+        let pow_target_timespan = match self {
+            ChainType::Regtest => 24 * 60 * 60,
+            ChainType::Mainnet | ChainType::Testnet | ChainType::Testnet4 | ChainType::Signet => {
+                14 * 24 * 60 * 60
+            }
+        };
+        pow_target_timespan / self.pow_target_spacing()
+    }
+}
+
 impl From<ChainType> for btck_ChainType {
     fn from(chain_type: ChainType) -> Self {
         chain_type as btck_ChainType
@@ -1139,6 +1415,54 @@ mod tests {
         assert_ne!(ChainType::Signet, ChainType::Regtest);
     }
 
+    #[test]
+    fn test_chain_type_magic() {
+        assert_eq!(ChainType::Mainnet.magic(), [0xf9, 0xbe, 0xb4, 0xd9]);
+        assert_eq!(ChainType::Regtest.magic(), [0xfa, 0xbf, 0xb5, 0xda]);
+        assert_ne!(ChainType::Testnet.magic(), ChainType::Testnet4.magic());
+        assert_ne!(ChainType::Signet.magic(), ChainType::Mainnet.magic());
+    }
+
+    #[test]
+    fn test_chain_type_subsidy_halving_interval() {
+        assert_eq!(ChainType::Mainnet.subsidy_halving_interval(), 210_000);
+        assert_eq!(ChainType::Testnet4.subsidy_halving_interval(), 210_000);
+        assert_eq!(ChainType::Regtest.subsidy_halving_interval(), 150);
+    }
+
+    #[test]
+    fn test_chain_type_pow_no_retargeting() {
+        assert!(ChainType::Regtest.pow_no_retargeting());
+        assert!(!ChainType::Mainnet.pow_no_retargeting());
+        assert!(!ChainType::Signet.pow_no_retargeting());
+    }
+
+    #[test]
+    fn test_chain_type_pow_limit() {
+        let mainnet = ChainType::Mainnet.pow_limit();
+        assert_eq!(mainnet[..28], [0xff; 28]);
+        assert_eq!(mainnet[28..], [0x00; 4]);
+        assert_eq!(ChainType::Testnet4.pow_limit(), mainnet);
+
+        let mut signet = [0u8; 32];
+        signet[27..30].copy_from_slice(&[0xae, 0x77, 0x03]);
+        assert_eq!(ChainType::Signet.pow_limit(), signet);
+
+        let regtest = ChainType::Regtest.pow_limit();
+        assert_eq!(regtest[..31], [0xff; 31]);
+        assert_eq!(regtest[31], 0x7f);
+    }
+
+    #[test]
+    fn test_chain_type_pow_timing() {
+        assert_eq!(ChainType::Mainnet.pow_target_spacing(), 600);
+        assert_eq!(ChainType::Signet.pow_target_spacing(), 600);
+        assert_eq!(ChainType::Mainnet.difficulty_adjustment_interval(), 2016);
+        assert_eq!(ChainType::Testnet4.difficulty_adjustment_interval(), 2016);
+        assert_eq!(ChainType::Signet.difficulty_adjustment_interval(), 2016);
+        assert_eq!(ChainType::Regtest.difficulty_adjustment_interval(), 144);
+    }
+
     #[test]
     fn test_chain_type_clone() {
         let mainnet = ChainType::Mainnet;
@@ -1156,6 +1480,14 @@ mod tests {
         let _regtest_params = ChainParams::new(ChainType::Regtest);
     }
 
+    #[test]
+    fn test_chain_params_clone() {
+        let params = ChainParams::new(ChainType::Regtest);
+        let cloned = params.clone();
+        drop(params);
+        assert!(!cloned.as_ptr().is_null());
+    }
+
     #[test]
     fn test_signet_params_creation() {
         // OP_TRUE challenge - blocks are trivially valid.
@@ -1193,6 +1525,45 @@ mod tests {
         assert!(regtest.is_ok());
     }
 
+    #[test]
+    fn test_context_for_chain() {
+        for chain_type in [
+            ChainType::Mainnet,
+            ChainType::Testnet,
+            ChainType::Testnet4,
+            ChainType::Signet,
+            ChainType::Regtest,
+        ] {
+            let context = Context::for_chain(chain_type).unwrap();
+            assert!(context.interrupt().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_context_chain_type() {
+        assert_eq!(Context::new().unwrap().chain_type(), ChainType::Mainnet);
+        for chain_type in [
+            ChainType::Mainnet,
+            ChainType::Testnet,
+            ChainType::Testnet4,
+            ChainType::Signet,
+            ChainType::Regtest,
+        ] {
+            let context = ContextBuilder::new()
+                .chain_type(chain_type)
+                .build()
+                .unwrap();
+            assert_eq!(context.chain_type(), chain_type);
+        }
+
+        let signet = ContextBuilder::new()
+            .chain_type(ChainType::Regtest)
+            .signet(&[0x51])
+            .build()
+            .unwrap();
+        assert_eq!(signet.chain_type(), ChainType::Signet);
+    }
+
     #[test]
     fn test_context_creation_with_signet() {
         // OP_TRUE challenge - blocks are trivially valid.
@@ -1233,7 +1604,7 @@ mod tests {
             .with_header_tip_notification(|_state, _height, _timestamp, _presync| {})
             .with_warning_set_notification(|_warning, _message| {})
             .with_warning_unset_notification(|_warning| {})
-            .with_flush_error_notification(|_message| {})
+            .with_flush_error_notification(|_kind, _message| {})
             .with_fatal_error_notification(|_message| {});
 
         assert!(builder.notification_registry.is_some());
@@ -1292,6 +1663,38 @@ mod tests {
         assert!(builder.validation_registry.is_some());
     }
 
+    #[test]
+    fn test_with_notifications() {
+        struct Handler;
+
+        impl KernelNotificationHandler for Handler {
+            fn on_fatal_error(&self, message: String) {
+                panic!("unexpected fatal error: {}", message);
+            }
+        }
+
+        let builder = ContextBuilder::new().with_notifications(Handler);
+        assert!(builder.notification_registry.is_some());
+
+        let context = ContextBuilder::new()
+            .chain_type(ChainType::Regtest)
+            .with_notifications(Handler)
+            .build();
+        assert!(context.is_ok());
+    }
+
+    #[test]
+    fn test_thread_name_prefix() {
+        let context = ContextBuilder::new().build().unwrap();
+        assert_eq!(context.thread_name_prefix(), "btck");
+
+        let context = ContextBuilder::new()
+            .thread_name_prefix("node\0")
+            .build()
+            .unwrap();
+        assert_eq!(context.thread_name_prefix(), "node");
+    }
+
     #[test]
     fn test_lazy_registry_creation() {
         let builder = ContextBuilder::new();
@@ -4,6 +4,7 @@ pub mod context;
 
 pub use chain::{Chain, ChainIterator};
 pub use chainstate::{
-    ChainstateManager, ChainstateManagerBuilder, ProcessBlockHeaderResult, ProcessBlockResult,
+    ChainstateManager, ChainstateManagerBuilder, ParallelBlockIter, ProcessBlockHeaderResult,
+    ProcessBlockResult, BLOCK_STREAM_CAPACITY,
 };
 pub use context::{ChainParams, ChainType, Context, ContextBuilder};
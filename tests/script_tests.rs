@@ -0,0 +1,500 @@
+//! Runs Bitcoin Core's `script_tests.json` vectors through [`verify`].
+//!
+//! Each vector is evaluated as Core's `script_tests.cpp` does: a crediting
+//! transaction pays the scriptPubKey, and a spending transaction spends it
+//! with the scriptSig and witness. The kernel only exposes consensus flags, so
+//! vectors expected to pass are run with their policy flags removed, which can
+//! only relax the rules, and failing vectors that name a policy flag are
+//! skipped. Taproot vectors built from `#SCRIPT#` placeholders are skipped too.
+
+use std::{fs, path::Path};
+
+use bitcoinkernel::{
+    prelude::*, validate_flags, verify, KernelError, PrecomputedTransactionData, ScriptPubkey,
+    ScriptVerificationFlags, ScriptVerifyError, Transaction, TxOut, VERIFY_CHECKLOCKTIMEVERIFY,
+    VERIFY_CHECKSEQUENCEVERIFY, VERIFY_DERSIG, VERIFY_NONE, VERIFY_NULLDUMMY, VERIFY_P2SH,
+    VERIFY_TAPROOT, VERIFY_WITNESS,
+};
+
+const SCRIPT_TESTS_PATH: &str = "libbitcoinkernel-sys/bitcoin/src/test/data/script_tests.json";
+
+/// Names of opcodes `0x61` (`OP_NOP`) through `0xb9` (`OP_NOP10`), as accepted
+/// by Core's `ParseOpCode` with or without the `OP_` prefix.
+const OPCODE_NAMES: [&str; 89] = [
+    "NOP",
+    "VER",
+    "IF",
+    "NOTIF",
+    "VERIF",
+    "VERNOTIF",
+    "ELSE",
+    "ENDIF",
+    "VERIFY",
+    "RETURN",
+    "TOALTSTACK",
+    "FROMALTSTACK",
+    "2DROP",
+    "2DUP",
+    "3DUP",
+    "2OVER",
+    "2ROT",
+    "2SWAP",
+    "IFDUP",
+    "DEPTH",
+    "DROP",
+    "DUP",
+    "NIP",
+    "OVER",
+    "PICK",
+    "ROLL",
+    "ROT",
+    "SWAP",
+    "TUCK",
+    "CAT",
+    "SUBSTR",
+    "LEFT",
+    "RIGHT",
+    "SIZE",
+    "INVERT",
+    "AND",
+    "OR",
+    "XOR",
+    "EQUAL",
+    "EQUALVERIFY",
+    "RESERVED1",
+    "RESERVED2",
+    "1ADD",
+    "1SUB",
+    "2MUL",
+    "2DIV",
+    "NEGATE",
+    "ABS",
+    "NOT",
+    "0NOTEQUAL",
+    "ADD",
+    "SUB",
+    "MUL",
+    "DIV",
+    "MOD",
+    "LSHIFT",
+    "RSHIFT",
+    "BOOLAND",
+    "BOOLOR",
+    "NUMEQUAL",
+    "NUMEQUALVERIFY",
+    "NUMNOTEQUAL",
+    "LESSTHAN",
+    "GREATERTHAN",
+    "LESSTHANOREQUAL",
+    "GREATERTHANOREQUAL",
+    "MIN",
+    "MAX",
+    "WITHIN",
+    "RIPEMD160",
+    "SHA1",
+    "SHA256",
+    "HASH160",
+    "HASH256",
+    "CODESEPARATOR",
+    "CHECKSIG",
+    "CHECKSIGVERIFY",
+    "CHECKMULTISIG",
+    "CHECKMULTISIGVERIFY",
+    "NOP1",
+    "CHECKLOCKTIMEVERIFY",
+    "CHECKSEQUENCEVERIFY",
+    "NOP4",
+    "NOP5",
+    "NOP6",
+    "NOP7",
+    "NOP8",
+    "NOP9",
+    "NOP10",
+];
+
+/// Core's policy-only flag names, which have no kernel equivalent.
+const POLICY_FLAGS: [&str; 14] = [
+    "STRICTENC",
+    "LOW_S",
+    "SIGPUSHONLY",
+    "MINIMALDATA",
+    "DISCOURAGE_UPGRADABLE_NOPS",
+    "CLEANSTACK",
+    "MINIMALIF",
+    "NULLFAIL",
+    "WITNESS_PUBKEYTYPE",
+    "CONST_SCRIPTCODE",
+    "DISCOURAGE_UPGRADABLE_WITNESS_PROGRAM",
+    "DISCOURAGE_UPGRADABLE_TAPROOT_VERSION",
+    "DISCOURAGE_OP_SUCCESS",
+    "DISCOURAGE_UPGRADABLE_PUBKEYTYPE",
+];
+
+/// The subset of JSON used by the test vectors.
+enum Json {
+    Array(Vec<Json>),
+    String(String),
+    Number(f64),
+}
+
+impl Json {
+    fn as_str(&self) -> &str {
+        match self {
+            Json::String(string) => string,
+            _ => panic!("expected a JSON string"),
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn parse(text: &'a str) -> Json {
+        let mut parser = JsonParser {
+            bytes: text.as_bytes(),
+            pos: 0,
+        };
+        parser.value()
+    }
+
+    fn value(&mut self) -> Json {
+        self.skip_whitespace();
+        match self.bytes[self.pos] {
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.bytes[self.pos] == b']' {
+                        self.pos += 1;
+                        return Json::Array(items);
+                    }
+                    items.push(self.value());
+                    self.skip_whitespace();
+                    if self.bytes[self.pos] == b',' {
+                        self.pos += 1;
+                    }
+                }
+            }
+            b'"' => {
+                self.pos += 1;
+                let mut string = Vec::new();
+                loop {
+                    match self.bytes[self.pos] {
+                        b'"' => break,
+                        b'\\' => {
+                            string.push(self.bytes[self.pos + 1]);
+                            self.pos += 2;
+                        }
+                        byte => {
+                            string.push(byte);
+                            self.pos += 1;
+                        }
+                    }
+                }
+                self.pos += 1;
+                Json::String(String::from_utf8(string).unwrap())
+            }
+            _ => {
+                let start = self.pos;
+                while self.pos < self.bytes.len()
+                    && matches!(
+                        self.bytes[self.pos],
+                        b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'
+                    )
+                {
+                    self.pos += 1;
+                }
+                let number = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap();
+                Json::Number(number.parse().unwrap())
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+}
+
+/// Assembles a script from Core's test notation. Mirrors Core's `ParseScript`.
+fn parse_script(asm: &str) -> Vec<u8> {
+    let mut script = Vec::new();
+    for word in asm.split_whitespace() {
+        let digits = word.strip_prefix('-').unwrap_or(word);
+        if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            push_int(&mut script, word.parse().unwrap());
+        } else if let Some(raw) = word.strip_prefix("0x").filter(|raw| !raw.is_empty()) {
+            script.extend(hex::decode(raw).unwrap());
+        } else if word.len() >= 2 && word.starts_with('\'') && word.ends_with('\'') {
+            push_data(&mut script, &word.as_bytes()[1..word.len() - 1]);
+        } else {
+            script.push(parse_opcode(word));
+        }
+    }
+    script
+}
+
+fn parse_opcode(word: &str) -> u8 {
+    let name = word.strip_prefix("OP_").unwrap_or(word);
+    if name == "RESERVED" {
+        return 0x50;
+    }
+    let index = OPCODE_NAMES
+        .iter()
+        .position(|opcode| *opcode == name)
+        .unwrap_or_else(|| panic!("unknown opcode {}", word));
+    0x61 + index as u8
+}
+
+fn push_int(script: &mut Vec<u8>, value: i64) {
+    match value {
+        0 => script.push(0x00),
+        -1 | 1..=16 => script.push((0x50 + value) as u8),
+        _ => {
+            let mut magnitude = value.unsigned_abs();
+            let mut bytes = Vec::new();
+            while magnitude > 0 {
+                bytes.push(magnitude as u8);
+                magnitude >>= 8;
+            }
+            let sign = if value < 0 { 0x80 } else { 0x00 };
+            if bytes.last().unwrap() & 0x80 != 0 {
+                bytes.push(sign);
+            } else {
+                *bytes.last_mut().unwrap() |= sign;
+            }
+            push_data(script, &bytes);
+        }
+    }
+}
+
+fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        len if len < 0x4c => script.push(len as u8),
+        len if len <= 0xff => script.extend([0x4c, len as u8]),
+        len if len <= 0xffff => {
+            script.push(0x4d);
+            script.extend((len as u16).to_le_bytes());
+        }
+        len => {
+            script.push(0x4e);
+            script.extend((len as u32).to_le_bytes());
+        }
+    }
+    script.extend(data);
+}
+
+/// Parses Core's comma-separated flag names, returning the consensus flags and
+/// whether any policy flags were named.
+fn parse_flags(names: &str) -> (ScriptVerificationFlags, bool) {
+    let mut flags = VERIFY_NONE;
+    let mut has_policy_flags = false;
+    for name in names.split(',').filter(|name| !name.is_empty()) {
+        match name {
+            "NONE" => {}
+            "P2SH" => flags.insert(VERIFY_P2SH),
+            "DERSIG" => flags.insert(VERIFY_DERSIG),
+            "NULLDUMMY" => flags.insert(VERIFY_NULLDUMMY),
+            "CHECKLOCKTIMEVERIFY" => flags.insert(VERIFY_CHECKLOCKTIMEVERIFY),
+            "CHECKSEQUENCEVERIFY" => flags.insert(VERIFY_CHECKSEQUENCEVERIFY),
+            "WITNESS" => flags.insert(VERIFY_WITNESS),
+            "TAPROOT" => flags.insert(VERIFY_TAPROOT),
+            _ if POLICY_FLAGS.contains(&name) => has_policy_flags = true,
+            _ => panic!("unknown flag {}", name),
+        }
+    }
+    (flags, has_policy_flags)
+}
+
+fn push_compact_size(buffer: &mut Vec<u8>, len: usize) {
+    match len {
+        len if len < 0xfd => buffer.push(len as u8),
+        len if len <= 0xffff => {
+            buffer.push(0xfd);
+            buffer.extend((len as u16).to_le_bytes());
+        }
+        len => {
+            buffer.push(0xfe);
+            buffer.extend((len as u32).to_le_bytes());
+        }
+    }
+}
+
+fn push_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    push_compact_size(buffer, bytes.len());
+    buffer.extend(bytes);
+}
+
+/// Serializes Core's `BuildCreditingTransaction`.
+fn crediting_transaction(script_pubkey: &[u8], amount: i64) -> Vec<u8> {
+    let mut tx = vec![0x01, 0x00, 0x00, 0x00, 0x01];
+    tx.extend([0u8; 32]);
+    tx.extend(u32::MAX.to_le_bytes());
+    push_bytes(&mut tx, &[0x00, 0x00]);
+    tx.extend(u32::MAX.to_le_bytes());
+    tx.push(0x01);
+    tx.extend(amount.to_le_bytes());
+    push_bytes(&mut tx, script_pubkey);
+    tx.extend(0u32.to_le_bytes());
+    tx
+}
+
+/// Serializes Core's `BuildSpendingTransaction`.
+fn spending_transaction(
+    credit_txid: [u8; 32],
+    script_sig: &[u8],
+    witness: &[Vec<u8>],
+    amount: i64,
+) -> Vec<u8> {
+    let mut tx = vec![0x01, 0x00, 0x00, 0x00];
+    if !witness.is_empty() {
+        tx.extend([0x00, 0x01]);
+    }
+    tx.push(0x01);
+    tx.extend(credit_txid);
+    tx.extend(0u32.to_le_bytes());
+    push_bytes(&mut tx, script_sig);
+    tx.extend(u32::MAX.to_le_bytes());
+    tx.push(0x01);
+    tx.extend(amount.to_le_bytes());
+    push_bytes(&mut tx, &[]);
+    if !witness.is_empty() {
+        push_compact_size(&mut tx, witness.len());
+        for item in witness {
+            push_bytes(&mut tx, item);
+        }
+    }
+    tx.extend(0u32.to_le_bytes());
+    tx
+}
+
+#[test]
+fn test_parse_script() {
+    assert_eq!(parse_script(""), Vec::<u8>::new());
+    assert_eq!(parse_script("0 -1 1 16"), vec![0x00, 0x4f, 0x51, 0x60]);
+    assert_eq!(parse_script("17 -17"), vec![0x01, 0x11, 0x01, 0x91]);
+    assert_eq!(
+        parse_script("128 -128"),
+        vec![0x02, 0x80, 0x00, 0x02, 0x80, 0x80]
+    );
+    assert_eq!(parse_script("0x4c 0x01 0x07"), vec![0x4c, 0x01, 0x07]);
+    assert_eq!(parse_script("'Az' EQUAL"), vec![0x02, b'A', b'z', 0x87]);
+    assert_eq!(
+        parse_script("DUP OP_HASH160 RESERVED NOP10"),
+        vec![0x76, 0xa9, 0x50, 0xb9]
+    );
+
+    let mut long = Vec::new();
+    push_data(&mut long, &[0u8; 0x100]);
+    assert_eq!(long[..3], [0x4d, 0x00, 0x01]);
+}
+
+#[test]
+fn test_parse_flags() {
+    assert_eq!(parse_flags(""), (VERIFY_NONE, false));
+    assert_eq!(parse_flags("NONE"), (VERIFY_NONE, false));
+    assert_eq!(
+        parse_flags("P2SH,WITNESS"),
+        (VERIFY_P2SH | VERIFY_WITNESS, false)
+    );
+    assert_eq!(parse_flags("P2SH,STRICTENC"), (VERIFY_P2SH, true));
+}
+
+#[test]
+fn test_script_tests_json() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(SCRIPT_TESTS_PATH);
+    let Json::Array(vectors) = JsonParser::parse(&fs::read_to_string(path).unwrap()) else {
+        panic!("expected an array of test vectors");
+    };
+
+    let mut run = 0;
+    for vector in &vectors {
+        let Json::Array(fields) = vector else {
+            panic!("expected a test vector array");
+        };
+        // Single element entries are comments.
+        if fields.len() == 1 {
+            continue;
+        }
+
+        let (witness, amount, fields) = match &fields[0] {
+            Json::Array(witness) => {
+                let (amount, items) = witness.split_last().unwrap();
+                let Json::Number(amount) = amount else {
+                    panic!("expected a witness amount");
+                };
+                let items: Vec<&str> = items.iter().map(Json::as_str).collect();
+                (items, (amount * 100_000_000.0).round() as i64, &fields[1..])
+            }
+            _ => (Vec::new(), 0, &fields[..]),
+        };
+        let script_sig = fields[0].as_str();
+        let script_pubkey = fields[1].as_str();
+        let expected_ok = fields[3].as_str() == "OK";
+        let description = format!(
+            "[{}, {}, {}]",
+            script_sig,
+            script_pubkey,
+            fields[2].as_str()
+        );
+
+        if [script_sig, script_pubkey]
+            .iter()
+            .chain(&witness)
+            .any(|field| field.contains('#'))
+        {
+            continue;
+        }
+        let (flags, has_policy_flags) = parse_flags(fields[2].as_str());
+        if (has_policy_flags && !expected_ok) || validate_flags(flags).is_err() {
+            continue;
+        }
+
+        let script_pubkey = ScriptPubkey::new(&parse_script(script_pubkey)).unwrap();
+        let witness: Vec<Vec<u8>> = witness
+            .iter()
+            .map(|item| hex::decode(item).unwrap())
+            .collect();
+        let credit =
+            Transaction::new(&crediting_transaction(&script_pubkey.to_bytes(), amount)).unwrap();
+        let spend = Transaction::new(&spending_transaction(
+            credit.txid().to_bytes(),
+            &parse_script(script_sig),
+            &witness,
+            amount,
+        ))
+        .unwrap();
+        let spent_outputs = vec![TxOut::new(&script_pubkey, amount)];
+        let tx_data = PrecomputedTransactionData::new(&spend, &spent_outputs).unwrap();
+
+        let result = verify(
+            &script_pubkey,
+            Some(amount),
+            &spend,
+            0,
+            Some(flags),
+            &tx_data,
+        );
+        if expected_ok {
+            assert!(result.is_ok(), "{} failed: {:?}", description, result);
+        } else {
+            assert!(
+                matches!(
+                    result,
+                    Err(KernelError::ScriptVerify(ScriptVerifyError::Invalid))
+                ),
+                "{} should fail, got {:?}",
+                description,
+                result
+            );
+        }
+        run += 1;
+    }
+
+    assert!(run >= 700, "only {} script test vectors were run", run);
+}
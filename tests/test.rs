@@ -4,19 +4,25 @@ mod common;
 mod tests {
     use crate::common::TempDir;
     use bitcoinkernel::notifications::types::BlockValidationState;
-    use bitcoinkernel::state::chainstate::ProcessBlockHeaderResult;
+    use bitcoinkernel::state::chainstate::{ProcessBlockHeaderResult, ProcessBlockResult};
     use bitcoinkernel::{
-        prelude::*, verify, Block, BlockHash, BlockHeader, BlockSpentOutputs, BlockTreeEntry,
-        BlockValidationStateRef, ChainParams, ChainType, ChainstateManager,
-        ChainstateManagerBuilder, Coin, Context, ContextBuilder, KernelError, Log, Logger,
-        PrecomputedTransactionData, ScriptPubkey, ScriptVerificationFlags, ScriptVerifyError,
-        Transaction, TransactionSpentOutputs, TxIn, TxOut, VERIFY_ALL, VERIFY_ALL_PRE_TAPROOT,
-        VERIFY_CHECKLOCKTIMEVERIFY, VERIFY_CHECKSEQUENCEVERIFY, VERIFY_DERSIG, VERIFY_NONE,
-        VERIFY_NULLDUMMY, VERIFY_P2SH, VERIFY_TAPROOT, VERIFY_WITNESS,
+        block_subsidy, prelude::*, verify, verify_detailed, verify_hex, verify_transaction, Block,
+        BlockFileReader, BlockHash, BlockHeader, BlockSpentOutputs, BlockTreeEntry,
+        BlockValidationResult, BlockValidationStateRef, ChainParams, ChainType, ChainstateManager,
+        ChainstateManagerBuilder, Coin, CoinProvider, Context, ContextBuilder, FeeRate,
+        HasScriptPubkey, KernelError, Log, Logger, PrecomputedTransactionData, ScriptPubkey,
+        ScriptVerificationFlags, ScriptVerifyError, SpentOutputsCoinProvider, Transaction,
+        TransactionSpentOutputs, TxIn, TxOut, TxOutPoint, Txid, UtxoMap, UtxoOutPoint, COIN,
+        COINBASE_MATURITY, VERIFY_ALL, VERIFY_ALL_PRE_TAPROOT, VERIFY_CHECKLOCKTIMEVERIFY,
+        VERIFY_CHECKSEQUENCEVERIFY, VERIFY_DERSIG, VERIFY_NONE, VERIFY_NULLDUMMY, VERIFY_P2SH,
+        VERIFY_TAPROOT, VERIFY_WITNESS,
     };
+    use std::collections::{HashMap, HashSet};
     use std::fs::File;
     use std::io::{BufRead, BufReader};
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::{Arc, Once};
+    use std::time::Duration;
 
     struct TestLog {}
 
@@ -73,8 +79,8 @@ mod tests {
             .with_warning_unset_notification(|_warning| {
                 log::info!("Unsetting warning.");
             })
-            .with_flush_error_notification(|message| {
-                log::info!("Flush error! {}", message);
+            .with_flush_error_notification(|kind, message| {
+                log::info!("Flush error! {} {}", kind, message);
             })
             .with_fatal_error_notification(|message| {
                 log::info!("Fatal error! {}", message);
@@ -128,6 +134,38 @@ mod tests {
         Ok(chainman)
     }
 
+    #[test]
+    fn test_check_block() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let block = Block::new(block_data[0].as_slice()).unwrap();
+        chainman.check_block(&block).unwrap();
+        assert_eq!(chainman.active_chain().height(), 0);
+        assert!(chainman
+            .get_block_tree_entry(&block.hash().to_owned())
+            .is_none());
+
+        // Regtest blocks do not meet the mainnet proof of work limit.
+        let mainnet_dir = TempDir::new("test_check_block_mainnet");
+        let mainnet = Context::for_chain(ChainType::Mainnet).unwrap();
+        let mainnet_chainman =
+            ChainstateManager::builder(&mainnet, mainnet_dir.data_dir(), mainnet_dir.blocks_dir())
+                .unwrap()
+                .chainstate_db_in_memory(true)
+                .block_tree_db_in_memory(true)
+                .build()
+                .unwrap();
+        assert!(matches!(
+            mainnet_chainman.check_block(&block),
+            Err(KernelError::InvalidBlock(
+                BlockValidationResult::InvalidHeader
+            ))
+        ));
+    }
+
     #[test]
     fn test_reindex() {
         let (context, temp_dir) = testing_setup();
@@ -160,6 +198,73 @@ mod tests {
         drop(chainman);
     }
 
+    #[test]
+    fn test_full_reindex() {
+        let (context, temp_dir) = testing_setup();
+
+        let tip_hash = {
+            let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+            chainman.tip_hash()
+        };
+
+        let chainman =
+            ChainstateManager::builder(&context, temp_dir.data_dir(), temp_dir.blocks_dir())
+                .unwrap()
+                .reindex(true)
+                .build()
+                .unwrap();
+        chainman.import_blocks().unwrap();
+        assert_eq!(chainman.height(), 206);
+        assert_eq!(chainman.tip_hash(), tip_hash);
+    }
+
+    #[test]
+    fn test_process_duplicate_block() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let tip_hash = chainman.tip_hash();
+
+        for raw_block in read_block_data().iter().rev().take(3) {
+            let block = Block::new(raw_block.as_slice()).unwrap();
+            let result = chainman.process_block(&block);
+            assert_eq!(result, ProcessBlockResult::Duplicate);
+            assert!(result.is_accepted());
+            assert!(!result.is_new_block());
+            assert!(!result.is_rejected());
+        }
+        assert_eq!(chainman.height(), 206);
+        assert_eq!(chainman.tip_hash(), tip_hash);
+    }
+
+    #[test]
+    fn test_block_hash_at_height() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+
+        for height in [0, 1, 100, chainman.height()] {
+            assert_eq!(
+                chainman.block_hash_at_height(height).unwrap(),
+                chain
+                    .at_height(height as usize)
+                    .unwrap()
+                    .block_hash()
+                    .to_owned()
+            );
+        }
+        assert_eq!(
+            chainman.block_hash_at_height(chainman.height()).unwrap(),
+            chainman.tip_hash()
+        );
+        assert!(chainman
+            .block_hash_at_height(chainman.height() + 1)
+            .is_none());
+        assert!(chainman.block_hash_at_height(-1).is_none());
+        assert!(chainman.block_hash_at_height(i32::MIN).is_none());
+    }
+
     #[test]
     fn test_invalid_block() {
         let (context, temp_dir) = testing_setup();
@@ -280,6 +385,35 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn script_verify_hex() {
+        let spk = "76a9144bfbaf6afb76cc5771bc6404810d1cc041a6933988ac";
+        let tx_valid = "02000000013f7cebd65c27431a90bba7f796914fe8cc2ddfc3f2cbd6f7e5f2fc854534da95000000006b483045022100de1ac3bcdfb0332207c4a91f3832bd2c2915840165f876ab47c5f8996b971c3602201c6c053d750fadde599e6f5c4e1963df0f01fc0d97815e8157e3d59fe09ca30d012103699b464d1d8bc9e47d4fb1cdaa89a1c5783d68363c4dbc4b524ed3d857148617feffffff02836d3c01000000001976a914fc25d6d5c94003bf5b0c7b640a248e2c637fcfb088ac7ada8202000000001976a914fbed3d9b11183209a57999d54d59f67c019e756c88ac6acb0700";
+        verify_hex(spk, tx_valid, 0, None, Some(VERIFY_ALL_PRE_TAPROOT)).unwrap();
+        verify_hex(&spk.to_uppercase(), tx_valid, 0, Some(0), Some(VERIFY_NONE)).unwrap();
+
+        let tx_corrupted_sig = tx_valid.replacen("ab47c5f8", "ab47c6f8", 1);
+        assert!(matches!(
+            verify_hex(spk, &tx_corrupted_sig, 0, None, Some(VERIFY_NONE)),
+            Err(KernelError::ScriptVerify(ScriptVerifyError::Invalid))
+        ));
+        assert!(matches!(
+            verify_hex(spk, tx_valid, 1, None, Some(VERIFY_NONE)),
+            Err(KernelError::ScriptVerify(ScriptVerifyError::TxInputIndex))
+        ));
+        assert!(matches!(
+            verify_hex(&spk[1..], tx_valid, 0, None, None),
+            Err(KernelError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            verify_hex("zz", tx_valid, 0, None, None),
+            Err(KernelError::InvalidHexCharacter {
+                character: 'z',
+                index: 0
+            })
+        ));
+    }
+
     #[test]
     fn script_verify_p2sh_multisig() {
         // Spending a multisig P2SH output using a mainnet tx with id 3cd7f78499632d6f672d8a9412ae756b29c41342954c97846e0d153c7753a37e
@@ -572,6 +706,60 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn script_verify_p2tr_scriptpath_annex() {
+        // Spending a P2TR output via the script-path. The output commits to a
+        // single OP_TRUE leaf under the BIP 341 NUMS internal key, and the
+        // witness is [script, control block, annex].
+        let spk = "5120f855ca43402fb99cde0e3e634b175642561ff584fe76d1686630d8fd2ea93b36";
+        let tx_with_annex = "0200000000010111111111111111111111111111111111111111111111111111111111111111110000000000ffffffff012823000000000000015103015121c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac00350010200000000";
+        let amount = 10000;
+        let base_flags = VERIFY_P2SH | VERIFY_WITNESS | VERIFY_TAPROOT;
+        let outputs = vec![TxOut::new(
+            &ScriptPubkey::try_from(hex::decode(spk).unwrap().as_slice()).unwrap(),
+            amount,
+        )];
+
+        verify_test(spk, tx_with_annex, amount, 0, outputs.clone(), base_flags).unwrap();
+        verify_test(spk, tx_with_annex, amount, 0, outputs.clone(), VERIFY_ALL).unwrap();
+
+        // same spend without the annex
+        let tx_without_annex = "0200000000010111111111111111111111111111111111111111111111111111111111111111110000000000ffffffff012823000000000000015102015121c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac000000000";
+        verify_test(
+            spk,
+            tx_without_annex,
+            amount,
+            0,
+            outputs.clone(),
+            base_flags,
+        )
+        .unwrap();
+
+        // same tx but the last element lacks the 0x50 annex tag, so it is
+        // parsed as the control block and the spend fails
+        let tx_untagged_annex = "0200000000010111111111111111111111111111111111111111111111111111111111111111110000000000ffffffff012823000000000000015103015121c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac00351010200000000";
+        assert!(matches!(
+            verify_test(
+                spk,
+                tx_untagged_annex,
+                amount,
+                0,
+                outputs.clone(),
+                base_flags
+            ),
+            Err(KernelError::ScriptVerify(ScriptVerifyError::Invalid))
+        ));
+        verify_test(
+            spk,
+            tx_untagged_annex,
+            amount,
+            0,
+            outputs,
+            base_flags & !VERIFY_TAPROOT,
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_verify_input_validation() {
         let script_data =
@@ -604,7 +792,14 @@ mod tests {
         ));
 
         // Test Invalid flags
-        let result = verify(&script_pubkey, Some(0), &tx, 0, Some(0xFFFFFFFF), &tx_data);
+        let result = verify(
+            &script_pubkey,
+            Some(0),
+            &tx,
+            0,
+            Some(ScriptVerificationFlags::from(0xFFFFFFFF)),
+            &tx_data,
+        );
         assert!(matches!(
             result,
             Err(KernelError::ScriptVerify(ScriptVerifyError::InvalidFlags))
@@ -644,6 +839,101 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_verify_detailed() {
+        let script_data =
+            hex::decode("76a9144bfbaf6afb76cc5771bc6404810d1cc041a6933988ac").unwrap();
+        let script_pubkey = ScriptPubkey::try_from(script_data.as_slice()).unwrap();
+        let tx_hex = "02000000013f7cebd65c27431a90bba7f796914fe8cc2ddfc3f2cbd6f7e5f2fc854534da95000000006b483045022100de1ac3bcdfb0332207c4a91f3832bd2c2915840165f876ab47c5f8996b971c3602201c6c053d750fadde599e6f5c4e1963df0f01fc0d97815e8157e3d59fe09ca30d012103699b464d1d8bc9e47d4fb1cdaa89a1c5783d68363c4dbc4b524ed3d857148617feffffff02836d3c01000000001976a914fc25d6d5c94003bf5b0c7b640a248e2c637fcfb088ac7ada8202000000001976a914fbed3d9b11183209a57999d54d59f67c019e756c88ac6acb0700";
+        let tx = Transaction::new(hex::decode(tx_hex).unwrap().as_slice()).unwrap();
+
+        // Without spent outputs, taproot rules cannot run.
+        let tx_data = PrecomputedTransactionData::new(&tx, &Vec::<TxOut>::new()).unwrap();
+        assert!(!tx_data.has_spent_outputs());
+        let report = verify_detailed(
+            &script_pubkey,
+            Some(0),
+            &tx,
+            0,
+            Some(VERIFY_ALL_PRE_TAPROOT),
+            &tx_data,
+        )
+        .unwrap();
+        assert_eq!(report.flags(), VERIFY_ALL_PRE_TAPROOT);
+        assert!(!report.spent_outputs_provided());
+        assert!(report.witness_checked());
+        assert!(!report.taproot_checked());
+
+        let report =
+            verify_detailed(&script_pubkey, Some(0), &tx, 0, Some(VERIFY_NONE), &tx_data).unwrap();
+        assert!(report.is_base_only());
+
+        // With spent outputs, the default flags apply every rule.
+        let spent_output = TxOut::new(&script_pubkey, 0);
+        let tx_data =
+            PrecomputedTransactionData::new(&tx, std::slice::from_ref(&spent_output)).unwrap();
+        assert!(tx_data.has_spent_outputs());
+        let report = verify_detailed(&script_pubkey, Some(0), &tx, 0, None, &tx_data).unwrap();
+        assert_eq!(report.flags(), VERIFY_ALL);
+        assert!(report.spent_outputs_provided());
+        assert!(report.taproot_checked());
+    }
+
+    #[test]
+    fn test_verify_transaction() {
+        // The two inputs of mainnet tx 1ba232a8bf936cf24155292c9a4330298278f572bacc78455eb68e3552197c30,
+        // a taproot key-path and a script-path spend
+        let tx_valid  = "02000000000102761402258bf42275f52db288dbbc8fdfe30b35dea86c5425a57feef1a4008b0b0100000000ffffffff3847ba0ccc4e1b63ed2f3b4a677bd247940f4d5669cc91d9a4eb096e7615badc0000000000ffffffff0291ad070000000000225120059715a12766bbbee8529b53dce51fe708e9895f50c6babec988c7917ff5958464d11a0000000000225120bee1246f13735551e5e5c2b5631014501a6c77f8ee2d16d33dc109096f22b2a40140ac4e4af854be645890275c8144869343752d5ceee9b361cfab3de0726c10a449cc7491a295417f7c457961fdde59bde483330364b42fddddeef65cd1d97150fc0440b78c0a5065343d451a93dcb499edd3d8994697932322be5e27fa218f5a99be8484a8ef802c3054dee442baced3c170b8afe18ec9758c860876fe4f06a5e3ccb240984299fc968b71d999354af2e991e089908adec84e1b1f04da8149aa0ffce28311b363dfd2dfc456de77746919c263e95a14952080f433ddb87b13b884812bda4420b5095be39b9f2f96a77235854af7635dd09d0324569e9b3d587fe5fb7c44720cad202b74c2011af089c849383ee527c72325de52df6a788428b68d49e9174053aabaac41c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0675a94484b3d55d76af4a2275d327a47c1ec5c7d2232596a09fc883d40bb237e00000000";
+        let spent_outputs = vec![
+            TxOut::new(
+                &ScriptPubkey::try_from(
+                    hex::decode(
+                        "51207ee3c4ab9c8144be0e39fc849fab95e70da97fb0d70754b34553c25f9d325fa0",
+                    )
+                    .unwrap()
+                    .as_slice(),
+                )
+                .unwrap(),
+                1757828,
+            ),
+            TxOut::new(
+                &ScriptPubkey::try_from(
+                    hex::decode(
+                        "5120e687f4f55e3de5264cf4c4f43b53edb5c26e4adae3a3098ce918a663582785bd",
+                    )
+                    .unwrap()
+                    .as_slice(),
+                )
+                .unwrap(),
+                503185,
+            ),
+        ];
+        let tx = Transaction::new(hex::decode(tx_valid).unwrap().as_slice()).unwrap();
+        verify_transaction(&tx, &spent_outputs, None).unwrap();
+
+        // same tx but with a corrupted signature in the second input
+        let tx_corrupted_sig  = "02000000000102761402258bf42275f52db288dbbc8fdfe30b35dea86c5425a57feef1a4008b0b0100000000ffffffff3847ba0ccc4e1b63ed2f3b4a677bd247940f4d5669cc91d9a4eb096e7615badc0000000000ffffffff0291ad070000000000225120059715a12766bbbee8529b53dce51fe708e9895f50c6babec988c7917ff5958464d11a0000000000225120bee1246f13735551e5e5c2b5631014501a6c77f8ee2d16d33dc109096f22b2a40140ac4e4af854be645890275c8144869343752d5ceee9b361cfab3de0726c10a449cc7491a295417f7c457961fdde59bde483330364b42fddddeef65cd1d97150fc0440b78c0a5065343d451a93dcb499edd3d8994697932322be5e27fa218f5a99be8484a8ef802c3054dee442baced3c170b8afe18ec9758c860876fe4f06a5e3ccb240984299fc968b71d999354af2e991e089908adec84e1b1f04da8149aa0ffce28211b363dfd2dfc456de77746919c263e95a14952080f433ddb87b13b884812bda4420b5095be39b9f2f96a77235854af7635dd09d0324569e9b3d587fe5fb7c44720cad202b74c2011af089c849383ee527c72325de52df6a788428b68d49e9174053aabaac41c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0675a94484b3d55d76af4a2275d327a47c1ec5c7d2232596a09fc883d40bb237e00000000";
+        let tx = Transaction::new(hex::decode(tx_corrupted_sig).unwrap().as_slice()).unwrap();
+        let err = verify_transaction(&tx, &spent_outputs, None).unwrap_err();
+        assert!(matches!(
+            err,
+            KernelError::InputVerify {
+                input_index: 1,
+                error: ScriptVerifyError::Invalid
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Script verification error at input 1: Script verification failed"
+        );
+        assert!(!err.is_retryable());
+
+        assert!(matches!(
+            verify_transaction(&tx, &spent_outputs[..1], None),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+    }
+
     #[test]
     fn test_header_validation() {
         let (context, temp_dir) = testing_setup();
@@ -710,113 +1000,1220 @@ mod tests {
         assert_eq!(tip.ancestor(0).unwrap().block_hash(), genesis_hash);
         assert!(tip.ancestor(-1).is_none());
         assert!(tip.ancestor(tip_height + 1).is_none());
+
+        for entry in chain.iter() {
+            let block = chainman.read_block_data(&entry).unwrap();
+            assert_eq!(
+                entry.raw_header().unwrap(),
+                block.header().consensus_encode().unwrap()
+            );
+        }
     }
 
     #[test]
-    fn test_block_transactions_iterator() {
-        let block_data = read_block_data();
-
-        let block = Block::try_from(block_data[5].as_slice()).unwrap();
-
-        let tx_count_via_iterator = block.transactions().count();
-        assert_eq!(tx_count_via_iterator, block.transaction_count());
+    fn test_chain_iter_with_height() {
+        let (context, temp_dir) = testing_setup();
 
-        let txs: Vec<_> = block.transactions().collect();
-        assert_eq!(txs.len(), block.transaction_count());
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
 
-        for (i, tx) in block.transactions().enumerate() {
-            let tx_via_index = block.transaction(i).unwrap();
-            assert_eq!(tx.input_count(), tx_via_index.input_count());
-            assert_eq!(tx.output_count(), tx_via_index.output_count());
+        let mut count = 0;
+        for (height, entry) in chain.iter_with_height() {
+            assert_eq!(height, entry.height());
+            assert_eq!(
+                chain.at_height(height as usize).unwrap().block_hash(),
+                entry.block_hash()
+            );
+            count += 1;
         }
-
-        let mut iter = block.transactions();
-        let initial_len = iter.len();
-        assert_eq!(initial_len, block.transaction_count());
-
-        iter.next();
-        assert_eq!(iter.len(), initial_len - 1);
-
-        let non_coinbase_txs: Vec<_> = block.transactions().skip(1).collect();
-        assert_eq!(non_coinbase_txs.len(), block.transaction_count() - 1);
+        assert_eq!(count, chain.height() + 1);
+
+        let (height, entry) = chain.iter_with_height().nth(5).unwrap();
+        assert_eq!(height, 5);
+        assert_eq!(entry.height(), 5);
+        let odd: Vec<_> = chain
+            .iter_with_height()
+            .filter(|(height, _)| height % 2 == 1)
+            .collect();
+        assert_eq!(odd[0].0, 1);
+        assert_eq!(odd[1].0, 3);
+        assert_eq!(odd[1].1.height(), 3);
     }
 
     #[test]
-    fn test_block_spent_outputs_iterator() {
+    fn test_chain_iter_take_while() {
         let (context, temp_dir) = testing_setup();
 
         let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
 
-        let active_chain = chainman.active_chain();
-        let block_index_tip = active_chain.tip();
-        let spent_outputs = chainman.read_spent_outputs(&block_index_tip).unwrap();
-
-        let count_via_iterator = spent_outputs.iter().count();
-        assert_eq!(count_via_iterator, spent_outputs.count());
-
-        let tx_spent_vec: Vec<_> = spent_outputs.iter().collect();
-        assert_eq!(tx_spent_vec.len(), spent_outputs.count());
-
-        for (i, tx_spent) in spent_outputs.iter().enumerate() {
-            let tx_spent_via_index = spent_outputs.transaction_spent_outputs(i).unwrap();
-            assert_eq!(tx_spent.count(), tx_spent_via_index.count());
-        }
-
-        let mut iter = spent_outputs.iter();
-        let initial_len = iter.len();
-        assert_eq!(initial_len, spent_outputs.count());
+        let cutoff = chain.at_height(100).unwrap().header().timestamp();
+        let entries: Vec<_> = chain
+            .iter()
+            .take_while(|entry| entry.header().timestamp() < cutoff)
+            .collect();
 
-        if initial_len > 0 {
-            iter.next();
-            assert_eq!(iter.len(), initial_len - 1);
+        assert!(!entries.is_empty());
+        for (height, entry) in entries.iter().enumerate() {
+            assert_eq!(entry.height() as usize, height);
+            assert!(entry.header().timestamp() < cutoff);
         }
+        let stop = chain.at_height(entries.len()).unwrap();
+        assert!(stop.header().timestamp() >= cutoff);
     }
 
     #[test]
-    fn test_transaction_spent_outputs_iterator() {
+    fn test_chain_ranges() {
         let (context, temp_dir) = testing_setup();
 
         let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+        let block_count = chain.height() as usize + 1;
+
+        for n in [1, 2, 3, 7, block_count] {
+            let ranges = chain.ranges(n);
+            assert_eq!(ranges.len(), n);
+            assert_eq!(ranges[0].0, 0);
+            assert_eq!(ranges[n - 1].1, block_count);
+            for pair in ranges.windows(2) {
+                assert_eq!(pair[0].1, pair[1].0);
+                let (first, second) = (pair[0].1 - pair[0].0, pair[1].1 - pair[1].0);
+                assert!(first == second || first == second + 1);
+            }
+        }
 
-        let active_chain = chainman.active_chain();
-        let block_index_tip = active_chain.tip();
-        let spent_outputs = chainman.read_spent_outputs(&block_index_tip).unwrap();
-
-        let tx_spent = spent_outputs.transaction_spent_outputs(0).unwrap();
+        assert_eq!(chain.ranges(block_count + 5).len(), block_count);
+        assert!(chain
+            .ranges(block_count + 5)
+            .iter()
+            .all(|(start, end)| end - start == 1));
+        assert!(chain.ranges(0).is_empty());
+
+        let heights: Vec<usize> = chain
+            .ranges(4)
+            .into_iter()
+            .flat_map(|(start, end)| chain.window(start, end - start))
+            .map(|entry| entry.height() as usize)
+            .collect();
+        assert_eq!(heights, (0..block_count).collect::<Vec<_>>());
+    }
 
-        let count_via_iterator = tx_spent.coins().count();
-        assert_eq!(count_via_iterator, tx_spent.count());
+    #[test]
+    fn test_chain_window() {
+        let (context, temp_dir) = testing_setup();
 
-        let coins: Vec<_> = tx_spent.coins().collect();
-        assert_eq!(coins.len(), tx_spent.count());
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+        let tip_height = chain.height() as usize;
 
-        for (i, coin) in tx_spent.coins().enumerate() {
-            let coin_via_index = tx_spent.coin(i).unwrap();
+        let window = chain.window(100, 10);
+        assert_eq!(window.len(), 10);
+        for (offset, entry) in window.iter().enumerate() {
+            assert_eq!(entry.height() as usize, 100 + offset);
             assert_eq!(
-                coin.confirmation_height(),
-                coin_via_index.confirmation_height()
+                entry.block_hash(),
+                chain.at_height(100 + offset).unwrap().block_hash()
             );
-            assert_eq!(coin.is_coinbase(), coin_via_index.is_coinbase());
-        }
-
-        let mut iter = tx_spent.coins();
-        let initial_len = iter.len();
-        assert_eq!(initial_len, tx_spent.count());
-
-        if initial_len > 0 {
-            iter.next();
-            assert_eq!(iter.len(), initial_len - 1);
         }
 
-        let coinbase_coins: Vec<_> = tx_spent.coins().filter(|coin| coin.is_coinbase()).collect();
+        let truncated = chain.window(tip_height - 2, 10);
+        assert_eq!(truncated.len(), 3);
+        assert_eq!(truncated[2].block_hash(), chain.tip().block_hash());
 
-        for coin in coinbase_coins {
-            assert!(coin.is_coinbase());
-        }
+        assert!(chain.window(tip_height + 1, 10).is_empty());
+        assert!(chain.window(0, 0).is_empty());
+        assert_eq!(chain.window(0, usize::MAX).len(), tip_height + 1);
     }
 
     #[test]
-    fn test_nested_iteration() {
+    fn test_block_tree_entry_next() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+
+        for raw_block in block_data[..200].iter() {
+            let block = Block::new(raw_block.as_slice()).unwrap();
+            assert!(chainman.process_block(&block).is_new_block());
+        }
+        let chain = chainman.active_chain();
+        assert_eq!(chain.height(), 200);
+
+        let mut entry = chain.at_height(0).unwrap();
+        let mut height = 0;
+        while let Some(next) = entry.next(&chain) {
+            height += 1;
+            assert_eq!(next.height(), height);
+            assert_eq!(next.prev().unwrap().block_hash(), entry.block_hash());
+            entry = next;
+        }
+        assert_eq!(height, 200);
+        assert_eq!(entry.block_hash(), chain.tip().block_hash());
+
+        // A header-only entry is not on the active chain.
+        let block = Block::new(block_data[200].as_slice()).unwrap();
+        let result = chainman.process_block_header(&block.header()).unwrap();
+        assert!(matches!(result, ProcessBlockHeaderResult::Valid));
+        let header_only = chainman.get_block_tree_entry(&block.hash()).unwrap();
+        assert!(!chain.contains(&header_only));
+        assert!(header_only.next(&chain).is_none());
+    }
+
+    #[test]
+    fn test_block_tree_entry_nth_ancestor() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+        let tip = chain.tip();
+        let tip_height = tip.height() as usize;
+
+        assert_eq!(tip.nth_ancestor(0).unwrap(), tip);
+        assert_eq!(tip.nth_ancestor(1).unwrap(), tip.prev().unwrap());
+
+        let mut expected = tip;
+        for n in 1..=tip_height {
+            expected = expected.prev().unwrap();
+            assert_eq!(tip.nth_ancestor(n).unwrap(), expected);
+        }
+        assert_eq!(expected.height(), 0);
+
+        assert!(tip.nth_ancestor(tip_height + 1).is_none());
+        assert!(tip.nth_ancestor(usize::MAX).is_none());
+        assert!(chain.at_height(0).unwrap().nth_ancestor(1).is_none());
+    }
+
+    #[test]
+    fn test_block_tree_entry_eq_and_debug() {
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+
+        let tip = chain.tip();
+        let by_height = chain.at_height(tip.height() as usize).unwrap();
+        let by_hash = chainman
+            .get_block_tree_entry(&tip.block_hash().to_owned())
+            .unwrap();
+        assert_eq!(tip, by_height);
+        assert_eq!(tip, by_hash);
+        assert_ne!(tip, tip.prev().unwrap());
+
+        assert_eq!(
+            format!("{:?}", tip),
+            format!(
+                "BlockTreeEntry(height: {}, hash: {})",
+                tip.height(),
+                tip.block_hash()
+            )
+        );
+    }
+
+    #[test]
+    fn test_height_and_tip_hash() {
+        let (context, temp_dir) = testing_setup();
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+
+        let chain = chainman.active_chain();
+        assert_eq!(chainman.height(), 0);
+        assert_eq!(
+            chainman.tip_hash(),
+            chain.at_height(0).unwrap().block_hash().to_owned()
+        );
+
+        let block = Block::new(read_block_data()[0].as_slice()).unwrap();
+        assert!(chainman.process_block(&block).is_new_block());
+        assert_eq!(chainman.height(), 1);
+        assert_eq!(chainman.tip_hash(), block.hash());
+    }
+
+    #[test]
+    fn test_block_locator() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+        assert_eq!(chain.height(), 206);
+
+        let expected_heights = [
+            206, 205, 204, 203, 202, 201, 200, 199, 198, 197, 196, 195, 193, 189, 181, 165, 133,
+            69, 0,
+        ];
+        let locator = chainman.block_locator();
+        assert_eq!(locator.len(), expected_heights.len());
+        for (hash, height) in locator.iter().zip(expected_heights) {
+            assert_eq!(
+                *hash,
+                chain.at_height(height).unwrap().block_hash().to_owned()
+            );
+        }
+        assert_eq!(
+            *locator.last().unwrap(),
+            chain.at_height(0).unwrap().block_hash().to_owned()
+        );
+    }
+
+    #[test]
+    fn test_read_while_processing() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let chainman = Arc::new(
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap(),
+        );
+        let done = Arc::new(AtomicBool::new(false));
+
+        let reader = {
+            let chainman = Arc::clone(&chainman);
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                // Read at least once, even if processing has already
+                // finished by the time this thread is scheduled.
+                let mut reads = 0;
+                loop {
+                    let finished = done.load(Ordering::Acquire);
+                    let tip = chainman.active_chain().tip();
+                    let block = chainman.read_block_data(&tip).unwrap();
+                    assert_eq!(block.hash().to_bytes(), tip.block_hash().to_bytes());
+                    reads += 1;
+                    if finished {
+                        break reads;
+                    }
+                }
+            })
+        };
+
+        for raw_block in block_data.iter() {
+            let block = Block::new(raw_block.as_slice()).unwrap();
+            assert!(chainman.process_block(&block).is_new_block());
+        }
+        done.store(true, Ordering::Release);
+
+        assert!(reader.join().unwrap() > 0);
+        assert_eq!(chainman.active_chain().height() as usize, block_data.len());
+    }
+
+    #[test]
+    fn test_wait_for_tip_change() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        assert!(chainman
+            .wait_for_tip_change(Duration::from_millis(10))
+            .is_none());
+
+        let target_height = block_data.len() as i32;
+        std::thread::scope(|scope| {
+            let processing = scope.spawn(|| {
+                for raw_block in block_data.iter() {
+                    let block = Block::new(raw_block.as_slice()).unwrap();
+                    assert!(chainman.process_block(&block).is_new_block());
+                }
+            });
+
+            // Stop waiting once processing has finished, so a panic in the
+            // processing thread fails the test instead of hanging it.
+            let mut last_height = 0;
+            while chainman.height() < target_height && !processing.is_finished() {
+                if let Some(tip) = chainman.wait_for_tip_change(Duration::from_secs(1)) {
+                    assert!(tip.height() > last_height);
+                    last_height = tip.height();
+                }
+            }
+        });
+        assert_eq!(chainman.height(), target_height);
+    }
+
+    #[test]
+    fn test_chain_shared_across_threads() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let chain = chainman.active_chain();
+        let target_height = block_data.len() as i32;
+
+        std::thread::scope(|scope| {
+            let readers: Vec<_> = (0..4)
+                .map(|_| {
+                    let chainman = &chainman;
+                    scope.spawn(move || {
+                        while chain.height() < target_height {
+                            let tip = chain.tip();
+                            let entry = chainman
+                                .get_block_tree_entry(&tip.block_hash().to_owned())
+                                .unwrap();
+                            assert_eq!(entry, tip);
+                            let block = chainman.read_block_data(&entry).unwrap();
+                            assert_eq!(block.hash().to_bytes(), tip.block_hash().to_bytes());
+                        }
+                        chain.tip().height()
+                    })
+                })
+                .collect();
+
+            for raw_block in block_data.iter() {
+                let block = Block::new(raw_block.as_slice()).unwrap();
+                assert!(chainman.process_block(&block).is_new_block());
+            }
+
+            for reader in readers {
+                assert_eq!(reader.join().unwrap(), target_height);
+            }
+        });
+    }
+
+    #[test]
+    fn test_chain_view_sees_new_blocks() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let (first, rest) = block_data.split_at(10);
+        for raw_block in first {
+            let block = Block::new(raw_block.as_slice()).unwrap();
+            assert!(chainman.process_block(&block).is_new_block());
+        }
+
+        let chain = chainman.active_chain();
+        assert_eq!(chain.height(), 10);
+        assert!(chain.at_height(11).is_none());
+
+        for raw_block in rest {
+            let block = Block::new(raw_block.as_slice()).unwrap();
+            assert!(chainman.process_block(&block).is_new_block());
+        }
+
+        assert_eq!(chain.height() as usize, block_data.len());
+        assert!(chain.at_height(11).is_some());
+        assert_eq!(
+            chain.tip().block_hash(),
+            chainman.active_chain().tip().block_hash()
+        );
+    }
+
+    #[test]
+    fn test_chainstate_manager_outlives_context() {
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let block_data = read_block_data();
+        assert_eq!(Arc::strong_count(&context), 1);
+        drop(context);
+
+        let tip = chainman.active_chain().tip();
+        assert_eq!(tip.height() as usize, block_data.len());
+        let block = chainman.read_block_data(&tip).unwrap();
+        assert_eq!(
+            block.consensus_encode().unwrap(),
+            block_data[block_data.len() - 1]
+        );
+        let spent_outputs = chainman.read_spent_outputs(&tip).unwrap();
+        spent_outputs.matches_block(&block).unwrap();
+
+        // Processing a known block still runs through the kernel and its
+        // notification callbacks.
+        assert!(chainman.process_block(&block).is_duplicate());
+    }
+
+    #[test]
+    fn test_reopen_after_drop() {
+        let (context, temp_dir) = testing_setup();
+
+        let (height, tip_hash) = {
+            let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+            let tip = chainman.active_chain().tip();
+            (tip.height(), tip.block_hash().to_bytes())
+        };
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let tip = chainman.active_chain().tip();
+        assert_eq!(tip.height(), height);
+        assert_eq!(tip.block_hash().to_bytes(), tip_hash);
+    }
+
+    #[test]
+    fn test_spawn_block_stream() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = Arc::new(setup_chainman_with_blocks(&context, &temp_dir).unwrap());
+        let chain = chainman.active_chain();
+
+        let hashes: Vec<_> = Arc::clone(&chainman)
+            .spawn_block_stream(0)
+            .into_iter()
+            .map(|block| block.unwrap().hash().to_bytes())
+            .collect();
+        assert_eq!(hashes.len(), chain.height() as usize + 1);
+        for (height, hash) in hashes.iter().enumerate() {
+            assert_eq!(
+                *hash,
+                chain.at_height(height).unwrap().block_hash().to_bytes()
+            );
+        }
+
+        let tail: Vec<_> = Arc::clone(&chainman)
+            .spawn_block_stream(chain.height() as usize - 1)
+            .into_iter()
+            .collect();
+        assert_eq!(tail.len(), 2);
+
+        let receiver = Arc::clone(&chainman).spawn_block_stream(0);
+        assert!(receiver.recv().unwrap().is_ok());
+        drop(receiver);
+
+        assert_eq!(
+            Arc::clone(&chainman)
+                .spawn_block_stream(chain.height() as usize + 1)
+                .into_iter()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_par_read_blocks() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = Arc::new(setup_chainman_with_blocks(&context, &temp_dir).unwrap());
+        let chain = chainman.active_chain();
+        let tip_height = chain.height() as usize;
+
+        let hashes: Vec<_> = Arc::clone(&chainman)
+            .par_read_blocks(0, tip_height + 1, 4)
+            .map(|block| block.unwrap().hash().to_bytes())
+            .collect();
+        assert_eq!(hashes.len(), tip_height + 1);
+        for (height, hash) in hashes.iter().enumerate() {
+            assert_eq!(
+                *hash,
+                chain.at_height(height).unwrap().block_hash().to_bytes()
+            );
+        }
+
+        // The range is clamped to the active chain's tip.
+        let tail: Vec<_> = Arc::clone(&chainman)
+            .par_read_blocks(tip_height - 1, tip_height + 10, 3)
+            .collect();
+        assert_eq!(tail.len(), 2);
+
+        assert_eq!(Arc::clone(&chainman).par_read_blocks(10, 20, 0).count(), 10);
+        assert_eq!(Arc::clone(&chainman).par_read_blocks(5, 5, 2).count(), 0);
+
+        // Dropping the iterator early stops the readers.
+        let mut blocks = Arc::clone(&chainman).par_read_blocks(0, tip_height + 1, 2);
+        assert!(blocks.next().unwrap().is_ok());
+        drop(blocks);
+    }
+
+    #[test]
+    fn test_chain_iter_snapshots_tip() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        for raw_block in &block_data[..10] {
+            let block = Block::new(raw_block.as_slice()).unwrap();
+            assert!(chainman.process_block(&block).is_new_block());
+        }
+
+        let chain = chainman.active_chain();
+        let mut entries = chain.iter();
+        assert_eq!(entries.next().unwrap().height(), 0);
+
+        // Blocks connected after the iterator was created are not yielded.
+        let block = Block::new(block_data[10].as_slice()).unwrap();
+        assert!(chainman.process_block(&block).is_new_block());
+        assert_eq!(chain.height(), 11);
+        assert_eq!(entries.count(), 10);
+
+        assert_eq!(chain.iter().count(), 12);
+    }
+
+    #[test]
+    fn test_read_spent_outputs_range() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+        let tip_height = chain.height() as usize;
+
+        let counts: Vec<_> = chainman
+            .read_spent_outputs_range(0, tip_height + 1)
+            .map(|spent_outputs| spent_outputs.unwrap().count())
+            .collect();
+        assert_eq!(counts.len(), tip_height + 1);
+        assert_eq!(counts[0], 0);
+        for (height, count) in counts.iter().enumerate() {
+            let entry = chain.at_height(height).unwrap();
+            assert_eq!(*count, chainman.read_spent_outputs(&entry).unwrap().count());
+        }
+
+        // The range is clamped to the active chain's tip.
+        assert_eq!(
+            chainman
+                .read_spent_outputs_range(tip_height - 1, tip_height + 10)
+                .count(),
+            2
+        );
+        assert_eq!(chainman.read_spent_outputs_range(5, 5).count(), 0);
+
+        let pairs: Vec<_> = chainman
+            .read_block_and_spent_range(0, tip_height + 1)
+            .map(|item| item.unwrap())
+            .collect();
+        assert_eq!(pairs.len(), tip_height + 1);
+        assert!(pairs[0].1.is_none());
+        for (height, (block, spent_outputs)) in pairs.iter().enumerate().skip(1) {
+            let entry = chain.at_height(height).unwrap();
+            assert_eq!(block.hash(), entry.block_hash().to_owned());
+            assert_eq!(
+                spent_outputs.as_ref().unwrap().count(),
+                block.transaction_count() - 1
+            );
+        }
+    }
+
+    /// Hash, transaction count, total output value and total fees of a block.
+    fn block_summary(chainman: &ChainstateManager, block: &Block) -> ([u8; 32], usize, i64, i64) {
+        let entry = chainman
+            .get_block_tree_entry(&block.hash())
+            .expect("block is indexed");
+        let output_value = block
+            .transactions()
+            .flat_map(|tx| {
+                tx.outputs()
+                    .map(|output| output.value())
+                    .collect::<Vec<_>>()
+            })
+            .sum();
+        let fees = if entry.height() == 0 {
+            0
+        } else {
+            let spent_outputs = chainman.read_spent_outputs(&entry).unwrap();
+            block
+                .transactions()
+                .skip(1)
+                .zip(spent_outputs.iter())
+                .map(|(tx, spent)| tx.fee(&spent).unwrap())
+                .sum()
+        };
+        (
+            block.hash().to_bytes(),
+            block.transaction_count(),
+            output_value,
+            fees,
+        )
+    }
+
+    #[test]
+    fn test_parallel_block_analysis_matches_sequential() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = Arc::new(setup_chainman_with_blocks(&context, &temp_dir).unwrap());
+        let chain = chainman.active_chain();
+        let end_height = chain.height() as usize + 1;
+
+        let sequential: Vec<_> = (0..end_height)
+            .map(|height| {
+                let block = chainman
+                    .read_block_data(&chain.at_height(height).unwrap())
+                    .unwrap();
+                block_summary(&chainman, &block)
+            })
+            .collect();
+        assert!(sequential.iter().any(|(_, _, _, fees)| *fees > 0));
+
+        for threads in [1, 2, 4, 8] {
+            let parallel: Vec<_> = Arc::clone(&chainman)
+                .par_read_blocks(0, end_height, threads)
+                .map(|block| block_summary(&chainman, &block.unwrap()))
+                .collect();
+            assert_eq!(
+                parallel, sequential,
+                "par_read_blocks with {threads} threads"
+            );
+
+            // Analyse interleaved heights on each thread and merge the results.
+            let mut merged = std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..threads)
+                    .map(|offset| {
+                        let chainman = &chainman;
+                        scope.spawn(move || {
+                            (offset..end_height)
+                                .step_by(threads)
+                                .map(|height| {
+                                    let entry = chainman.active_chain().at_height(height).unwrap();
+                                    let block = chainman.read_block_data(&entry).unwrap();
+                                    (height, block_summary(chainman, &block))
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().unwrap())
+                    .collect::<Vec<_>>()
+            });
+            merged.sort_by_key(|(height, _)| *height);
+            let merged: Vec<_> = merged.into_iter().map(|(_, summary)| summary).collect();
+            assert_eq!(merged, sequential, "{threads} analysis threads");
+        }
+    }
+
+    #[test]
+    fn test_tip_header() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let tip = chainman.active_chain().tip();
+        let tip_block = chainman.read_block_data(&tip).unwrap();
+
+        let header = chainman.tip_header().unwrap();
+        assert_eq!(header, tip_block.header().consensus_encode().unwrap());
+        assert_eq!(
+            BlockHeader::new(&header).unwrap().hash().to_bytes(),
+            tip.block_hash().to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_block_sigop_cost() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+
+        let sigop_cost_at = |height: usize| {
+            let entry = chain.at_height(height).unwrap();
+            let block = chainman.read_block_data(&entry).unwrap();
+            let spent_outputs = chainman.read_spent_outputs(&entry).unwrap();
+            block.total_sigop_cost(&spent_outputs).unwrap()
+        };
+
+        // Twenty transactions each spending a single P2WPKH output.
+        assert_eq!(sigop_cost_at(202), 20);
+        // A transaction spending two P2WPKH outputs.
+        assert_eq!(sigop_cost_at(206), 2);
+
+        let entry = chain.at_height(202).unwrap();
+        let block = chainman.read_block_data(&entry).unwrap();
+        let other_spent_outputs = chainman
+            .read_spent_outputs(&chain.at_height(206).unwrap())
+            .unwrap();
+        assert!(matches!(
+            block.total_sigop_cost(&other_spent_outputs),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+    }
+
+    #[test]
+    fn test_block_consensus_decode_stream() {
+        let block_data = read_block_data();
+        let stream: Vec<u8> = block_data.iter().flatten().copied().collect();
+        let mut cursor = std::io::Cursor::new(stream);
+
+        for raw_block in block_data.iter() {
+            let block = Block::consensus_decode(&mut cursor).unwrap();
+            assert_eq!(block.consensus_encode().unwrap(), *raw_block);
+        }
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+        assert!(Block::consensus_decode(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn test_block_file_reader() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        let mut file = Vec::new();
+        for raw_block in block_data.iter() {
+            file.extend_from_slice(&ChainType::Regtest.magic());
+            file.extend_from_slice(&(raw_block.len() as u32).to_le_bytes());
+            file.extend_from_slice(raw_block);
+        }
+        file.extend_from_slice(&[0u8; 32]);
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let mut count = 0;
+        for block in BlockFileReader::new(std::io::Cursor::new(&file), ChainType::Regtest) {
+            assert!(chainman.process_block(&block.unwrap()).is_new_block());
+            count += 1;
+        }
+        assert_eq!(count, block_data.len());
+        assert_eq!(chainman.active_chain().height() as usize, block_data.len());
+
+        // Only the records are obfuscated; Bitcoin Core writes the padding
+        // as plain zeros.
+        let key = [0x5a, 0x01, 0xff, 0x10, 0x00, 0x33, 0x7e, 0x80];
+        let records = file.len() - 32;
+        let obfuscated: Vec<u8> = file
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                if i < records {
+                    byte ^ key[i % 8]
+                } else {
+                    *byte
+                }
+            })
+            .collect();
+        let blocks: Vec<_> =
+            BlockFileReader::new(std::io::Cursor::new(obfuscated), ChainType::Regtest)
+                .xor_key(key)
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(blocks.len(), block_data.len());
+
+        let mut mainnet = BlockFileReader::new(std::io::Cursor::new(&file), ChainType::Mainnet);
+        assert!(matches!(
+            mainnet.next(),
+            Some(Err(KernelError::InvalidMagic { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_import_blocks_from_dir() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        // Split the blocks across two files to check they are read in order.
+        let source = TempDir::new("test_import_blocks_from_dir");
+        let split = block_data.len() / 2;
+        for (number, blocks) in [&block_data[..split], &block_data[split..]]
+            .iter()
+            .enumerate()
+        {
+            let mut file = Vec::new();
+            for raw_block in blocks.iter() {
+                file.extend_from_slice(&ChainType::Regtest.magic());
+                file.extend_from_slice(&(raw_block.len() as u32).to_le_bytes());
+                file.extend_from_slice(raw_block);
+            }
+            let path =
+                std::path::Path::new(source.data_dir()).join(format!("blk{:05}.dat", number));
+            std::fs::write(path, file).unwrap();
+        }
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let imported = chainman.import_blocks_from_dir(source.data_dir()).unwrap();
+        assert_eq!(imported as usize, block_data.len());
+        assert_eq!(chainman.active_chain().height() as usize, block_data.len());
+
+        // Known blocks are skipped, and directories without block files are
+        // a no-op.
+        assert_eq!(
+            chainman.import_blocks_from_dir(source.data_dir()).unwrap(),
+            0
+        );
+        assert_eq!(
+            chainman
+                .import_blocks_from_dir(source.blocks_dir())
+                .unwrap(),
+            0
+        );
+        assert!(chainman
+            .import_blocks_from_dir(&format!("{}/missing", source.data_dir()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_import_blocks_from_kernel_blocks_dir() {
+        let (source_context, source_dir) = testing_setup();
+        let source = setup_chainman_with_blocks(&source_context, &source_dir).unwrap();
+        let source_height = source.active_chain().height();
+
+        // The kernel obfuscates its block files with the key in xor.dat and
+        // pre-allocates them, so the import has to stop at the zero padding.
+        assert!(std::path::Path::new(source_dir.blocks_dir())
+            .join("xor.dat")
+            .exists());
+
+        let (context, temp_dir) = testing_setup();
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let imported = chainman
+            .import_blocks_from_dir(source_dir.blocks_dir())
+            .unwrap();
+        assert_eq!(imported as i32, source_height);
+        assert_eq!(chainman.tip_hash(), source.tip_hash());
+    }
+
+    #[test]
+    fn test_import_blocks_from_dir_out_of_order() {
+        let (context, temp_dir) = testing_setup();
+        let block_data = read_block_data();
+
+        // The later half of the chain goes into the lower numbered file and
+        // each file is written in reverse, so almost every block is read
+        // before its parent.
+        let source = TempDir::new("test_import_blocks_from_dir_out_of_order");
+        let split = block_data.len() / 2;
+        for (number, blocks) in [&block_data[split..], &block_data[..split]]
+            .iter()
+            .enumerate()
+        {
+            let mut file = Vec::new();
+            for raw_block in blocks.iter().rev() {
+                file.extend_from_slice(&ChainType::Regtest.magic());
+                file.extend_from_slice(&(raw_block.len() as u32).to_le_bytes());
+                file.extend_from_slice(raw_block);
+            }
+            let path =
+                std::path::Path::new(source.data_dir()).join(format!("blk{:05}.dat", number));
+            std::fs::write(path, file).unwrap();
+        }
+
+        let chainman =
+            ChainstateManager::new(&context, temp_dir.data_dir(), temp_dir.blocks_dir()).unwrap();
+        let imported = chainman.import_blocks_from_dir(source.data_dir()).unwrap();
+        assert_eq!(imported as usize, block_data.len());
+        assert_eq!(chainman.active_chain().height() as usize, block_data.len());
+    }
+
+    #[test]
+    fn test_block_file_reader_stats() {
+        let block_data = read_block_data();
+
+        let mut file = Vec::new();
+        for raw_block in block_data[..3].iter() {
+            file.extend_from_slice(&ChainType::Regtest.magic());
+            file.extend_from_slice(&(raw_block.len() as u32).to_le_bytes());
+            file.extend_from_slice(raw_block);
+        }
+        file.extend_from_slice(&[0u8; 32]);
+
+        let mut reader = BlockFileReader::new(std::io::Cursor::new(&file), ChainType::Regtest);
+        let mut expected_bytes = 0;
+        for raw_block in block_data[..3].iter() {
+            assert!(reader.next().unwrap().is_ok());
+            expected_bytes += 8 + raw_block.len() as u64;
+            assert_eq!(reader.stats().bytes_read(), expected_bytes);
+        }
+        assert!(reader.next().is_none());
+
+        // Reading the zeroed padding consumes one more magic.
+        let stats = reader.stats();
+        assert_eq!(stats.blocks_read(), 3);
+        assert_eq!(stats.bytes_read(), expected_bytes + 4);
+    }
+
+    #[test]
+    fn test_block_file_reader_from_chainstate_manager() {
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        // The regtest blocks are processed in height order after the genesis
+        // block, so the first block file holds the active chain in order.
+        let mut reader = BlockFileReader::from_chainstate_manager(&chainman, 0).unwrap();
+        let blocks: Vec<Block> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+        let chain = chainman.active_chain();
+        assert_eq!(blocks.len(), chain.height() as usize + 1);
+
+        // The kernel pre-allocates its block files and obfuscates them with
+        // the key in xor.dat, so iteration must stop cleanly at the zero
+        // padding rather than at the end of the file.
+        let blocks_dir = std::path::Path::new(temp_dir.blocks_dir());
+        assert!(blocks_dir.join("xor.dat").exists());
+        let file_len = std::fs::metadata(blocks_dir.join("blk00000.dat"))
+            .unwrap()
+            .len();
+        assert!(reader.stats().bytes_read() < file_len);
+        assert!(reader.next().is_none());
+        for (block, entry) in blocks.iter().zip(chain.iter()) {
+            assert_eq!(block.hash(), entry.block_hash().to_owned());
+        }
+
+        assert!(matches!(
+            BlockFileReader::from_chainstate_manager(&chainman, 99_999),
+            Err(KernelError::Internal(_))
+        ));
+    }
+
+    #[test]
+    fn test_block_file_reader_open_while_locked() {
+        let (context, temp_dir) = testing_setup();
+
+        // The manager holds the data directory lock while the files are read.
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let hashes: Vec<_> = BlockFileReader::open(temp_dir.blocks_dir(), 0, ChainType::Regtest)
+            .unwrap()
+            .map(|block| block.unwrap().hash())
+            .collect();
+        let chain = chainman.active_chain();
+        assert_eq!(hashes.len(), chain.height() as usize + 1);
+        assert_eq!(hashes.last().unwrap(), &chainman.tip_hash());
+
+        assert!(matches!(
+            BlockFileReader::open(temp_dir.blocks_dir(), 0, ChainType::Mainnet)
+                .unwrap()
+                .next(),
+            Some(Err(KernelError::InvalidMagic { .. }))
+        ));
+        assert!(matches!(
+            BlockFileReader::open(temp_dir.blocks_dir(), 1, ChainType::Regtest),
+            Err(KernelError::Internal(_))
+        ));
+    }
+
+    #[test]
+    fn test_block_transactions_iterator() {
+        let block_data = read_block_data();
+
+        let block = Block::try_from(block_data[5].as_slice()).unwrap();
+
+        let tx_count_via_iterator = block.transactions().count();
+        assert_eq!(tx_count_via_iterator, block.transaction_count());
+
+        let txs: Vec<_> = block.transactions().collect();
+        assert_eq!(txs.len(), block.transaction_count());
+
+        for (i, tx) in block.transactions().enumerate() {
+            let tx_via_index = block.transaction(i).unwrap();
+            assert_eq!(tx.input_count(), tx_via_index.input_count());
+            assert_eq!(tx.output_count(), tx_via_index.output_count());
+        }
+
+        let mut iter = block.transactions();
+        let initial_len = iter.len();
+        assert_eq!(initial_len, block.transaction_count());
+
+        iter.next();
+        assert_eq!(iter.len(), initial_len - 1);
+
+        let non_coinbase_txs: Vec<_> = block.transactions().skip(1).collect();
+        assert_eq!(non_coinbase_txs.len(), block.transaction_count() - 1);
+    }
+
+    #[test]
+    fn test_block_spent_outputs_iterator() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let active_chain = chainman.active_chain();
+        let block_index_tip = active_chain.tip();
+        let spent_outputs = chainman.read_spent_outputs(&block_index_tip).unwrap();
+
+        let count_via_iterator = spent_outputs.iter().count();
+        assert_eq!(count_via_iterator, spent_outputs.count());
+
+        let tx_spent_vec: Vec<_> = spent_outputs.iter().collect();
+        assert_eq!(tx_spent_vec.len(), spent_outputs.count());
+
+        for (i, tx_spent) in spent_outputs.iter().enumerate() {
+            let tx_spent_via_index = spent_outputs.transaction_spent_outputs(i).unwrap();
+            assert_eq!(tx_spent.count(), tx_spent_via_index.count());
+        }
+
+        let mut iter = spent_outputs.iter();
+        let initial_len = iter.len();
+        assert_eq!(initial_len, spent_outputs.count());
+
+        if initial_len > 0 {
+            iter.next();
+            assert_eq!(iter.len(), initial_len - 1);
+        }
+    }
+
+    #[test]
+    fn test_spent_outputs_debug() {
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let tip = chainman.active_chain().tip();
+        let spent_outputs = chainman.read_spent_outputs(&tip).unwrap();
+
+        let mut coins = 0;
+        let mut value = 0;
+        for tx_spent in spent_outputs.iter() {
+            let tx_value: i64 = tx_spent.coins().map(|coin| coin.value()).sum();
+            assert_eq!(
+                format!("{:?}", tx_spent),
+                format!(
+                    "TransactionSpentOutputs(coins: {}, value: {})",
+                    tx_spent.count(),
+                    tx_value
+                )
+            );
+            coins += tx_spent.count();
+            value += tx_value;
+        }
+
+        let expected = format!(
+            "BlockSpentOutputs(transactions: {}, coins: {}, value: {})",
+            spent_outputs.count(),
+            coins,
+            value
+        );
+        assert_eq!(format!("{:?}", spent_outputs), expected);
+        assert_eq!(format!("{:?}", spent_outputs.as_ref()), expected);
+    }
+
+    #[test]
+    fn test_coin_debug() {
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let tip = chainman.active_chain().tip();
+        let spent_outputs = chainman.read_spent_outputs(&tip).unwrap();
+        let tx_spent = spent_outputs.transaction_spent_outputs(0).unwrap();
+        let coin = tx_spent.coin(0).unwrap();
+
+        let expected = format!(
+            "Coin(confirmation_height: {}, is_coinbase: {}, output: {:?})",
+            coin.confirmation_height(),
+            coin.is_coinbase(),
+            coin.output()
+        );
+        assert_eq!(format!("{:?}", coin), expected);
+        assert_eq!(format!("{:?}", coin.to_owned()), expected);
+        assert!(expected.contains(&format!("value: {}", coin.value())));
+    }
+
+    #[test]
+    fn test_coin_refs_outlive_parent_refs() {
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let tip = chainman.active_chain().tip();
+        let spent_outputs = chainman.read_spent_outputs(&tip).unwrap();
+
+        // The coins and their outputs borrow from `spent_outputs`, not from
+        // the per-transaction references dropped at the end of each closure.
+        let outputs: Vec<_> = spent_outputs
+            .iter()
+            .flat_map(|tx_spent| tx_spent.coins().map(|coin| coin.output()))
+            .collect();
+        let first = spent_outputs
+            .transaction_spent_outputs(0)
+            .unwrap()
+            .coin(0)
+            .unwrap();
+
+        let scripts: Vec<_> = outputs
+            .iter()
+            .map(|output| output.script_pubkey())
+            .collect();
+        let coin_scripts: Vec<_> = spent_outputs
+            .iter()
+            .flat_map(|tx_spent| tx_spent.coins().map(|coin| coin.script_pubkey()))
+            .collect();
+
+        let expected: usize = spent_outputs.iter().map(|tx_spent| tx_spent.count()).sum();
+        assert_eq!(outputs.len(), expected);
+        assert_eq!(outputs[0].value(), first.value());
+        assert_eq!(scripts.len(), coin_scripts.len());
+        for (script, coin_script) in scripts.iter().zip(coin_scripts.iter()) {
+            assert_eq!(script.to_bytes(), coin_script.to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_has_script_pubkey() {
+        fn script_bytes(item: &impl HasScriptPubkey) -> Vec<u8> {
+            item.locking_script().to_bytes()
+        }
+
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let tip = chainman.active_chain().tip();
+        let spent_outputs = chainman.read_spent_outputs(&tip).unwrap();
+        let tx_spent = spent_outputs.transaction_spent_outputs(0).unwrap();
+        let coin = tx_spent.coin(0).unwrap();
+        let expected = coin.output().script_pubkey().to_bytes();
+
+        assert_eq!(script_bytes(&coin), expected);
+        assert_eq!(script_bytes(&coin.to_owned()), expected);
+        assert_eq!(script_bytes(&coin.output()), expected);
+        assert_eq!(script_bytes(&coin.output().to_owned()), expected);
+    }
+
+    #[test]
+    fn test_transaction_spent_outputs_iterator() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let active_chain = chainman.active_chain();
+        let block_index_tip = active_chain.tip();
+        let spent_outputs = chainman.read_spent_outputs(&block_index_tip).unwrap();
+
+        let tx_spent = spent_outputs.transaction_spent_outputs(0).unwrap();
+
+        let count_via_iterator = tx_spent.coins().count();
+        assert_eq!(count_via_iterator, tx_spent.count());
+
+        let coins: Vec<_> = tx_spent.coins().collect();
+        assert_eq!(coins.len(), tx_spent.count());
+
+        for (i, coin) in tx_spent.coins().enumerate() {
+            let coin_via_index = tx_spent.coin(i).unwrap();
+            assert_eq!(
+                coin.confirmation_height(),
+                coin_via_index.confirmation_height()
+            );
+            assert_eq!(coin.is_coinbase(), coin_via_index.is_coinbase());
+        }
+
+        let mut iter = tx_spent.coins();
+        let initial_len = iter.len();
+        assert_eq!(initial_len, tx_spent.count());
+
+        if initial_len > 0 {
+            iter.next();
+            assert_eq!(iter.len(), initial_len - 1);
+        }
+
+        let coinbase_coins: Vec<_> = tx_spent.coins().filter(|coin| coin.is_coinbase()).collect();
+
+        for coin in coinbase_coins {
+            assert!(coin.is_coinbase());
+        }
+    }
+
+    #[test]
+    fn test_transaction_spent_outputs_coin_accessors() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let active_chain = chainman.active_chain();
+        let block_index_tip = active_chain.tip();
+        let spent_outputs = chainman.read_spent_outputs(&block_index_tip).unwrap();
+
+        let tx_spent = spent_outputs.transaction_spent_outputs(0).unwrap();
+
+        for coin in tx_spent.coins() {
+            assert_eq!(coin.value(), coin.output().value());
+            assert_eq!(
+                coin.script_pubkey().to_bytes(),
+                coin.output().script_pubkey().to_bytes()
+            );
+
+            if coin.is_coinbase() {
+                assert_eq!(
+                    coin.maturity_height(),
+                    Some(coin.confirmation_height() + COINBASE_MATURITY)
+                );
+            } else {
+                assert_eq!(coin.maturity_height(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nested_iteration() {
         let (context, temp_dir) = testing_setup();
 
         let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
@@ -853,6 +2250,380 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transaction_fee() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let active_chain = chainman.active_chain();
+
+        let block_index = active_chain.at_height(202).unwrap();
+        let block = chainman.read_block_data(&block_index).unwrap();
+        let spent_outputs = chainman.read_spent_outputs(&block_index).unwrap();
+
+        let mut total_fees = 0;
+        for (tx, spent) in block.transactions().skip(1).zip(spent_outputs.iter()) {
+            let fee = tx.fee(&spent).unwrap();
+            assert_eq!(fee, 141);
+            total_fees += fee;
+        }
+
+        // The coinbase claims the 25 BTC regtest subsidy plus every fee.
+        let coinbase = block.transaction(0).unwrap();
+        let coinbase_value: i64 = coinbase.outputs().map(|output| output.value()).sum();
+        assert_eq!(coinbase_value, 25 * COIN + total_fees);
+
+        let tx1 = block.transaction(1).unwrap();
+        let tx2 = block.transaction(2).unwrap();
+        assert_eq!(tx1.weight().unwrap(), 560);
+        assert_eq!(tx2.weight().unwrap(), 561);
+        let rate1 = tx1
+            .fee_rate(&spent_outputs.transaction_spent_outputs(0).unwrap())
+            .unwrap();
+        let rate2 = tx2
+            .fee_rate(&spent_outputs.transaction_spent_outputs(1).unwrap())
+            .unwrap();
+        assert_eq!(rate1, FeeRate::from_fee_and_weight(141, 560));
+        assert_eq!(rate2.to_string(), "1.000 sat/vB");
+        assert!(rate1 > rate2);
+
+        // The second transaction of block 206 has two inputs.
+        let other = chainman
+            .read_block_data(&active_chain.at_height(206).unwrap())
+            .unwrap();
+        let two_inputs = other.transaction(1).unwrap();
+        let one_coin = spent_outputs.transaction_spent_outputs(0).unwrap();
+        assert!(matches!(
+            two_inputs.fee(&one_coin),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+    }
+
+    #[test]
+    fn test_validate_coinbase_value_at_halving() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let active_chain = chainman.active_chain();
+
+        // Regtest halves every 150 blocks; neither block has fees.
+        let last_full = chainman
+            .read_block_data(&active_chain.at_height(149).unwrap())
+            .unwrap();
+        let first_halved = chainman
+            .read_block_data(&active_chain.at_height(150).unwrap())
+            .unwrap();
+        assert_eq!(block_subsidy(149, ChainType::Regtest), 50 * COIN);
+        assert_eq!(block_subsidy(150, ChainType::Regtest), 25 * COIN);
+        assert!(last_full.validate_coinbase_value(149, 0, ChainType::Regtest));
+        assert!(first_halved.validate_coinbase_value(150, 0, ChainType::Regtest));
+        // Claiming the pre-halving subsidy after the halving is invalid.
+        assert!(!last_full.validate_coinbase_value(150, 0, ChainType::Regtest));
+
+        let block_index = active_chain.at_height(202).unwrap();
+        let block = chainman.read_block_data(&block_index).unwrap();
+        let spent_outputs = chainman.read_spent_outputs(&block_index).unwrap();
+        let fees: i64 = block
+            .transactions()
+            .skip(1)
+            .zip(spent_outputs.iter())
+            .map(|(tx, spent)| tx.fee(&spent).unwrap())
+            .sum();
+        assert!(block.validate_coinbase_value(202, fees, ChainType::Regtest));
+        assert!(!block.validate_coinbase_value(202, fees - 1, ChainType::Regtest));
+    }
+
+    #[test]
+    fn test_spent_outputs_coin_provider() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let active_chain = chainman.active_chain();
+        let block_index = active_chain.at_height(202).unwrap();
+        let block = chainman.read_block_data(&block_index).unwrap();
+        let spent_outputs = chainman.read_spent_outputs(&block_index).unwrap();
+
+        let provider = SpentOutputsCoinProvider::new(&block, &spent_outputs).unwrap();
+        // Usable as a trait object.
+        let provider_dyn: &dyn CoinProvider = &provider;
+        let expected: usize = spent_outputs.iter().map(|tx_spent| tx_spent.count()).sum();
+        assert_eq!(provider.len(), expected);
+        assert!(!provider.is_empty());
+
+        for tx in block.transactions().skip(1) {
+            let outputs: Vec<TxOut> = tx
+                .inputs()
+                .map(|input| {
+                    provider_dyn
+                        .coin(input.outpoint())
+                        .unwrap()
+                        .output()
+                        .to_owned()
+                })
+                .collect();
+            let tx_data = PrecomputedTransactionData::new(&tx, &outputs).unwrap();
+            for (index, output) in outputs.iter().enumerate() {
+                verify(
+                    &output.script_pubkey(),
+                    Some(output.value()),
+                    &tx,
+                    index,
+                    Some(VERIFY_ALL_PRE_TAPROOT),
+                    &tx_data,
+                )
+                .unwrap();
+            }
+        }
+
+        let coinbase = block.transaction(0).unwrap();
+        assert!(provider
+            .coin(coinbase.input(0).unwrap().outpoint())
+            .is_none());
+
+        let coinbase_only = chainman
+            .read_block_data(&active_chain.at_height(201).unwrap())
+            .unwrap();
+        assert!(matches!(
+            SpentOutputsCoinProvider::new(&coinbase_only, &spent_outputs),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+    }
+
+    #[test]
+    fn test_utxo_map_apply_blocks() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let mut utxos = UtxoMap::new();
+        let mut created = 0;
+        let mut spent = 0;
+        for entry in chainman.active_chain().iter().skip(1) {
+            let block = chainman.read_block_data(&entry).unwrap();
+            let spent_outputs = chainman.read_spent_outputs(&entry).unwrap();
+
+            // Every coin the block spends must be tracked with matching data.
+            for (tx, tx_spent) in block.transactions().skip(1).zip(spent_outputs.iter()) {
+                for (input, coin) in tx.inputs().zip(tx_spent.coins()) {
+                    let utxo = utxos.get(&input.outpoint()).unwrap();
+                    assert_eq!(utxo.height(), coin.confirmation_height());
+                    assert_eq!(utxo.is_coinbase(), coin.is_coinbase());
+                    assert_eq!(utxo.output().value(), coin.output().value());
+                    spent += 1;
+                }
+            }
+
+            utxos
+                .apply_block(&block, entry.height() as u32, &spent_outputs)
+                .unwrap();
+
+            for tx in block.transactions() {
+                created += tx
+                    .outputs()
+                    .filter(|output| output.script_pubkey().as_bytes().first() != Some(&0x6a))
+                    .count();
+            }
+        }
+        assert_eq!(utxos.len(), created - spent);
+
+        // Mismatched spent outputs leave the map unchanged.
+        let chain = chainman.active_chain();
+        let block = chainman
+            .read_block_data(&chain.at_height(202).unwrap())
+            .unwrap();
+        let spent_outputs = chainman
+            .read_spent_outputs(&chain.at_height(206).unwrap())
+            .unwrap();
+        let len = utxos.len();
+        assert!(matches!(
+            utxos.apply_block(&block, 202, &spent_outputs),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+        assert_eq!(utxos.len(), len);
+    }
+
+    #[test]
+    fn test_utxo_map_apply_same_block_spend() {
+        // A block with a coinbase paying OP_TRUE, a transaction creating an
+        // OP_TRUE and an OP_RETURN output, a transaction spending the first,
+        // and a transaction spending an unknown output to OP_RETURN.
+        const SAME_BLOCK_SPEND_HEX: &str = "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000401000000010000000000000000000000000000000000000000000000000000000000000000ffffffff02010affffffff0100f2052a01000000015100000000010000000111111111111111111111111111111111111111111111111111111111111111110000000000ffffffff02e80300000000000001510000000000000000016a0000000001000000017e0582282c8bd7df9287b10b5073b123f6c8e8fe8804d0fdfb8eb0fcc494a5500000000000ffffffff018403000000000000015100000000010000000122222222222222222222222222222222222222222222222222222222222222220000000000ffffffff010000000000000000016a00000000";
+
+        let (context, temp_dir) = testing_setup();
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let block = Block::new(&hex::decode(SAME_BLOCK_SPEND_HEX).unwrap()).unwrap();
+        let coinbase = block.transaction(0).unwrap();
+        let creating = block.transaction(1).unwrap();
+        let spending = block.transaction(2).unwrap();
+
+        // `apply_block` only checks that the spent outputs have one coin per
+        // input. Block 205 has three single-input transactions, like this one.
+        let spent_outputs = chainman
+            .read_spent_outputs(&chainman.active_chain().at_height(205).unwrap())
+            .unwrap();
+
+        let mut utxos = UtxoMap::new();
+        utxos.apply_block(&block, 10, &spent_outputs).unwrap();
+
+        // Only the coinbase output and the final spend remain.
+        assert_eq!(utxos.len(), 2);
+        let created_by = |txid: [u8; 32]| {
+            utxos
+                .iter()
+                .find(|(outpoint, _)| outpoint.txid() == txid)
+                .map(|(outpoint, entry)| (outpoint.index(), entry))
+        };
+
+        let (index, coinbase_entry) = created_by(coinbase.txid().to_bytes()).unwrap();
+        assert_eq!(index, 0);
+        assert!(coinbase_entry.is_coinbase());
+        assert_eq!(coinbase_entry.height(), 10);
+        assert_eq!(coinbase_entry.output().value(), 50 * COIN);
+
+        let input = spending.input(0).unwrap();
+        let spent_in_block = input.outpoint();
+        assert!(UtxoOutPoint::from(&spent_in_block) == spent_in_block);
+        assert_eq!(spent_in_block.txid().to_bytes(), creating.txid().to_bytes());
+        assert!(!utxos.contains(&spent_in_block));
+        assert!(created_by(creating.txid().to_bytes()).is_none());
+
+        let (index, final_entry) = created_by(spending.txid().to_bytes()).unwrap();
+        assert_eq!(index, 0);
+        assert!(!final_entry.is_coinbase());
+        assert_eq!(final_entry.output().value(), 900);
+    }
+
+    /// Value, script, height and coinbase flag of each unspent output.
+    type UtxoSnapshot = HashMap<UtxoOutPoint, (i64, Vec<u8>, u32, bool)>;
+
+    fn utxo_snapshot(utxos: &UtxoMap) -> UtxoSnapshot {
+        utxos
+            .iter()
+            .map(|(outpoint, entry)| {
+                (
+                    *outpoint,
+                    (
+                        entry.output().value(),
+                        entry.output().script_pubkey().to_bytes(),
+                        entry.height(),
+                        entry.is_coinbase(),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_utxo_map_revert_blocks() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+        let chain = chainman.active_chain();
+        let tip_height = chain.height();
+
+        let mut utxos = UtxoMap::new();
+        let mut snapshots = Vec::new();
+        for (height, entry) in chain.iter_with_height().skip(1) {
+            snapshots.push(utxo_snapshot(&utxos));
+            let block = chainman.read_block_data(&entry).unwrap();
+            let spent_outputs = chainman.read_spent_outputs(&entry).unwrap();
+            utxos
+                .apply_block(&block, height as u32, &spent_outputs)
+                .unwrap();
+        }
+
+        let tip_snapshot = utxo_snapshot(&utxos);
+
+        // Disconnect blocks from the tip, checking each revert restores the
+        // map from before the block was applied.
+        for height in (tip_height - 10..=tip_height).rev() {
+            let entry = chain.at_height(height as usize).unwrap();
+            let block = chainman.read_block_data(&entry).unwrap();
+            let spent_outputs = chainman.read_spent_outputs(&entry).unwrap();
+            utxos.revert_block(&block, &spent_outputs).unwrap();
+            assert_eq!(utxo_snapshot(&utxos), snapshots[height as usize - 1]);
+        }
+
+        // Reconnecting the same blocks reaches the same tip state.
+        for height in tip_height - 10..=tip_height {
+            let entry = chain.at_height(height as usize).unwrap();
+            let block = chainman.read_block_data(&entry).unwrap();
+            let spent_outputs = chainman.read_spent_outputs(&entry).unwrap();
+            utxos
+                .apply_block(&block, height as u32, &spent_outputs)
+                .unwrap();
+        }
+        assert_eq!(utxo_snapshot(&utxos), tip_snapshot);
+
+        // Mismatched spent outputs leave the map unchanged.
+        let block = chainman
+            .read_block_data(&chain.at_height(202).unwrap())
+            .unwrap();
+        let spent_outputs = chainman
+            .read_spent_outputs(&chain.at_height(206).unwrap())
+            .unwrap();
+        let before = utxo_snapshot(&utxos);
+        assert!(matches!(
+            utxos.revert_block(&block, &spent_outputs),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+        assert_eq!(utxo_snapshot(&utxos), before);
+    }
+
+    #[test]
+    fn test_outpoint_hash() {
+        let block = Block::new(read_block_data()[205].as_slice()).unwrap();
+        let tx = block.transaction(1).unwrap();
+        let first = tx.input(0).unwrap().outpoint().to_owned();
+        let second = tx.input(1).unwrap().outpoint().to_owned();
+
+        assert_eq!(first, first.clone());
+        assert_ne!(first, second);
+
+        let outpoints: HashSet<TxOutPoint> = [first.clone(), first.clone(), second].into();
+        assert_eq!(outpoints.len(), 2);
+        assert!(outpoints.contains(&first));
+
+        let txids: HashSet<Txid> = [first.txid().to_owned(), first.txid().to_owned()].into();
+        assert_eq!(txids.len(), 1);
+    }
+
+    #[test]
+    fn test_spent_outputs_matches_block() {
+        let (context, temp_dir) = testing_setup();
+
+        let chainman = setup_chainman_with_blocks(&context, &temp_dir).unwrap();
+
+        let active_chain = chainman.active_chain();
+        let read = |height| {
+            let entry = active_chain.at_height(height).unwrap();
+            (
+                chainman.read_block_data(&entry).unwrap(),
+                chainman.read_spent_outputs(&entry).unwrap(),
+            )
+        };
+        let (block_202, spent_202) = read(202);
+        let (block_204, spent_204) = read(204);
+        let (_, spent_206) = read(206);
+
+        spent_202.matches_block(&block_202).unwrap();
+        spent_204.matches_block(&block_204).unwrap();
+
+        // Block 202 has more transactions than block 206.
+        assert!(matches!(
+            spent_206.matches_block(&block_202),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+        // Blocks 204 and 206 have the same transaction count, but block 206's
+        // transaction spends two coins.
+        assert!(matches!(
+            spent_206.matches_block(&block_204),
+            Err(KernelError::MismatchedOutputsSize)
+        ));
+    }
+
     fn verify_test(
         spent: &str,
         spending: &str,